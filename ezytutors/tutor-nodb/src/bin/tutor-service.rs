@@ -2,6 +2,8 @@ use actix_web::{web, App, HttpServer};
 use std::io;
 use std::sync::Mutex;
 
+#[path = "../cors.rs"]
+mod cors;
 #[path = "../handlers.rs"]
 mod handlers;
 #[path = "../models.rs"]
@@ -11,6 +13,7 @@ mod routes;
 #[path = "../state.rs"]
 mod state;
 
+use cors::CorsConfig;
 use routes::*;
 use state::AppState;
 
@@ -22,9 +25,11 @@ async fn main() -> io::Result<()> {
         visit_count: Mutex::new(0),
         courses: Mutex::new(vec![]), // Mutex로 보호된 빈 vector로 초기화
     });
+    let cors_config = CorsConfig::from_env(); // 다른 오리진의 프론트엔드에서 오는 요청 허용 범위
     let app = move || { // 웹 애플리케이션 정의
         App::new()
             .app_data(shared_data.clone()) // 웹 애플리케이션 상태 등록
+            .wrap(cors_config.build()) // 프리플라이트(OPTIONS) 응답과 CORS 헤더 추가
             .configure(general_routes)
             .configure(course_routes) // 새로운 course_routes 그룹을 애플리케이션에 등록
     };
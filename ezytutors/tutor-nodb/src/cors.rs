@@ -0,0 +1,128 @@
+use actix_cors::Cors;
+use std::env;
+
+// 다른 오리진의 프론트엔드가 /courses를 호출할 수 있도록 허용하는 CORS 설정
+// 환경변수로 덮어쓸 수 있음(목록은 쉼표로 구분, CORS_ALLOWED_ORIGINS의 "*"는 모든 오리진)
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>, // CORS_ALLOWED_ORIGINS
+    pub allowed_methods: Vec<String>, // CORS_ALLOWED_METHODS
+    pub allowed_headers: Vec<String>, // CORS_ALLOWED_HEADERS
+    pub allow_credentials: bool, // CORS_ALLOW_CREDENTIALS, 쿠키나 Authorization 헤더를 담은 요청 허용
+    pub max_age: usize, // CORS_MAX_AGE, 브라우저가 프리플라이트 결과를 캐시하는 시간(초)
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: vec!["http://localhost:8080".to_string()],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+            allowed_headers: vec!["Content-Type".to_string(), "Authorization".to_string()],
+            allow_credentials: false,
+            max_age: 3600,
+        }
+    }
+}
+
+impl CorsConfig {
+    pub fn from_env() -> CorsConfig {
+        let mut config = CorsConfig::default();
+        let list = |key: &str| {
+            env::var(key).ok().map(|value| {
+                value
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<String>>()
+            })
+        };
+        if let Some(origins) = list("CORS_ALLOWED_ORIGINS") {
+            config.allowed_origins = origins;
+        }
+        if let Some(methods) = list("CORS_ALLOWED_METHODS") {
+            config.allowed_methods = methods;
+        }
+        if let Some(headers) = list("CORS_ALLOWED_HEADERS") {
+            config.allowed_headers = headers;
+        }
+        if let Ok(value) = env::var("CORS_ALLOW_CREDENTIALS") {
+            config.allow_credentials = value == "true";
+        }
+        if let Some(max_age) = env::var("CORS_MAX_AGE").ok().and_then(|v| v.parse().ok()) {
+            config.max_age = max_age;
+        }
+        config
+    }
+
+    // App::wrap()에 등록할 미들웨어, 프리플라이트(OPTIONS) 요청은 미들웨어가 바로 응답함
+    // App을 만들 때마다(워커마다) 호출됨
+    pub fn build(&self) -> Cors {
+        let mut cors = Cors::default()
+            .allowed_methods(self.allowed_methods.iter().map(String::as_str))
+            .allowed_headers(self.allowed_headers.iter().map(String::as_str))
+            .max_age(self.max_age);
+        for origin in &self.allowed_origins {
+            cors = if origin == "*" {
+                cors.allow_any_origin()
+            } else {
+                cors.allowed_origin(origin)
+            };
+        }
+        if self.allow_credentials {
+            cors = cors.supports_credentials();
+        }
+        cors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::{header, Method, StatusCode};
+    use actix_web::{test, web, App, HttpResponse};
+
+    #[actix_rt::test]
+    async fn preflight_and_actual_request() {
+        let config = CorsConfig {
+            allow_credentials: true,
+            ..CorsConfig::default()
+        };
+        let app = test::init_service(
+            App::new()
+                .wrap(config.build())
+                .route("/courses/1", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        // 프리플라이트 요청
+        let req = test::TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri("/courses/1")
+            .insert_header((header::ORIGIN, "http://localhost:8080"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "GET"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let headers = resp.headers();
+        assert_eq!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "http://localhost:8080");
+        assert_eq!(headers.get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(), "true");
+        assert_eq!(headers.get(header::ACCESS_CONTROL_MAX_AGE).unwrap(), "3600");
+
+        // 실제 요청에도 CORS 헤더가 붙음
+        let req = test::TestRequest::get()
+            .uri("/courses/1")
+            .insert_header((header::ORIGIN, "http://localhost:8080"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "http://localhost:8080");
+
+        // 허용하지 않은 오리진에는 Access-Control-Allow-Origin을 보내지 않으므로 브라우저가 응답을 막음
+        let req = test::TestRequest::get()
+            .uri("/courses/1")
+            .insert_header((header::ORIGIN, "http://evil.example"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+}
@@ -69,7 +69,7 @@ pub async fn get_courses_for_tutor(
         .filter(|course| course.tutor_id == tutor_id)
         .collect::<Vec<Course>>();
 
-    if filtered_courses.len() > 0 { // 강사의 강의를 찾은 경우
+    if !filtered_courses.is_empty() { // 강사의 강의를 찾은 경우
         HttpResponse::Ok().json(filtered_courses)
     } else { // 강사의 강의를 찾지 못한 경우
         HttpResponse::Ok().json("No courses found for tutor".to_string())
@@ -0,0 +1,28 @@
+#![no_main]
+
+use http::framing::{self, BodyLength, Framing};
+use libfuzzer_sys::fuzz_target;
+
+// 서버가 요청을 읽을 때 쓰는 프레이밍 파서(엄격/비엄격 모두)가 임의의 바이트에도 패닉 없이 결과를 내야 함
+// 받아들인 요청은 소비한 길이가 입력 안에 있어야 하고, 엄격한 모드에서 받은 요청은 비엄격 모드에서도 같은 결과
+// 시드는 parse_request와 같은 코퍼스를 씀(cargo +nightly fuzz run parse_framing corpus/parse_request)
+fuzz_target!(|data: &[u8]| {
+    let strict = framing::parse_request(data, true);
+    let lenient = framing::parse_request(data, false);
+    if let Ok((_, consumed)) = &strict {
+        assert!(*consumed <= data.len());
+        assert_eq!(lenient.as_ref().ok(), strict.as_ref().ok());
+    }
+    if let Ok((_, consumed)) = &lenient {
+        assert!(*consumed <= data.len());
+    }
+
+    // 헤더 부분만 따로 검사해서 바디가 없는 요청이면 전체 파싱도 성공해야 함
+    if let Ok(end) = framing::head_end(data) {
+        if let Ok(head) = std::str::from_utf8(&data[..end]) {
+            if let Ok(Framing { body_length: BodyLength::Fixed(0), .. }) = framing::request_framing(head, true) {
+                assert!(strict.is_ok());
+            }
+        }
+    }
+});
@@ -0,0 +1,29 @@
+#![no_main]
+
+use http::form::FormLimits;
+use http::httprequest::HttpRequest;
+use http::httprequestref::HttpRequestRef;
+use libfuzzer_sys::fuzz_target;
+
+// 임의의 바이트를 요청으로 파싱하고 접근자와 직렬화를 호출해도 패닉이 없어야 함
+// corpus/parse_request의 시드는 잘못된 형식과 요청 스머글링 패턴들
+fuzz_target!(|data: &[u8]| {
+    let req: HttpRequest = String::from_utf8_lossy(data).to_string().into();
+    let _ = req.header("Content-Length");
+    let _ = req.cookies();
+    let _ = req.content_type();
+
+    let limits = FormLimits {
+        max_file_bytes: 4096,
+        ..FormLimits::default()
+    };
+    let _ = req.form(&limits);
+    let _ = req.multipart(data, &limits);
+
+    let _: HttpRequest = String::from(&req).into();
+
+    // 빌린 파싱은 UTF-8로 바꾸지 않은 원본 바이트 그대로
+    let borrowed = HttpRequestRef::parse(data);
+    let _ = borrowed.header("Transfer-Encoding");
+    let _ = HttpRequest::from(&borrowed);
+});
@@ -0,0 +1,633 @@
+use super::httprequest::{HttpRequest, Method, Resource, Version};
+use super::httpresponse::{read_chunked, read_line, HttpResponse, ParseError};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum ClientError {
+    Io(io::Error),
+    Timeout,
+    InvalidUrl(String),
+    InvalidResponse(String),
+    TooManyRedirects(usize),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(e) => write!(f, "통신 실패: {}", e),
+            ClientError::Timeout => write!(f, "응답 시간이 초과되었습니다"),
+            ClientError::InvalidUrl(url) => write!(f, "잘못된 URL입니다: {}", url),
+            ClientError::InvalidResponse(message) => write!(f, "잘못된 응답입니다: {}", message),
+            ClientError::TooManyRedirects(count) => {
+                write!(f, "리다이렉트가 {}번을 넘었습니다", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<ParseError> for ClientError {
+    fn from(e: ParseError) -> Self {
+        ClientError::InvalidResponse(e.to_string())
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => ClientError::Timeout,
+            _ => ClientError::Io(e),
+        }
+    }
+}
+
+// 블로킹 HTTP/1.1 클라이언트
+// 응답을 다 읽은 커넥션은 호스트별로 보관했다가 다음 요청에 재사용(keep-alive)
+pub struct Client {
+    timeout: Duration,
+    max_redirects: usize,
+    max_body_bytes: usize,
+    idle: Mutex<HashMap<String, Vec<BufReader<TcpStream>>>>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client {
+            timeout: Duration::from_secs(30),
+            max_redirects: 5,
+            max_body_bytes: 16 * 1024 * 1024,
+            idle: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Client {
+    pub fn new() -> Client {
+        Client::default()
+    }
+
+    // 연결, 요청 전송, 응답 대기에 각각 적용되는 타임아웃
+    pub fn with_timeout(mut self, timeout: Duration) -> Client {
+        self.timeout = timeout;
+        self
+    }
+
+    // 따라갈 리다이렉트의 최대 횟수, 0이면 리다이렉트 응답을 그대로 리턴
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Client {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    // 응답 바디의 최대 크기, 넘으면 InvalidResponse(서버가 보낸 Content-Length나 청크 크기를 그대로 믿지 않음)
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Client {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn get(&self, url: &str) -> Result<HttpResponse<'static>, ClientError> {
+        self.send(request(Method::Get, url)?)
+    }
+
+    pub fn post(
+        &self,
+        url: &str,
+        content_type: &str,
+        body: &str,
+    ) -> Result<HttpResponse<'static>, ClientError> {
+        let mut req = request(Method::Post, url)?;
+        set_header(&mut req.headers, "Content-Type", content_type);
+        req.msg_body = body.to_string();
+        self.send(req)
+    }
+
+    // Host 헤더의 주소로 요청을 보내고, 리다이렉트 응답이면 Location을 따라감
+    pub fn send(&self, mut req: HttpRequest) -> Result<HttpResponse<'static>, ClientError> {
+        let mut redirects = 0;
+        loop {
+            let resp = self.send_once(&req)?;
+            let status = resp.status_code().to_string();
+            let location = match status.as_str() {
+                "301" | "302" | "303" | "307" | "308" => resp.header("Location"),
+                _ => None,
+            };
+            let location = match location {
+                Some(location) if self.max_redirects > 0 => location.to_string(),
+                _ => return Ok(resp),
+            };
+            if redirects == self.max_redirects {
+                return Err(ClientError::TooManyRedirects(redirects));
+            }
+            redirects += 1;
+
+            // 상대 경로면 같은 호스트, 절대 URL이면 새 호스트로 요청
+            let (host, path) = if location.starts_with('/') {
+                (header(&req, "Host").unwrap_or("").to_string(), location)
+            } else {
+                parse_url(&location)?
+            };
+            // 다른 호스트로 가는 리다이렉트에는 호출한 쪽이 넣은 인증 정보와 쿠키를 보내지 않음
+            if !host.eq_ignore_ascii_case(header(&req, "Host").unwrap_or("")) {
+                req.headers.retain(|k, _| {
+                    !["Authorization", "Proxy-Authorization", "Cookie"]
+                        .iter()
+                        .any(|name| k.trim().eq_ignore_ascii_case(name))
+                });
+            }
+            set_header(&mut req.headers, "Host", &host);
+            req.resource = Resource::Path(path);
+
+            // 307/308은 메서드와 바디를 유지하고, 나머지는 GET으로 바꿔서 요청
+            if matches!(status.as_str(), "301" | "302" | "303") && req.method != Method::Head {
+                req.method = Method::Get;
+                req.msg_body.clear();
+                req.headers.retain(|k, _| !k.eq_ignore_ascii_case("Content-Type"));
+            }
+        }
+    }
+
+    fn send_once(&self, req: &HttpRequest) -> Result<HttpResponse<'static>, ClientError> {
+        let host = header(req, "Host")
+            .filter(|h| !h.is_empty())
+            .ok_or_else(|| ClientError::InvalidUrl("Host 헤더가 없습니다".to_string()))?;
+        let addr = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{}:80", host)
+        };
+        let bytes = String::from(req);
+        let head_request = req.method == Method::Head;
+
+        // 유휴 커넥션을 먼저 사용하고, 보내는 사이에 서버가 닫았으면 새 커넥션으로 다시 시도
+        // 서버가 요청을 처리했는지 알 수 없으므로 다시 보내는 것은 멱등 메서드만
+        if let Some(mut conn) = self.take_idle(&addr) {
+            match exchange(&mut conn, bytes.as_bytes(), head_request, self.max_body_bytes) {
+                Ok(Some((resp, reusable))) => {
+                    if reusable {
+                        self.put_idle(addr, conn);
+                    }
+                    return Ok(resp);
+                }
+                Err(ClientError::Timeout) => return Err(ClientError::Timeout),
+                Ok(None) | Err(_) if is_idempotent(&req.method) => {}
+                Ok(None) => return Err(closed_without_response()),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut conn = self.connect(&addr)?;
+        match exchange(&mut conn, bytes.as_bytes(), head_request, self.max_body_bytes)? {
+            Some((resp, reusable)) => {
+                if reusable {
+                    self.put_idle(addr, conn);
+                }
+                Ok(resp)
+            }
+            None => Err(closed_without_response()),
+        }
+    }
+
+    fn connect(&self, addr: &str) -> Result<BufReader<TcpStream>, ClientError> {
+        let mut last_error = io::Error::from(io::ErrorKind::AddrNotAvailable);
+        for socket_addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&socket_addr, self.timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(self.timeout))?;
+                    stream.set_write_timeout(Some(self.timeout))?;
+                    return Ok(BufReader::new(stream));
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error.into())
+    }
+
+    // 유휴 중에 서버가 닫은 커넥션은 버리고 열려 있는 커넥션을 꺼냄
+    fn take_idle(&self, addr: &str) -> Option<BufReader<TcpStream>> {
+        let mut idle = self.idle.lock().unwrap();
+        let conns = idle.get_mut(addr)?;
+        while let Some(conn) = conns.pop() {
+            if is_open(&conn) {
+                return Some(conn);
+            }
+        }
+        None
+    }
+
+    fn put_idle(&self, addr: String, conn: BufReader<TcpStream>) {
+        self.idle.lock().unwrap().entry(addr).or_default().push(conn);
+    }
+}
+
+// http://host[:port]/path 형식의 URL을 Host 헤더 값과 경로로 나눔
+fn parse_url(url: &str) -> Result<(String, String), ClientError> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| ClientError::InvalidUrl(url.to_string()))?;
+    let (host, path) = match rest.find(['/', '?']) {
+        Some(i) if rest[i..].starts_with('?') => (&rest[..i], format!("/{}", &rest[i..])),
+        Some(i) => (&rest[..i], rest[i..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+    if host.is_empty() {
+        return Err(ClientError::InvalidUrl(url.to_string()));
+    }
+    Ok((host.to_string(), path))
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(method, Method::Get | Method::Head | Method::Put | Method::Delete | Method::Options)
+}
+
+fn closed_without_response() -> ClientError {
+    ClientError::InvalidResponse("응답 없이 연결이 닫혔습니다".to_string())
+}
+
+// 읽을 데이터가 없어서 기다려야 하면 열려 있는 커넥션(닫혔으면 EOF, 요청하지 않은 데이터가 있어도 재사용하지 않음)
+fn is_open(conn: &BufReader<TcpStream>) -> bool {
+    let stream = conn.get_ref();
+    if !conn.buffer().is_empty() || stream.set_nonblocking(true).is_err() {
+        return false;
+    }
+    let open = matches!(stream.peek(&mut [0; 1]), Err(e) if e.kind() == io::ErrorKind::WouldBlock);
+    stream.set_nonblocking(false).is_ok() && open
+}
+
+fn request(method: Method, url: &str) -> Result<HttpRequest, ClientError> {
+    let (host, path) = parse_url(url)?;
+    let mut headers = HashMap::new();
+    headers.insert("Host".to_string(), host);
+    Ok(HttpRequest {
+        method,
+        version: Version::V1_1,
+        resource: Resource::Path(path),
+        headers,
+        msg_body: String::new(),
+    })
+}
+
+fn header<'r>(req: &'r HttpRequest, name: &str) -> Option<&'r str> {
+    req.headers
+        .iter()
+        .find(|(k, _)| k.trim().eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.trim())
+}
+
+// 대소문자만 다른 기존 헤더를 지우고 새 값으로 설정
+fn set_header(headers: &mut HashMap<String, String>, name: &str, value: &str) {
+    headers.retain(|k, _| !k.trim().eq_ignore_ascii_case(name));
+    headers.insert(name.to_string(), value.to_string());
+}
+
+// 요청을 보내고 응답 하나를 읽음
+// 응답 바이트를 하나도 받기 전에 연결이 닫혔으면 None, 그 외에는 응답과 커넥션 재사용 가능 여부
+fn exchange(
+    conn: &mut BufReader<TcpStream>,
+    bytes: &[u8],
+    head_request: bool,
+    max_body_bytes: usize,
+) -> Result<Option<(HttpResponse<'static>, bool)>, ClientError> {
+    conn.get_mut().write_all(bytes)?;
+    conn.get_mut().flush()?;
+
+    // 100 Continue 같은 중간 응답은 건너뜀
+    let mut resp = loop {
+        if conn.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut head = String::new();
+        loop {
+            let line = read_line(conn)?;
+            if line.is_empty() {
+                break;
+            }
+            head.push_str(&line);
+            head.push_str("\r\n");
+        }
+        let resp = HttpResponse::parse_head(&head)?;
+        if !resp.status_code().starts_with('1') || resp.status_code() == "101" {
+            break resp;
+        }
+    };
+
+    // 바디 길이: HEAD 응답과 204/304는 바디 없음 -> chunked -> Content-Length -> 연결이 닫힐 때까지
+    // 어느 경우든 max_body_bytes까지만 읽음
+    let too_large = || ClientError::InvalidResponse(format!("응답 바디가 {}바이트를 넘습니다", max_body_bytes));
+    let mut framed = true;
+    let body = if head_request || resp.status_code() == "204" || resp.status_code() == "304" {
+        Vec::new()
+    } else if resp.is_chunked() {
+        read_chunked(conn, max_body_bytes).map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidData => ClientError::InvalidResponse(e.to_string()),
+            _ => e.into(),
+        })?
+    } else if let Some(length) = resp.content_length()? {
+        if length > max_body_bytes {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        conn.take(length as u64).read_to_end(&mut body)?;
+        if body.len() < length {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        body
+    } else {
+        framed = false;
+        let mut body = Vec::new();
+        conn.take(max_body_bytes as u64 + 1).read_to_end(&mut body)?;
+        if body.len() > max_body_bytes {
+            return Err(too_large());
+        }
+        body
+    };
+
+    // HTTP/1.1은 Connection: close가 없으면, HTTP/1.0은 keep-alive가 있으면 재사용
+    let connection = resp.header("Connection").unwrap_or("").to_ascii_lowercase();
+    let reusable = framed
+        && match resp.version() {
+            "HTTP/1.1" => !connection.contains("close"),
+            _ => connection.contains("keep-alive"),
+        };
+
+    resp.set_body(Some(String::from_utf8_lossy(&body).into_owned()));
+    Ok(Some((resp, reusable)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // 테스트 서버에서 요청 헤더 부분을 빈 행까지 읽음
+    fn read_request_head(reader: &mut BufReader<TcpStream>) -> String {
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            head.push_str(&line);
+            if line == "\r\n" || line.is_empty() {
+                return head;
+            }
+        }
+    }
+
+    fn client() -> Client {
+        Client::new().with_timeout(Duration::from_secs(2))
+    }
+
+    #[test]
+    fn test_keep_alive_reuses_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // 커넥션을 하나만 받으므로 두 번째 요청도 같은 커넥션으로 와야 함
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut stream = stream;
+            let first = read_request_head(&mut reader);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nX-Test: a\r\nX-Test: b\r\n\r\nfirst")
+                .unwrap();
+            let second = read_request_head(&mut reader);
+            let mut body = vec![0; 4];
+            reader.read_exact(&mut body).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\n\r\n3;ext=1\r\nsec\r\n3\r\nond\r\n0\r\nTrailer: x\r\n\r\n")
+                .unwrap();
+            (first, second, body)
+        });
+
+        let client = client();
+        let resp = client.get(&format!("http://{}/first", addr)).unwrap();
+        assert_eq!(resp.status_code(), "200");
+        assert_eq!(resp.body(), "first");
+        assert_eq!(resp.header("x-test"), Some("a, b"));
+
+        let resp = client
+            .post(&format!("http://{}/second?x=1", addr), "text/plain", "data")
+            .unwrap();
+        assert_eq!(resp.status_code(), "201");
+        assert_eq!(resp.status_text(), "Created");
+        assert_eq!(resp.body(), "second");
+
+        let (first, second, body) = server.join().unwrap();
+        assert!(first.starts_with("GET /first HTTP/1.1\r\n"));
+        assert!(first.contains(&format!("Host:{}\r\n", addr)));
+        assert!(second.starts_with("POST /second?x=1 HTTP/1.1\r\n"));
+        assert!(second.contains("Content-Length:4\r\n"));
+        assert!(second.contains("Content-Type:text/plain\r\n"));
+        assert_eq!(body, b"data");
+    }
+
+    #[test]
+    fn test_follows_redirect_on_new_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in [
+                "HTTP/1.1 303 See Other\r\nLocation: /done\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                "HTTP/1.0 200 OK\r\n\r\nredirected",
+            ] {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                requests.push(read_request_head(&mut reader));
+                let mut stream = stream;
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+
+        let resp = client()
+            .post(&format!("http://{}/orders", addr), "application/json", "{}")
+            .unwrap();
+        assert_eq!(resp.body(), "redirected");
+
+        // 303 리다이렉트는 바디 없이 GET으로 다시 요청
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /orders HTTP/1.1\r\n"));
+        assert!(requests[1].starts_with("GET /done HTTP/1.1\r\n"));
+        assert!(!requests[1].contains("Content-Type"));
+    }
+
+    #[test]
+    fn test_cross_host_redirect_drops_credentials() {
+        let target = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target_addr = target.local_addr().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            let responses = [
+                (&listener, "HTTP/1.1 302 Found\r\nLocation: /moved\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()),
+                (
+                    &listener,
+                    format!("HTTP/1.1 302 Found\r\nLocation: http://{}/other\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", target_addr),
+                ),
+                (&target, "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok".to_string()),
+            ];
+            for (listener, response) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                requests.push(read_request_head(&mut reader));
+                let mut stream = stream;
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+
+        let client = client();
+        let mut req = request(Method::Get, &format!("http://{}/start", addr)).unwrap();
+        req.headers.insert("authorization".to_string(), "Bearer secret".to_string());
+        req.headers.insert("Cookie".to_string(), "sid=abc".to_string());
+        req.headers.insert("Accept".to_string(), "text/plain".to_string());
+        assert_eq!(client.send(req).unwrap().body(), "ok");
+
+        // 같은 호스트 안의 리다이렉트는 그대로 보내고, 다른 호스트로 가면 인증 정보와 쿠키만 뺌
+        let requests = server.join().unwrap();
+        assert!(requests[1].contains("authorization:Bearer secret\r\n"));
+        assert!(requests[1].contains("Cookie:sid=abc\r\n"));
+        assert!(requests[2].starts_with("GET /other HTTP/1.1\r\n"));
+        assert!(!requests[2].contains("secret"));
+        assert!(!requests[2].contains("sid=abc"));
+        assert!(requests[2].contains("Accept:text/plain\r\n"));
+    }
+
+    #[test]
+    fn test_stale_connection_retry() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // 첫 번째 커넥션: 응답 후 유휴 중에 닫음 -> 두 번째 커넥션: 요청을 읽고 응답 없이 닫음
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            read_request_head(&mut reader);
+            let mut stream = stream;
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").unwrap();
+            drop((stream, reader));
+
+            let mut requests = Vec::new();
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            requests.push(read_request_head(&mut reader));
+            let mut stream = stream;
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").unwrap();
+            requests.push(read_request_head(&mut reader));
+            drop((stream, reader));
+
+            // POST는 다시 보내지 않아야 함
+            listener.set_nonblocking(true).unwrap();
+            thread::sleep(Duration::from_millis(200));
+            (requests, listener.accept().is_err())
+        });
+
+        let client = client();
+        let url = format!("http://{}/orders", addr);
+        assert_eq!(client.get(&url).unwrap().status_code(), "200");
+        thread::sleep(Duration::from_millis(100));
+        // 닫힌 유휴 커넥션은 보내기 전에 버리고 새 커넥션으로 보냄
+        assert_eq!(client.post(&url, "text/plain", "").unwrap().status_code(), "200");
+        let result = client.post(&url, "text/plain", "");
+        assert!(matches!(result, Err(ClientError::InvalidResponse(_))));
+
+        let (requests, no_retry) = server.join().unwrap();
+        assert!(requests.iter().all(|r| r.starts_with("POST /orders HTTP/1.1\r\n")));
+        assert!(no_retry);
+    }
+
+    #[test]
+    fn test_redirect_limit() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut stream = stream;
+                while !read_request_head(&mut reader).is_empty() {
+                    let _ = stream.write_all(b"HTTP/1.1 302 Found\r\nLocation: /loop\r\nContent-Length: 0\r\n\r\n");
+                }
+            }
+        });
+
+        let url = format!("http://{}/loop", addr);
+        let result = client().with_max_redirects(2).get(&url);
+        assert!(matches!(result, Err(ClientError::TooManyRedirects(2))));
+        let resp = client().with_max_redirects(0).get(&url).unwrap();
+        assert_eq!(resp.status_code(), "302");
+    }
+
+    #[test]
+    fn test_max_body_bytes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            for response in [
+                "HTTP/1.1 200 OK\r\nContent-Length: 999999999999999\r\n\r\nabc",
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nfffffffffffffff\r\nabc",
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n8\r\n12345678\r\n8\r\n12345678\r\n0\r\n\r\n",
+                "HTTP/1.0 200 OK\r\n\r\n0123456789abcdefg",
+                "HTTP/1.0 200 OK\r\n\r\n0123456789",
+            ] {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                read_request_head(&mut reader);
+                let mut stream = stream;
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        // 서버가 보낸 길이로 버퍼를 잡지 않고 최대 크기를 넘으면 오류
+        let client = client().with_max_body_bytes(10);
+        let url = format!("http://{}/", addr);
+        for _ in 0..4 {
+            assert!(matches!(client.get(&url), Err(ClientError::InvalidResponse(_))));
+        }
+        assert_eq!(client.get(&url).unwrap().body(), "0123456789");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_read_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            thread::sleep(Duration::from_millis(500));
+            drop(stream);
+        });
+
+        let client = Client::new().with_timeout(Duration::from_millis(100));
+        let result = client.get(&format!("http://{}/", addr));
+        assert!(matches!(result, Err(ClientError::Timeout)));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            parse_url("http://localhost:3000/api/shipping/orders").unwrap(),
+            ("localhost:3000".to_string(), "/api/shipping/orders".to_string())
+        );
+        assert_eq!(
+            parse_url("http://example.com").unwrap(),
+            ("example.com".to_string(), "/".to_string())
+        );
+        assert_eq!(
+            parse_url("http://example.com?q=1").unwrap(),
+            ("example.com".to_string(), "/?q=1".to_string())
+        );
+        assert!(matches!(parse_url("https://example.com/"), Err(ClientError::InvalidUrl(_))));
+        assert!(matches!(parse_url("http:///path"), Err(ClientError::InvalidUrl(_))));
+    }
+}
@@ -0,0 +1,267 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// 요청의 Cookie 헤더에 담긴 쿠키들, 같은 이름이 여러 번 오면 첫 번째 값을 사용
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CookieJar {
+    cookies: Vec<(String, String)>,
+}
+
+impl CookieJar {
+    // "name=value; name2=value2" 형식을 파싱(이름이 없거나 '='가 없는 항목은 무시)
+    pub fn parse(header: &str) -> CookieJar {
+        let cookies = header
+            .split(';')
+            .filter_map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                let name = name.trim();
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                (!name.is_empty()).then(|| (name.to_string(), value.to_string()))
+            })
+            .collect();
+        CookieJar { cookies }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.cookies
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // sign()으로 서명한 쿠키의 원래 값, 서명이 없거나 맞지 않으면 None
+    pub fn get_signed(&self, name: &str, key: &[u8]) -> Option<&str> {
+        verify(name, self.get(name)?, key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.cookies.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+// 응답의 Set-Cookie 헤더 하나를 만드는 빌더
+// 이름과 값은 그대로 쓰므로 ';', ',', 공백 같은 문자는 호출하는 쪽에서 인코딩해야 함
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetCookie {
+    name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    expires: Option<SystemTime>,
+    max_age: Option<Duration>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+impl SetCookie {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> SetCookie {
+        SetCookie {
+            name: name.into(),
+            value: value.into(),
+            path: None,
+            domain: None,
+            expires: None,
+            max_age: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    // 브라우저에 저장된 쿠키를 지우는 Set-Cookie(지울 쿠키와 Path, Domain이 같아야 함)
+    pub fn removal(name: impl Into<String>) -> SetCookie {
+        SetCookie::new(name, "")
+            .expires(UNIX_EPOCH)
+            .max_age(Duration::ZERO)
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> SetCookie {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn domain(mut self, domain: impl Into<String>) -> SetCookie {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    pub fn expires(mut self, expires: SystemTime) -> SetCookie {
+        self.expires = Some(expires);
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> SetCookie {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn secure(mut self, secure: bool) -> SetCookie {
+        self.secure = secure;
+        self
+    }
+
+    pub fn http_only(mut self, http_only: bool) -> SetCookie {
+        self.http_only = http_only;
+        self
+    }
+
+    // SameSite=None은 브라우저가 Secure 쿠키에만 허용하므로 Secure도 함께 설정
+    pub fn same_site(mut self, same_site: SameSite) -> SetCookie {
+        self.same_site = Some(same_site);
+        if same_site == SameSite::None {
+            self.secure = true;
+        }
+        self
+    }
+
+    // 값 뒤에 HMAC-SHA256 서명을 붙임(CookieJar::get_signed()로 확인)
+    pub fn signed(mut self, key: &[u8]) -> SetCookie {
+        self.value = sign(&self.name, &self.value, key);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+// Set-Cookie 헤더 값
+impl fmt::Display for SetCookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+        if let Some(path) = &self.path {
+            write!(f, "; Path={}", path)?;
+        }
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={}", domain)?;
+        }
+        if let Some(expires) = self.expires {
+            write!(f, "; Expires={}", httpdate::fmt_http_date(expires))?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if self.secure {
+            write!(f, "; Secure")?;
+        }
+        if self.http_only {
+            write!(f, "; HttpOnly")?;
+        }
+        match self.same_site {
+            Some(SameSite::Strict) => write!(f, "; SameSite=Strict"),
+            Some(SameSite::Lax) => write!(f, "; SameSite=Lax"),
+            Some(SameSite::None) => write!(f, "; SameSite=None"),
+            None => Ok(()),
+        }
+    }
+}
+
+// "값.서명" 형식, 서명은 "이름=값"의 HMAC-SHA256을 16진수로 쓴 것(다른 쿠키로 옮겨 써도 검증에 실패함)
+pub fn sign(name: &str, value: &str, key: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC은 모든 키 길이를 허용함");
+    mac.update(format!("{}={}", name, value).as_bytes());
+    let signature: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}.{}", value, signature)
+}
+
+// 서명이 맞으면 서명을 뗀 원래 값을 리턴(비교는 상수 시간)
+pub fn verify<'v>(name: &str, signed: &'v str, key: &[u8]) -> Option<&'v str> {
+    let (value, signature) = signed.rsplit_once('.')?;
+    if signature.len() != 64 {
+        return None;
+    }
+    let signature: Vec<u8> = (0..64)
+        .step_by(2)
+        .map(|i| u8::from_str_radix(signature.get(i..i + 2)?, 16).ok())
+        .collect::<Option<_>>()?;
+    let mut mac = HmacSha256::new_from_slice(key).ok()?;
+    mac.update(format!("{}={}", name, value).as_bytes());
+    mac.verify_slice(&signature).ok()?;
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cookie_header() {
+        let jar = CookieJar::parse(" sid=abc123; theme=\"dark\"; flag; =x; sid=second; empty=");
+        assert_eq!(jar.get("sid"), Some("abc123"));
+        assert_eq!(jar.get("theme"), Some("dark"));
+        assert_eq!(jar.get("empty"), Some(""));
+        assert_eq!(jar.get("flag"), None);
+        assert_eq!(jar.iter().count(), 4);
+        assert!(CookieJar::parse("").is_empty());
+    }
+
+    #[test]
+    fn test_set_cookie_attributes() {
+        let expires = UNIX_EPOCH + Duration::from_secs(1_445_412_480);
+        let cookie = SetCookie::new("sid", "abc")
+            .path("/")
+            .domain("example.com")
+            .expires(expires)
+            .max_age(Duration::from_secs(3600))
+            .http_only(true)
+            .same_site(SameSite::Lax);
+        assert_eq!(
+            cookie.to_string(),
+            "sid=abc; Path=/; Domain=example.com; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=3600; HttpOnly; SameSite=Lax"
+        );
+        assert_eq!(
+            SetCookie::new("a", "b").same_site(SameSite::None).to_string(),
+            "a=b; Secure; SameSite=None"
+        );
+        assert_eq!(
+            SetCookie::removal("sid").path("/").to_string(),
+            "sid=; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Max-Age=0"
+        );
+    }
+
+    #[test]
+    fn test_signed_cookie() {
+        let key = b"secret key";
+        let cookie = SetCookie::new("user", "42.admin").signed(key);
+        assert!(cookie.value().starts_with("42.admin."));
+
+        let jar = CookieJar::parse(&format!("user={}", cookie.value()));
+        assert_eq!(jar.get_signed("user", key), Some("42.admin"));
+        assert_eq!(jar.get_signed("user", b"other key"), None);
+
+        // 값을 바꾸거나 다른 이름의 쿠키로 옮기면 검증에 실패
+        let tampered = cookie.value().replacen("42", "43", 1);
+        assert_eq!(verify("user", &tampered, key), None);
+        assert_eq!(verify("other", cookie.value(), key), None);
+        assert_eq!(verify("user", "42", key), None);
+    }
+}
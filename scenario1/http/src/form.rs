@@ -0,0 +1,571 @@
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// 한 번에 읽는 크기와 파트 헤더 부분의 최대 길이
+const READ_CHUNK: usize = 8 * 1024;
+const MAX_PART_HEADER_BYTES: usize = 8 * 1024;
+
+// 임시 파일 이름이 겹치지 않도록 프로세스 안에서 증가시키는 번호
+static NEXT_TEMP_FILE: AtomicU64 = AtomicU64::new(0);
+
+// 폼 바디를 파싱할 때 적용하는 한도와 파일 파트를 저장할 임시 디렉터리
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormLimits {
+    pub max_field_bytes: usize, // 파일이 아닌 필드 값 하나의 최대 크기
+    pub max_file_bytes: u64,    // 파일 파트 하나의 최대 크기
+    pub max_parts: usize,       // 필드와 파일을 합친 최대 개수
+    pub temp_dir: PathBuf,
+}
+
+impl Default for FormLimits {
+    fn default() -> Self {
+        FormLimits {
+            max_field_bytes: 64 * 1024,
+            max_file_bytes: 10 * 1024 * 1024,
+            max_parts: 100,
+            temp_dir: env::temp_dir(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FormError {
+    UnsupportedMediaType(String),
+    Malformed(&'static str),
+    TooLarge,
+    Io(io::Error),
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormError::UnsupportedMediaType(media) => {
+                write!(f, "폼으로 파싱할 수 없는 Content-Type입니다: {}", media)
+            }
+            FormError::Malformed(message) => write!(f, "잘못된 폼 바디입니다: {}", message),
+            FormError::TooLarge => write!(f, "폼 바디가 한도를 넘었습니다"),
+            FormError::Io(e) => write!(f, "폼 바디를 처리하지 못했습니다: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FormError {}
+
+impl From<io::Error> for FormError {
+    fn from(e: io::Error) -> Self {
+        FormError::Io(e)
+    }
+}
+
+// 파싱한 폼, 같은 이름의 필드가 여러 개일 수 있으므로 순서대로 보관
+#[derive(Debug, Default)]
+pub struct Form {
+    pub fields: Vec<(String, String)>,
+    pub files: Vec<FilePart>,
+}
+
+impl Form {
+    // 이름이 일치하는 첫 번째 필드 값
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // 이름이 일치하는 첫 번째 파일 파트
+    pub fn file(&self, name: &str) -> Option<&FilePart> {
+        self.files.iter().find(|file| file.name == name)
+    }
+}
+
+// 임시 디렉터리에 저장된 파일 파트, persist()로 옮기지 않으면 드롭될 때 삭제됨
+#[derive(Debug)]
+pub struct FilePart {
+    pub name: String,
+    pub filename: Option<String>, // 클라이언트가 보낸 파일 이름(경로가 섞여 있을 수 있으므로 그대로 쓰지 말 것)
+    pub content_type: Option<String>,
+    pub size: u64,
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl FilePart {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 임시 파일을 dest로 옮김(다른 파일 시스템이면 복사한 뒤 삭제)
+    pub fn persist(mut self, dest: &Path) -> io::Result<()> {
+        if fs::rename(&self.path, dest).is_err() {
+            fs::copy(&self.path, dest)?;
+            let _ = fs::remove_file(&self.path);
+        }
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for FilePart {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+// application/x-www-form-urlencoded 바디(또는 쿼리 문자열)를 이름과 값의 목록으로 파싱
+pub fn parse_urlencoded(s: &str) -> Vec<(String, String)> {
+    s.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+// '+'는 공백으로, %XX는 해당 바이트로 바꿈(잘못된 %는 그대로 둠)
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => match (bytes.get(i + 1).and_then(hex), bytes.get(i + 2).and_then(hex)) {
+                (Some(high), Some(low)) => {
+                    out.push(high * 16 + low);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex(b: &u8) -> Option<u8> {
+    (*b as char).to_digit(16).map(|d| d as u8)
+}
+
+// Content-Type 값의 미디어 타입(소문자)과 매개변수
+// 매개변수 값은 따옴표로 감쌀 수 있고 따옴표 안의 ';'는 구분자가 아님
+pub fn parse_media_type(value: &str) -> (String, Vec<(String, String)>) {
+    let mut parts = split_params(value).into_iter();
+    let media_type = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let params = parts
+        .filter_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            Some((name.trim().to_ascii_lowercase(), unquote(value.trim())))
+        })
+        .collect();
+    (media_type, params)
+}
+
+fn split_params(value: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                parts.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        parts.last_mut().unwrap().push(c);
+    }
+    parts
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => {
+            let mut out = String::new();
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => out.extend(chars.next()),
+                    c => out.push(c),
+                }
+            }
+            out
+        }
+        None => value.to_string(),
+    }
+}
+
+// multipart/form-data 바디를 읽으면서 파싱
+// 파일 파트는 메모리에 모으지 않고 읽는 대로 임시 파일에 씀
+pub fn parse_multipart(body: impl Read, boundary: &str, limits: &FormLimits) -> Result<Form, FormError> {
+    if boundary.is_empty() || boundary.len() > 70 {
+        return Err(FormError::Malformed("boundary 길이가 잘못되었습니다"));
+    }
+    let mut reader = PartReader {
+        body,
+        buf: Vec::new(),
+        eof: false,
+    };
+    let mut form = Form::default();
+
+    // 첫 번째 구분자 앞의 프리앰블은 버림
+    let delimiter = format!("--{}", boundary).into_bytes();
+    reader.skip_until(&delimiter)?;
+
+    // 파트 사이의 구분자는 앞의 CRLF까지 포함
+    let delimiter = format!("\r\n--{}", boundary).into_bytes();
+    loop {
+        // 구분자 바로 뒤가 "--"면 마지막 파트
+        reader.fill_to(2)?;
+        if reader.buf.starts_with(b"--") {
+            return Ok(form);
+        }
+        reader.skip_line_end()?;
+
+        if form.fields.len() + form.files.len() >= limits.max_parts {
+            return Err(FormError::TooLarge);
+        }
+        let headers = reader.part_headers()?;
+        let (name, filename, content_type) = part_disposition(&headers)?;
+
+        match filename {
+            Some(filename) => {
+                let mut file = FilePart {
+                    name,
+                    filename: Some(filename).filter(|f| !f.is_empty()),
+                    content_type,
+                    size: 0,
+                    path: temp_path(&limits.temp_dir),
+                    persisted: false,
+                };
+                let mut out = File::options().write(true).create_new(true).open(&file.path)?;
+                reader.copy_part(&delimiter, |chunk| {
+                    file.size += chunk.len() as u64;
+                    if file.size > limits.max_file_bytes {
+                        return Err(FormError::TooLarge);
+                    }
+                    out.write_all(chunk)?;
+                    Ok(())
+                })?;
+                out.flush()?;
+                form.files.push(file);
+            }
+            None => {
+                let mut value = Vec::new();
+                reader.copy_part(&delimiter, |chunk| {
+                    if value.len() + chunk.len() > limits.max_field_bytes {
+                        return Err(FormError::TooLarge);
+                    }
+                    value.extend_from_slice(chunk);
+                    Ok(())
+                })?;
+                let value = String::from_utf8(value)
+                    .map_err(|_| FormError::Malformed("필드 값이 UTF-8이 아닙니다"))?;
+                form.fields.push((name, value));
+            }
+        }
+    }
+}
+
+fn temp_path(dir: &Path) -> PathBuf {
+    let n = NEXT_TEMP_FILE.fetch_add(1, Ordering::Relaxed);
+    dir.join(format!("http-upload-{}-{}", process::id(), n))
+}
+
+// Content-Disposition: form-data의 name, filename과 Content-Type
+fn part_disposition(
+    headers: &[(String, String)],
+) -> Result<(String, Option<String>, Option<String>), FormError> {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    };
+    let disposition = header("Content-Disposition")
+        .ok_or(FormError::Malformed("Content-Disposition 헤더가 없습니다"))?;
+    let (kind, params) = parse_media_type(disposition);
+    if kind != "form-data" {
+        return Err(FormError::Malformed("form-data 파트가 아닙니다"));
+    }
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+    let name = param("name").ok_or(FormError::Malformed("파트에 name이 없습니다"))?;
+    Ok((name, param("filename"), header("Content-Type").map(str::to_string)))
+}
+
+// 바디를 조금씩 읽어서 버퍼에 쌓아 두는 리더
+struct PartReader<R> {
+    body: R,
+    buf: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: Read> PartReader<R> {
+    // 버퍼에 한 번 더 읽어 넣음, 바디가 끝났으면 false
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        let mut chunk = [0; READ_CHUNK];
+        let n = loop {
+            match self.body.read(&mut chunk) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        self.buf.extend_from_slice(&chunk[..n]);
+        self.eof = n == 0;
+        Ok(n > 0)
+    }
+
+    fn fill_to(&mut self, len: usize) -> Result<(), FormError> {
+        while self.buf.len() < len {
+            if !self.fill()? {
+                return Err(FormError::Malformed("바디가 중간에 끝났습니다"));
+            }
+        }
+        Ok(())
+    }
+
+    // needle이 나올 때까지 버리고 needle 뒤부터 남김
+    fn skip_until(&mut self, needle: &[u8]) -> Result<(), FormError> {
+        loop {
+            if let Some(i) = find(&self.buf, needle) {
+                self.buf.drain(..i + needle.len());
+                return Ok(());
+            }
+            let keep = self.buf.len().min(needle.len() - 1);
+            self.buf.drain(..self.buf.len() - keep);
+            if !self.fill()? {
+                return Err(FormError::Malformed("boundary를 찾을 수 없습니다"));
+            }
+        }
+    }
+
+    // 구분자 행의 나머지(공백 패딩)와 CRLF를 건너뜀
+    fn skip_line_end(&mut self) -> Result<(), FormError> {
+        loop {
+            if let Some(i) = find(&self.buf, b"\r\n") {
+                if self.buf[..i].iter().any(|b| !matches!(b, b' ' | b'\t')) {
+                    return Err(FormError::Malformed("boundary 뒤에 잘못된 문자가 있습니다"));
+                }
+                self.buf.drain(..i + 2);
+                return Ok(());
+            }
+            if self.buf.len() > MAX_PART_HEADER_BYTES || !self.fill()? {
+                return Err(FormError::Malformed("boundary 행이 끝나지 않았습니다"));
+            }
+        }
+    }
+
+    // 빈 행까지의 파트 헤더
+    fn part_headers(&mut self) -> Result<Vec<(String, String)>, FormError> {
+        let end = loop {
+            if self.buf.starts_with(b"\r\n") {
+                break 0;
+            }
+            if let Some(i) = find(&self.buf, b"\r\n\r\n") {
+                break i + 2;
+            }
+            if self.buf.len() > MAX_PART_HEADER_BYTES {
+                return Err(FormError::TooLarge);
+            }
+            if !self.fill()? {
+                return Err(FormError::Malformed("파트 헤더가 끝나지 않았습니다"));
+            }
+        };
+        let head = String::from_utf8_lossy(&self.buf[..end]).into_owned();
+        self.buf.drain(..end + 2);
+        Ok(head
+            .split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect())
+    }
+
+    // 다음 구분자 앞까지의 파트 내용을 조각으로 넘기고 구분자는 버림
+    // 버퍼 끝에 걸친 구분자를 놓치지 않도록 구분자 길이만큼은 남겨 두고 넘김
+    fn copy_part(
+        &mut self,
+        delimiter: &[u8],
+        mut write: impl FnMut(&[u8]) -> Result<(), FormError>,
+    ) -> Result<(), FormError> {
+        loop {
+            if let Some(i) = find(&self.buf, delimiter) {
+                write(&self.buf[..i])?;
+                self.buf.drain(..i + delimiter.len());
+                return Ok(());
+            }
+            let safe = self.buf.len().saturating_sub(delimiter.len() - 1);
+            if safe > 0 {
+                write(&self.buf[..safe])?;
+                self.buf.drain(..safe);
+            }
+            if !self.fill()? {
+                return Err(FormError::Malformed("마지막 boundary가 없습니다"));
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 한 번에 한 바이트씩만 돌려주는 리더(버퍼 경계에 걸친 구분자를 검사)
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((b, rest)) if !buf.is_empty() => {
+                    buf[0] = *b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn limits(name: &str) -> FormLimits {
+        let temp_dir = env::temp_dir().join(format!("http-form-{}-{}", name, process::id()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        FormLimits {
+            temp_dir,
+            ..FormLimits::default()
+        }
+    }
+
+    fn multipart_body(file: &[u8]) -> Vec<u8> {
+        let mut body = b"preamble\r\n--XyZ\r\n\
+            Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+            Order #1; \"rush\"\r\n--XyZ  \r\n\
+            Content-Disposition: form-data; name=\"invoice\"; filename=\"a;b.bin\"\r\n\
+            Content-Type: application/octet-stream\r\n\r\n"
+            .to_vec();
+        body.extend_from_slice(file);
+        body.extend_from_slice(b"\r\n--XyZ--\r\nepilogue");
+        body
+    }
+
+    #[test]
+    fn test_parse_urlencoded() {
+        let fields = parse_urlencoded("name=J%C3%BCrgen+Kim&empty=&flag&&bad=%zz%4");
+        assert_eq!(
+            fields,
+            vec![
+                ("name".to_string(), "Jürgen Kim".to_string()),
+                ("empty".to_string(), String::new()),
+                ("flag".to_string(), String::new()),
+                ("bad".to_string(), "%zz%4".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_media_type() {
+        let (media, params) = parse_media_type("Multipart/Form-Data; boundary=\"a;b\\\"c\"; x=1");
+        assert_eq!(media, "multipart/form-data");
+        assert_eq!(
+            params,
+            vec![
+                ("boundary".to_string(), "a;b\"c".to_string()),
+                ("x".to_string(), "1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_multipart_streams_file_parts() {
+        let limits = limits("ok");
+        // 파일 내용에 CRLF와 구분자의 앞부분, 바이너리 바이트가 섞여 있어도 그대로 보존
+        let file: Vec<u8> = [b"\r\n--Xy\r\n-".as_slice(), &[0, 255, 13, 10], b"x--XyZ-"].concat();
+        let body = multipart_body(&file);
+
+        let form = parse_multipart(Trickle(&body), "XyZ", &limits).unwrap();
+        assert_eq!(form.field("title"), Some("Order #1; \"rush\""));
+        let part = form.file("invoice").unwrap();
+        assert_eq!(part.filename.as_deref(), Some("a;b.bin"));
+        assert_eq!(part.content_type.as_deref(), Some("application/octet-stream"));
+        assert_eq!(part.size, file.len() as u64);
+        assert_eq!(fs::read(part.path()).unwrap(), file);
+
+        // persist()로 옮긴 파일은 남고 나머지 임시 파일은 드롭될 때 삭제됨
+        let mut form = form;
+        let dest = limits.temp_dir.join("kept.bin");
+        form.files.remove(0).persist(&dest).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), file);
+        fs::remove_file(dest).unwrap();
+        assert_eq!(fs::read_dir(&limits.temp_dir).unwrap().count(), 0);
+        fs::remove_dir(&limits.temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_multipart_limits_and_errors() {
+        let limits = limits("err");
+        let body = multipart_body(&[7; 100]);
+
+        let small_file = FormLimits {
+            max_file_bytes: 99,
+            ..limits.clone()
+        };
+        assert!(matches!(
+            parse_multipart(&body[..], "XyZ", &small_file),
+            Err(FormError::TooLarge)
+        ));
+        let small_field = FormLimits {
+            max_field_bytes: 4,
+            ..limits.clone()
+        };
+        assert!(matches!(
+            parse_multipart(&body[..], "XyZ", &small_field),
+            Err(FormError::TooLarge)
+        ));
+        let one_part = FormLimits {
+            max_parts: 1,
+            ..limits.clone()
+        };
+        assert!(matches!(
+            parse_multipart(&body[..], "XyZ", &one_part),
+            Err(FormError::TooLarge)
+        ));
+
+        // 마지막 구분자가 없거나 boundary가 다르면 잘못된 바디
+        let truncated = &body[..body.len() - 20];
+        assert!(matches!(
+            parse_multipart(truncated, "XyZ", &limits),
+            Err(FormError::Malformed(_))
+        ));
+        assert!(matches!(
+            parse_multipart(&body[..], "other", &limits),
+            Err(FormError::Malformed(_))
+        ));
+
+        // 실패한 파싱이 남긴 임시 파일은 없어야 함
+        assert_eq!(fs::read_dir(&limits.temp_dir).unwrap().count(), 0);
+        fs::remove_dir(&limits.temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,387 @@
+use super::httprequest::HttpRequest;
+
+// 요청 바디의 길이를 정하는 방식(RFC 9112 6.3)
+#[derive(Debug, PartialEq)]
+pub enum BodyLength {
+    Fixed(usize),
+    Chunked,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Framing {
+    pub body_length: BodyLength,
+    // 프레이밍이 모호했던 요청(strict = false에서 받아들인 경우)이라 응답 후 커넥션을 닫아야 함
+    pub must_close: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FramingError {
+    // 요청이 아직 끝나지 않음(더 읽어야 함)
+    Incomplete,
+    // 바디 길이를 하나로 정할 수 없거나 행 구분이 모호한 요청
+    Malformed,
+    // chunked 외의 전송 코딩(gzip 등)은 지원하지 않음
+    UnsupportedTransferCoding,
+}
+
+// bytes의 맨 앞에서 요청 하나를 엄격한 프레이밍 규칙으로 파싱하고, 소비한 바이트 수를 함께 리턴
+// strict가 false면 CL과 TE가 함께 오거나 토큰이 아닌 이름이 있어도 받아들임(Framing::must_close 참고)
+// From<String>과 달리 바디는 헤더가 정한 길이만큼만 읽으므로 뒤에 남은 바이트는 다음 요청
+pub fn parse_request(bytes: &[u8], strict: bool) -> Result<(HttpRequest, usize), FramingError> {
+    let end = head_end(bytes)?;
+    let head = std::str::from_utf8(&bytes[..end]).map_err(|_| FramingError::Malformed)?;
+    let (body, consumed) = match request_framing(head, strict)?.body_length {
+        BodyLength::Fixed(length) => {
+            let total = end.checked_add(length).ok_or(FramingError::Malformed)?;
+            let body = bytes.get(end..total).ok_or(FramingError::Incomplete)?;
+            (body.to_vec(), total)
+        }
+        BodyLength::Chunked => {
+            let (body, length) = decode_chunked(&bytes[end..])?;
+            (body, end + length)
+        }
+    };
+    let mut req = HttpRequest::from(head.to_string());
+    req.msg_body = String::from_utf8_lossy(&body).into_owned();
+    Ok((req, consumed))
+}
+
+// 헤더 부분(빈 행까지)의 길이, CRLF가 아닌 LF가 나오면 헤더가 끝나기 전이라도 바로 거부(RFC 9112 2.2)
+pub fn head_end(buffer: &[u8]) -> Result<usize, FramingError> {
+    for (i, &b) in buffer.iter().enumerate() {
+        if b != b'\n' {
+            continue;
+        }
+        if i == 0 || buffer[i - 1] != b'\r' {
+            return Err(FramingError::Malformed);
+        }
+        if buffer[..=i].ends_with(b"\r\n\r\n") {
+            return Ok(i + 1);
+        }
+    }
+    Err(FramingError::Incomplete)
+}
+
+// 헤더 부분(요청 행 + 헤더)으로 바디 길이를 정함
+// 두 헤더가 함께 오면 앞단 프록시와 바디 길이를 다르게 해석할 수 있으므로(CL.TE, TE.CL 스머글링) strict에서는 거부
+// 엄격하지 않은 모드에서는 Transfer-Encoding을 따르고 응답 후 커넥션을 닫음(RFC 9112 6.1)
+pub fn request_framing(head: &str, strict: bool) -> Result<Framing, FramingError> {
+    check_lines(head)?;
+    check_target(head)?;
+    if strict {
+        check_tokens(head)?;
+    }
+
+    let both = header_value(head, "Content-Length").is_some() && header_value(head, "Transfer-Encoding").is_some();
+    if both && strict {
+        return Err(FramingError::Malformed);
+    }
+
+    let body_length = if is_chunked(head)? {
+        // HTTP/1.0에는 청크 인코딩이 없으므로 잘못된 요청으로 취급
+        if head.split("\r\n").next().unwrap_or("").ends_with("HTTP/1.0") {
+            return Err(FramingError::Malformed);
+        }
+        BodyLength::Chunked
+    } else {
+        BodyLength::Fixed(content_length(head)?)
+    };
+    Ok(Framing {
+        body_length,
+        must_close: both,
+    })
+}
+
+// 청크 크기 행(크기;확장)의 크기, 16진수 숫자만 허용(부호나 0x 접두사는 거부)
+pub fn chunk_size(line: &str) -> Result<usize, FramingError> {
+    let size = line.split(';').next().unwrap_or("").trim();
+    if size.is_empty() || !size.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(FramingError::Malformed);
+    }
+    usize::from_str_radix(size, 16).map_err(|_| FramingError::Malformed)
+}
+
+// 헤더 부분에서 이름으로 헤더 값을 찾음(대소문자 무시)
+pub fn header_value<'h>(head: &'h str, name: &str) -> Option<&'h str> {
+    header_values(head, name).next()
+}
+
+// 같은 이름의 헤더가 여러 번 오면 모든 값(순서대로)
+fn header_values<'h>(head: &'h str, name: &str) -> impl Iterator<Item = &'h str> {
+    let name = name.to_string();
+    head.split("\r\n").skip(1).filter_map(move |line| {
+        let (key, value) = line.split_once(':')?;
+        if key.trim().eq_ignore_ascii_case(&name) {
+            Some(value.trim())
+        } else {
+            None
+        }
+    })
+}
+
+// 행 구분이 모호한 요청은 거부(RFC 9112 2.2, 5.2)
+// HttpRequest는 LF만으로도 행을 나누므로 CRLF가 아닌 CR이나 LF를 허용하면 여기서 본 헤더와 달라질 수 있음
+fn check_lines(head: &str) -> Result<(), FramingError> {
+    let lines: Vec<&str> = head.trim_end_matches("\r\n").split("\r\n").collect();
+    if lines.iter().any(|line| line.contains(['\r', '\n'])) {
+        return Err(FramingError::Malformed);
+    }
+    // 공백으로 시작하는 행은 앞 헤더 값을 잇는 obs-fold(더 이상 허용되지 않음)
+    if lines.iter().skip(1).any(|line| line.starts_with([' ', '\t'])) {
+        return Err(FramingError::Malformed);
+    }
+    Ok(())
+}
+
+// 요청 대상은 '/'로 시작하는 origin-form이어야 하고, OPTIONS만 서버 전체를 뜻하는 "*"를 쓸 수 있음(RFC 9112 3.2)
+// 핸들러는 경로가 '/'로 시작한다고 가정하므로 "x"나 "http:x", 빈 대상은 설정과 관계없이 거부
+fn check_target(head: &str) -> Result<(), FramingError> {
+    let mut words = head.split("\r\n").next().unwrap_or("").split_whitespace();
+    let method = words.next().unwrap_or("");
+    match words.next().unwrap_or("") {
+        target if target.starts_with('/') => Ok(()),
+        "*" if method == "OPTIONS" => Ok(()),
+        _ => Err(FramingError::Malformed),
+    }
+}
+
+// 메서드와 헤더 이름은 토큰 문자로만 구성되어야 하고, 헤더 이름과 콜론 사이에 공백이 있으면 안 됨(RFC 9112 3, 5.1)
+// 공백을 허용하면 "Transfer-Encoding : chunked"를 프록시는 무시하고 이 서버는 따르는 식으로 해석이 갈림
+fn check_tokens(head: &str) -> Result<(), FramingError> {
+    let mut lines = head.split("\r\n").filter(|line| !line.is_empty());
+    let method = lines.next().unwrap_or("").split(' ').next().unwrap_or("");
+    if !is_token(method) {
+        return Err(FramingError::Malformed);
+    }
+    for line in lines {
+        match line.split_once(':') {
+            Some((name, _)) if is_token(name) => {}
+            _ => return Err(FramingError::Malformed),
+        }
+    }
+    Ok(())
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+// Content-Length는 숫자만 허용하고, 여러 번 오거나 목록이면 모든 값이 같아야 함(RFC 9112 6.3)
+fn content_length(head: &str) -> Result<usize, FramingError> {
+    let mut length = None;
+    for value in header_values(head, "Content-Length").flat_map(|v| v.split(',')) {
+        let value = value.trim();
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(FramingError::Malformed);
+        }
+        let value: usize = value.parse().map_err(|_| FramingError::Malformed)?;
+        if length.is_some_and(|length| length != value) {
+            return Err(FramingError::Malformed);
+        }
+        length = Some(value);
+    }
+    Ok(length.unwrap_or(0))
+}
+
+// Transfer-Encoding이 있으면 chunked가 마지막에 한 번만 와야 함(RFC 9112 6.1, 6.3)
+// 그렇지 않으면 바디 길이를 정할 수 없으므로 Malformed, chunked 앞에 다른 코딩이 있으면 UnsupportedTransferCoding
+fn is_chunked(head: &str) -> Result<bool, FramingError> {
+    let codings: Vec<String> = header_values(head, "Transfer-Encoding")
+        .flat_map(|v| v.split(','))
+        .map(|coding| coding.trim().to_ascii_lowercase())
+        .filter(|coding| !coding.is_empty())
+        .collect();
+    match codings.split_last() {
+        None => Ok(false),
+        Some((last, rest)) if last == "chunked" && !rest.contains(last) => {
+            if rest.is_empty() {
+                Ok(true)
+            } else {
+                Err(FramingError::UnsupportedTransferCoding)
+            }
+        }
+        Some(_) => Err(FramingError::Malformed),
+    }
+}
+
+// 청크 바디를 디코딩하고(트레일러는 무시) 소비한 바이트 수를 함께 리턴
+// 청크 크기는 상대가 보낸 값이므로 미리 버퍼를 잡지 않고 실제로 받은 바이트만큼만 복사
+fn decode_chunked(bytes: &[u8]) -> Result<(Vec<u8>, usize), FramingError> {
+    let mut body = Vec::new();
+    let mut pos = 0;
+    loop {
+        let size = chunk_size(take_line(bytes, &mut pos)?)?;
+        if size == 0 {
+            while !take_line(bytes, &mut pos)?.is_empty() {}
+            return Ok((body, pos));
+        }
+        let data_end = pos.checked_add(size).ok_or(FramingError::Malformed)?;
+        let data = bytes.get(pos..data_end).ok_or(FramingError::Incomplete)?;
+        match bytes.get(data_end..data_end + 2) {
+            Some(b"\r\n") => {}
+            Some(_) => return Err(FramingError::Malformed),
+            None => return Err(FramingError::Incomplete),
+        }
+        body.extend_from_slice(data);
+        pos = data_end + 2;
+    }
+}
+
+// pos부터 CRLF로 끝나는 행 하나를 꺼내고 pos를 다음 행으로 옮김
+fn take_line<'b>(bytes: &'b [u8], pos: &mut usize) -> Result<&'b str, FramingError> {
+    let rest = &bytes[*pos..];
+    let line_end = rest
+        .windows(2)
+        .position(|window| window == b"\r\n")
+        .ok_or(FramingError::Incomplete)?;
+    let line = std::str::from_utf8(&rest[..line_end]).map_err(|_| FramingError::Malformed)?;
+    *pos += line_end + 2;
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::httprequest::Resource;
+
+    #[test]
+    fn test_parse_request_reads_framed_body() {
+        let input = b"POST /a HTTP/1.1\r\nContent-Length: 2\r\n\r\nhiGET /b HTTP/1.1\r\n\r\n";
+        let (req, consumed) = parse_request(input, true).unwrap();
+        assert_eq!(req.resource, Resource::Path("/a".to_string()));
+        assert_eq!(req.msg_body, "hi");
+        let (req, rest) = parse_request(&input[consumed..], true).unwrap();
+        assert_eq!(req.resource, Resource::Path("/b".to_string()));
+        assert_eq!(consumed + rest, input.len());
+
+        let chunked = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6;ext=1\r\n world\r\n0\r\nTrailer: x\r\n\r\n";
+        let (req, consumed) = parse_request(chunked, true).unwrap();
+        assert_eq!(req.msg_body, "hello world");
+        assert_eq!(consumed, chunked.len());
+
+        // 끝나지 않은 요청은 Incomplete, 큰 청크 크기도 미리 할당하지 않음
+        for input in [
+            &b"GET / HTTP/1.1\r\nHost: a"[..],
+            b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc",
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffff\r\nhi",
+        ] {
+            assert_eq!(parse_request(input, true).unwrap_err(), FramingError::Incomplete);
+        }
+    }
+
+    #[test]
+    fn test_strictness_setting() {
+        let cl_te = "POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n";
+        assert_eq!(request_framing(cl_te, true), Err(FramingError::Malformed));
+        assert_eq!(
+            request_framing(cl_te, false),
+            Ok(Framing {
+                body_length: BodyLength::Chunked,
+                must_close: true,
+            })
+        );
+
+        let space = "POST / HTTP/1.1\r\nContent-Length : 2\r\n\r\n";
+        assert_eq!(request_framing(space, true), Err(FramingError::Malformed));
+        assert_eq!(request_framing(space, false).unwrap().body_length, BodyLength::Fixed(2));
+
+        // 행 구분과 Content-Length 규칙은 설정과 관계없이 적용
+        for head in [
+            "POST / HTTP/1.1\r\nContent-Length: 2\r\nContent-Length: 3\r\n\r\n",
+            "POST / HTTP/1.1\r\nX: a\r\n b\r\n\r\n",
+            "POST / HTTP/1.1\r\nX: a\rContent-Length: 5\r\n\r\n",
+        ] {
+            assert_eq!(request_framing(head, false), Err(FramingError::Malformed), "{:?}", head);
+        }
+        assert_eq!(head_end(b"GET / HTTP/1.1\nHost: a"), Err(FramingError::Malformed));
+    }
+
+    // RFC 9112의 메시지 프레이밍 요구 사항
+    // 바디 길이를 하나로 정할 수 없는 요청은 Malformed, 지원하지 않는 전송 코딩은 UnsupportedTransferCoding
+    #[test]
+    fn test_rfc9112_framing_conformance() {
+        use FramingError::*;
+        let post = |headers: &str, body: &str| format!("POST / HTTP/1.1\r\nHost: a\r\n{}\r\n{}", headers, body);
+        let chunked = |body: &str| post("Transfer-Encoding: chunked\r\n", body);
+
+        // (설명, 요청, 바디 또는 에러)
+        type Case = (&'static str, String, Result<&'static str, FramingError>);
+        let cases: Vec<Case> = vec![
+            // 6.3 Content-Length
+            ("CL 부호", post("Content-Length: +5\r\n", "hello"), Err(Malformed)),
+            ("CL 음수", post("Content-Length: -1\r\n", ""), Err(Malformed)),
+            ("CL 16진수", post("Content-Length: 0x5\r\n", "hello"), Err(Malformed)),
+            ("CL 빈 값", post("Content-Length: \r\n", ""), Err(Malformed)),
+            ("CL 오버플로", post("Content-Length: 99999999999999999999999\r\n", ""), Err(Malformed)),
+            ("서로 다른 CL", post("Content-Length: 5\r\nContent-Length: 6\r\n", "hello!"), Err(Malformed)),
+            ("서로 다른 CL 목록", post("Content-Length: 5, 6\r\n", "hello!"), Err(Malformed)),
+            ("같은 값의 CL", post("Content-Length: 5\r\ncontent-length: 5\r\n", "hello"), Ok("hello")),
+            ("같은 값의 CL 목록", post("Content-Length: 5, 5\r\n", "hello"), Ok("hello")),
+            // 6.1 Transfer-Encoding
+            ("chunked가 마지막이 아님", post("Transfer-Encoding: chunked, gzip\r\n", ""), Err(Malformed)),
+            ("chunked 두 번", post("Transfer-Encoding: chunked\r\nTransfer-Encoding: chunked\r\n", "0\r\n\r\n"), Err(Malformed)),
+            ("chunked 없는 TE", post("Transfer-Encoding: gzip\r\n", ""), Err(Malformed)),
+            ("이름만 비슷한 코딩", post("Transfer-Encoding: xchunked\r\n", "0\r\n\r\n"), Err(Malformed)),
+            ("chunked 앞의 다른 코딩", post("Transfer-Encoding: gzip, chunked\r\n", "0\r\n\r\n"), Err(UnsupportedTransferCoding)),
+            ("대소문자 무시", post("Transfer-Encoding: Chunked\r\n", "2\r\nhi\r\n0\r\n\r\n"), Ok("hi")),
+            // 7.1 chunk-size
+            ("chunk-size 부호", chunked("+2\r\nhi\r\n0\r\n\r\n"), Err(Malformed)),
+            ("chunk-size 0x 접두사", chunked("0x2\r\nhi\r\n0\r\n\r\n"), Err(Malformed)),
+            ("빈 chunk-size", chunked("\r\nhi\r\n0\r\n\r\n"), Err(Malformed)),
+            ("chunk-size 오버플로", chunked("1ffffffffffffffff\r\nhi\r\n0\r\n\r\n"), Err(Malformed)),
+            ("청크 데이터가 길이보다 김", chunked("2\r\nhiX\r\n0\r\n\r\n"), Err(Malformed)),
+            ("청크 확장", chunked("2;name=\"v\"\r\nhi\r\n0\r\nTrailer: x\r\n\r\n"), Ok("hi")),
+            // 2.2 행 구분자, 5.2 obs-fold
+            ("LF만 쓴 요청 행", "GET / HTTP/1.1\nHost: a\r\n\r\n".to_string(), Err(Malformed)),
+            ("LF만 쓴 요청", "GET / HTTP/1.1\nHost: a\n\n".to_string(), Err(Malformed)),
+            ("헤더 값 안의 CR", post("X: a\rContent-Length: 5\r\n", "hello"), Err(Malformed)),
+            ("obs-fold", post("X: a\r\n b\r\n", ""), Err(Malformed)),
+            ("탭으로 시작하는 obs-fold", post("X: a\r\n\tContent-Length: 5\r\n", "hello"), Err(Malformed)),
+            // 3.2 요청 대상
+            ("상대 경로 대상", "GET x HTTP/1.1\r\nHost: a\r\n\r\n".to_string(), Err(Malformed)),
+            ("scheme만 있는 대상", "GET http:x HTTP/1.1\r\nHost: a\r\n\r\n".to_string(), Err(Malformed)),
+            ("빈 대상", "GET  HTTP/1.1\r\nHost: a\r\n\r\n".to_string(), Err(Malformed)),
+            ("OPTIONS가 아닌 *", "GET * HTTP/1.1\r\nHost: a\r\n\r\n".to_string(), Err(Malformed)),
+            ("OPTIONS *", "OPTIONS * HTTP/1.1\r\nHost: a\r\n\r\n".to_string(), Ok("")),
+            ("HTTP/1.0의 chunked", "POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n".to_string(), Err(Malformed)),
+        ];
+        for (name, input, expected) in cases {
+            let result = parse_request(input.as_bytes(), true).map(|(req, _)| req.msg_body);
+            assert_eq!(result, expected.map(str::to_string), "{}", name);
+        }
+    }
+
+    // 퍼징 시드 중 스머글링, LF/CR 행 구분, obs-fold 등 프레이밍이 모호한 요청은 모두 거부되어야 하고
+    // 나머지 시드는 끝나지 않은 요청(empty, request-line-only)을 빼면 그대로 파싱되어야 함
+    #[test]
+    fn test_fuzz_corpus_rejects_ambiguous_seeds() {
+        let rejected = [
+            "bare-", "obs-fold", "smuggle-", "te-", "chunked-http10", "chunked-size-", "content-length-",
+            "empty-header-name", "header-without-colon", "invalid-token-header-name", "target-",
+        ];
+        let incomplete = ["empty", "request-line-only"];
+
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/parse_request");
+        let mut count = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            let result = parse_request(&std::fs::read(&path).unwrap(), true);
+            if rejected.iter().any(|prefix| name.starts_with(prefix)) {
+                assert!(
+                    matches!(result, Err(FramingError::Malformed | FramingError::UnsupportedTransferCoding)),
+                    "{}: {:?}",
+                    name,
+                    result
+                );
+                count += 1;
+            } else if incomplete.contains(&name.as_str()) {
+                assert_eq!(result.unwrap_err(), FramingError::Incomplete, "{}", name);
+            } else {
+                assert!(result.is_ok(), "{}: {:?}", name, result);
+            }
+        }
+        assert!(count > 0);
+    }
+}
@@ -1,11 +1,14 @@
+use super::cookie::CookieJar;
+use super::form::{self, Form, FormError, FormLimits};
 use std::collections::HashMap;
+use std::io::Read;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Resource {
     Path(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct HttpRequest {
     pub method: Method,
     pub version: Version,
@@ -20,29 +23,30 @@ impl From<String> for HttpRequest {
         let mut parsed_version = Version::V1_1;
         let mut parsed_resource = Resource::Path("".to_string());
         let mut parsed_headers = HashMap::new();
-        let mut parsed_msg_body = "";
 
-        // 유입되는 HTTP 요청에서 각 행을 읽음
-        for line in req.lines() {
+        // 첫 번째 빈 행을 기준으로 헤더 부분과 메시지 바디를 나눔(바디 안의 ':'나 빈 행은 그대로 유지)
+        let (head, parsed_msg_body) = match req.find("\r\n\r\n") {
+            Some(i) => (&req[..i], &req[i + 4..]),
+            None => match req.find("\n\n") {
+                Some(i) => (&req[..i], &req[i + 2..]),
+                None => (req.as_str(), ""),
+            },
+        };
 
-            // 읽은 행이 request 행이면 process_req_line() 호출
-            if line.contains("HTTP") {
+        // 헤더 부분에서 각 행을 읽음
+        for (i, line) in head.lines().enumerate() {
+
+            // 첫 번째 행은 request 행이므로 process_req_line() 호출
+            if i == 0 {
                 let (method, resource, version) = process_req_line(line);
                 parsed_method = method;
                 parsed_version = version;
                 parsed_resource = resource;
 
             // 읽은 행이 header 행이면 process_header_line() 호출
-            } else if line.contains(":") {
+            } else if line.contains(':') {
                 let (key, value) = process_header_line(line);
                 parsed_headers.insert(key, value);
-            
-            // 빈 행이면 아무것도 안 함
-            } else if line.len() == 0 {
-            
-            // 전부 아니면 메시지 바디로 취급
-            } else {
-                parsed_msg_body = line;
             }
         }
 
@@ -57,19 +61,95 @@ impl From<String> for HttpRequest {
     }
 }
 
+impl HttpRequest {
+    // 이름이 일치하는 헤더 값(이름은 대소문자를 구분하지 않고 값의 앞뒤 공백은 제거)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    }
+
+    // Cookie 헤더의 쿠키들(헤더가 없으면 빈 쿠키 저장소)
+    pub fn cookies(&self) -> CookieJar {
+        self.header("Cookie").map(CookieJar::parse).unwrap_or_default()
+    }
+
+    // Content-Type의 미디어 타입(소문자, 매개변수 제외)
+    pub fn content_type(&self) -> Option<String> {
+        self.header("Content-Type")
+            .map(|value| form::parse_media_type(value).0)
+    }
+
+    // 바디를 폼으로 파싱(x-www-form-urlencoded와 multipart/form-data)
+    // msg_body는 문자열이므로 바이너리 파일이 들어 있는 바디는 multipart()에 원본 바이트를 넘겨야 함
+    pub fn form(&self, limits: &FormLimits) -> Result<Form, FormError> {
+        match self.content_type().as_deref() {
+            Some("application/x-www-form-urlencoded") => {
+                let fields = form::parse_urlencoded(&self.msg_body);
+                if fields.iter().any(|(_, value)| value.len() > limits.max_field_bytes) {
+                    return Err(FormError::TooLarge);
+                }
+                if fields.len() > limits.max_parts {
+                    return Err(FormError::TooLarge);
+                }
+                Ok(Form {
+                    fields,
+                    files: Vec::new(),
+                })
+            }
+            _ => self.multipart(self.msg_body.as_bytes(), limits),
+        }
+    }
+
+    // multipart/form-data 바디를 body에서 읽으면서 파싱(boundary는 이 요청의 Content-Type에서 가져옴)
+    pub fn multipart(&self, body: impl Read, limits: &FormLimits) -> Result<Form, FormError> {
+        let (media_type, params) = form::parse_media_type(self.header("Content-Type").unwrap_or(""));
+        if media_type != "multipart/form-data" {
+            return Err(FormError::UnsupportedMediaType(media_type));
+        }
+        let boundary = params
+            .into_iter()
+            .find(|(name, _)| name == "boundary")
+            .map(|(_, value)| value)
+            .ok_or(FormError::Malformed("boundary가 없습니다"))?;
+        form::parse_multipart(body, &boundary, limits)
+    }
+}
+
+// HttpRequest를 전송할 형태의 문자열로 변환(파싱의 역방향)
+// 헤더는 파싱한 값을 그대로 "key:value"로 쓰고, Content-Length는 바디 길이로 다시 계산
+impl From<&HttpRequest> for String {
+    fn from(req: &HttpRequest) -> String {
+        let Resource::Path(path) = &req.resource;
+        let mut s = format!("{} {} {}\r\n", req.method.as_str(), path, req.version.as_str());
+        for (key, value) in &req.headers {
+            if !key.trim().eq_ignore_ascii_case("Content-Length") {
+                s.push_str(&format!("{}:{}\r\n", key, value));
+            }
+        }
+        if !req.msg_body.is_empty() || matches!(req.method, Method::Post | Method::Put | Method::Patch) {
+            s.push_str(&format!("Content-Length:{}\r\n", req.msg_body.len()));
+        }
+        s.push_str("\r\n");
+        s.push_str(&req.msg_body);
+        s
+    }
+}
+
 fn process_req_line(s: &str) -> (Method, Resource, Version) {
 
     // 요청 행을 공백으로 구분된 개별 덩어리로 파싱
     let mut words = s.split_whitespace();
 
-    // 요청 행의 첫 번째 부분에서 HTTP 메서드 추출
-    let method = words.next().unwrap();
+    // 요청 행의 첫 번째 부분에서 HTTP 메서드 추출(빠진 부분은 빈 문자열로 취급)
+    let method = words.next().unwrap_or("");
 
     // 요청 행의 두 번째 부분에서 리소스(URI/URL)을 추출
-    let resource = words.next().unwrap();
+    let resource = words.next().unwrap_or("");
 
     // 요청 행의 세 번째 부분에서 HTTP 버전을 추출
-    let version = words.next().unwrap();
+    let version = words.next().unwrap_or("");
 
     (
         method.into(),
@@ -80,8 +160,8 @@ fn process_req_line(s: &str) -> (Method, Resource, Version) {
 
 fn process_header_line(s: &str) -> (String, String) {
 
-    // 구분자(':')로 나누어진 단어들로 행을 파싱
-    let mut header_items = s.split(":");
+    // 첫 번째 구분자(':')를 기준으로 행을 파싱(값 안의 ':'는 그대로 유지)
+    let mut header_items = s.splitn(2, ':');
     let mut key = String::from("");
     let mut value = String::from("");
 
@@ -98,10 +178,15 @@ fn process_header_line(s: &str) -> (String, String) {
     (key, value)
 }
 
-#[derive(Debug, PartialEq)] // Debug는 toString(), PartialEq는 equals()와 유사
+#[derive(Debug, PartialEq, Clone)] // Debug는 toString(), PartialEq는 equals()와 유사
 pub enum Method {
     Get,
     Post,
+    Put,
+    Delete,
+    Patch,
+    Head,
+    Options,
     Uninitialized,
 }
 
@@ -111,13 +196,35 @@ impl From<&str> for Method {
         match s {
             "GET" => Method::Get,
             "POST" => Method::Post,
+            "PUT" => Method::Put,
+            "DELETE" => Method::Delete,
+            "PATCH" => Method::Patch,
+            "HEAD" => Method::Head,
+            "OPTIONS" => Method::Options,
             _ => Method::Uninitialized, // 기타(와일드카드 패턴)
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl Method {
+    // 요청 행에 쓰이는 메서드 이름(현재 타입 -> 문자열)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+            Method::Patch => "PATCH",
+            Method::Head => "HEAD",
+            Method::Options => "OPTIONS",
+            Method::Uninitialized => "",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Version {
+    V1_0,
     V1_1,
     V2_0,
     Uninitialized,
@@ -126,15 +233,29 @@ pub enum Version {
 impl From<&str> for Version {
     fn from(s: &str) -> Version {
         match s {
+            "HTTP/1.0" => Version::V1_0,
             "HTTP/1.1" => Version::V1_1,
+            "HTTP/2.0" | "HTTP/2" => Version::V2_0,
             _ => Version::Uninitialized,
         }
     }
 }
 
+impl Version {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Version::V1_0 => "HTTP/1.0",
+            Version::V1_1 => "HTTP/1.1",
+            Version::V2_0 => "HTTP/2.0",
+            Version::Uninitialized => "",
+        }
+    }
+}
+
 #[cfg(test)] // 변환이 잘 되는지 테스트
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     #[test]
     fn test_method_into() {
         let m: Method = "GET".into(); // 타입 변환하는 메서드(현재 타입 -> 다른 타입)
@@ -144,6 +265,19 @@ mod tests {
     fn test_version_into() {
         let m: Version = "HTTP/1.1".into();
         assert_eq!(m, Version::V1_1);
+        let m: Version = "HTTP/1.0".into();
+        assert_eq!(m, Version::V1_0);
+        let m: Version = "HTTP/2.0".into();
+        assert_eq!(m, Version::V2_0);
+        let m: Version = "HTTP/9.9".into();
+        assert_eq!(m, Version::Uninitialized);
+    }
+    #[test]
+    fn test_method_as_str() {
+        for name in ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"] {
+            let m: Method = name.into();
+            assert_eq!(m.as_str(), name);
+        }
     }
     #[test]
     fn test_read_http() {
@@ -153,7 +287,7 @@ mod tests {
 
         // 헤더 구성
         let mut headers_expected = HashMap::new();
-        headers_expected.insert("Host".into(), " localhost".into());
+        headers_expected.insert("Host".into(), " localhost:3000".into());
         headers_expected.insert("Accept".into(), " */*".into());
         headers_expected.insert("User-Agent".into(), " curl/7.64.1".into());
 
@@ -165,4 +299,136 @@ mod tests {
         assert_eq!(Resource::Path("/greeting".to_string()), req.resource);
         assert_eq!(headers_expected, req.headers);
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_body_keeps_colons_and_blank_lines() {
+        let req: HttpRequest = String::from(
+            "POST /orders HTTP/1.1\r\nHost:localhost\r\nContent-Length:19\r\n\r\n{\"id\": 1}\r\n\r\nnext: x",
+        )
+        .into();
+        assert_eq!(req.msg_body, "{\"id\": 1}\r\n\r\nnext: x");
+        assert_eq!(req.headers.len(), 2);
+        assert_eq!(req.headers.get("Content-Length").map(|v| v.as_str()), Some("19"));
+    }
+
+    #[test]
+    fn test_cookies_accessor() {
+        let req: HttpRequest =
+            String::from("GET / HTTP/1.1\r\ncookie: sid=abc; theme=dark\r\n\r\n").into();
+        let jar = req.cookies();
+        assert_eq!(jar.get("sid"), Some("abc"));
+        assert_eq!(jar.get("theme"), Some("dark"));
+    }
+
+    #[test]
+    fn test_form_accessors() {
+        let req: HttpRequest = String::from(
+            "POST /orders HTTP/1.1\r\ncontent-type: Application/X-WWW-Form-Urlencoded; charset=utf-8\r\n\r\nid=7&note=a+b%21",
+        )
+        .into();
+        assert_eq!(req.header("Content-Type"), Some("Application/X-WWW-Form-Urlencoded; charset=utf-8"));
+        assert_eq!(req.content_type().as_deref(), Some("application/x-www-form-urlencoded"));
+        let form = req.form(&FormLimits::default()).unwrap();
+        assert_eq!(form.field("id"), Some("7"));
+        assert_eq!(form.field("note"), Some("a b!"));
+        assert!(req.multipart(req.msg_body.as_bytes(), &FormLimits::default()).is_err());
+        assert!(req.cookies().is_empty());
+
+        let req: HttpRequest = String::from(
+            "POST /upload HTTP/1.1\r\nContent-Type: multipart/form-data; boundary=\"b1\"\r\n\r\n\
+             --b1\r\nContent-Disposition: form-data; name=\"id\"\r\n\r\n7\r\n--b1--\r\n",
+        )
+        .into();
+        assert_eq!(req.form(&FormLimits::default()).unwrap().field("id"), Some("7"));
+
+        let req: HttpRequest = String::from("POST / HTTP/1.1\r\nContent-Type: text/plain\r\n\r\nx").into();
+        assert!(matches!(
+            req.form(&FormLimits::default()),
+            Err(FormError::UnsupportedMediaType(media)) if media == "text/plain"
+        ));
+    }
+
+    // 퍼징 시드(fuzz/corpus/parse_request)의 잘못된 요청들도 패닉 없이 파싱되어야 함
+    // 퍼징은 nightly가 필요하므로 일반 테스트에서도 시드만큼은 확인
+    #[test]
+    fn test_fuzz_corpus_parses() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/parse_request");
+        let mut count = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let data = std::fs::read(entry.unwrap().path()).unwrap();
+            let req: HttpRequest = String::from_utf8_lossy(&data).to_string().into();
+            let _ = req.cookies();
+            let _ = req.form(&FormLimits::default());
+            let _: HttpRequest = String::from(&req).into();
+            count += 1;
+        }
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn test_malformed_request_lines() {
+        let req: HttpRequest = String::from("GET /").into();
+        assert_eq!(req.method, Method::Get);
+        assert_eq!(req.resource, Resource::Path("/".to_string()));
+        assert_eq!(req.version, Version::Uninitialized);
+
+        let req: HttpRequest = String::from("").into();
+        assert_eq!(req.method, Method::Uninitialized);
+
+        // 콜론이 없는 행은 헤더로 취급하지 않고, 이름이 빈 헤더는 그대로 보관
+        let req: HttpRequest = String::from("GET / HTTP/1.1\r\nHost localhost\r\n: v\r\n\r\n").into();
+        assert_eq!(req.headers.len(), 1);
+        assert_eq!(req.headers.get("").map(String::as_str), Some(" v"));
+    }
+
+    fn method() -> impl Strategy<Value = Method> {
+        prop_oneof![
+            Just(Method::Get),
+            Just(Method::Post),
+            Just(Method::Put),
+            Just(Method::Delete),
+            Just(Method::Patch),
+            Just(Method::Head),
+            Just(Method::Options),
+        ]
+    }
+
+    proptest! {
+        // 직렬화한 요청을 다시 파싱하면 Content-Length 헤더만 추가된 같은 요청이 되어야 함
+        #[test]
+        fn test_request_round_trip(
+            method in method(),
+            version in prop_oneof![Just(Version::V1_0), Just(Version::V1_1)],
+            path in "/[A-Za-z0-9/._~%?=&-]{0,40}",
+            headers in proptest::collection::hash_map("[A-Za-z][A-Za-z0-9-]{0,15}", "[ -~]{0,30}", 0..8),
+            body in "(?s).{0,64}",
+        ) {
+            let headers: HashMap<String, String> = headers
+                .into_iter()
+                .filter(|(k, _)| !k.eq_ignore_ascii_case("Content-Length"))
+                .collect();
+            let req = HttpRequest {
+                method,
+                version,
+                resource: Resource::Path(path),
+                headers,
+                msg_body: body,
+            };
+
+            let mut expected = req.clone();
+            if !req.msg_body.is_empty() || matches!(req.method, Method::Post | Method::Put | Method::Patch) {
+                expected.headers.insert("Content-Length".to_string(), req.msg_body.len().to_string());
+            }
+            let parsed: HttpRequest = String::from(&req).into();
+            prop_assert_eq!(parsed, expected);
+        }
+
+        // 임의의 텍스트를 파싱해도 패닉이 없어야 함
+        #[test]
+        fn test_parse_arbitrary_text(text in "(?s).{0,256}") {
+            let req: HttpRequest = text.into();
+            let _ = req.cookies();
+            let _ = req.content_type();
+            let _: HttpRequest = String::from(&req).into();
+        }
+    }
+}
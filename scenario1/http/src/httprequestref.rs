@@ -0,0 +1,151 @@
+use super::httprequest::{HttpRequest, Method, Resource, Version};
+use std::collections::HashMap;
+
+// 읽기 버퍼를 복사하지 않고 그 위의 슬라이스로 파싱한 요청
+// 파싱 규칙은 HttpRequest::from(String)과 같고, 요청 행은 ASCII 공백으로만 나눔
+// 버퍼보다 오래 보관해야 하면 HttpRequest::from(&req)로 소유한 요청으로 변환
+#[derive(Debug, PartialEq, Clone)]
+pub struct HttpRequestRef<'buf> {
+    pub method: Method,
+    pub version: Version,
+    pub path: &'buf [u8],
+    pub headers: Vec<(&'buf [u8], &'buf [u8])>, // 받은 순서대로(같은 이름이 여러 번 올 수 있음)
+    pub msg_body: &'buf [u8],
+}
+
+impl<'buf> HttpRequestRef<'buf> {
+    pub fn parse(buf: &'buf [u8]) -> HttpRequestRef<'buf> {
+        // 첫 번째 빈 행을 기준으로 헤더 부분과 메시지 바디를 나눔
+        let (head, msg_body) = match find(buf, b"\r\n\r\n") {
+            Some(i) => (&buf[..i], &buf[i + 4..]),
+            None => match find(buf, b"\n\n") {
+                Some(i) => (&buf[..i], &buf[i + 2..]),
+                None => (buf, &buf[buf.len()..]),
+            },
+        };
+
+        let mut lines = head.split_inclusive(|&b| b == b'\n').map(trim_line_ending);
+        let request_line = lines.next();
+        let mut words = request_line
+            .unwrap_or(b"")
+            .split(|b| b.is_ascii_whitespace())
+            .filter(|word| !word.is_empty());
+        let method = words.next().unwrap_or(b"");
+        let path = words.next().unwrap_or(b"");
+        // HttpRequest::from과 같이 요청 행이 아예 없으면 HTTP/1.1로 취급
+        let version = match request_line {
+            Some(_) => std::str::from_utf8(words.next().unwrap_or(b"")).unwrap_or("").into(),
+            None => Version::V1_1,
+        };
+
+        // 콜론이 없는 행은 헤더로 취급하지 않고, 이름과 값은 첫 번째 콜론에서 나눔(값의 앞 공백 포함)
+        let headers = lines
+            .filter_map(|line| {
+                let colon = line.iter().position(|&b| b == b':')?;
+                Some((&line[..colon], &line[colon + 1..]))
+            })
+            .collect();
+
+        HttpRequestRef {
+            method: std::str::from_utf8(method).unwrap_or("").into(),
+            version,
+            path,
+            headers,
+            msg_body,
+        }
+    }
+
+    // 이름이 일치하는 헤더 값(대소문자 무시, 같은 이름이 여러 번 오면 마지막 값)
+    // HttpRequest는 받은 이름 그대로 HashMap에 값 하나씩만 보관하므로 대소문자까지 같은 중복은 마지막 값이 남지만,
+    // 대소문자만 다른 중복(Host와 host)은 HttpRequest::header()가 어느 값을 돌려줄지 정해져 있지 않아 결과가 다를 수 있음
+    pub fn header(&self, name: &str) -> Option<&'buf [u8]> {
+        self.headers
+            .iter()
+            .rev()
+            .find(|(key, _)| key.trim_ascii().eq_ignore_ascii_case(name.as_bytes()))
+            .map(|(_, value)| value.trim_ascii())
+    }
+}
+
+// 소유한 요청으로 변환(UTF-8이 아닌 바이트는 U+FFFD로 바뀜)
+impl From<&HttpRequestRef<'_>> for HttpRequest {
+    fn from(req: &HttpRequestRef) -> HttpRequest {
+        let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+        let mut headers = HashMap::new();
+        for (key, value) in &req.headers {
+            headers.insert(text(key), text(value));
+        }
+        HttpRequest {
+            method: req.method.clone(),
+            version: req.version.clone(),
+            resource: Resource::Path(text(req.path)),
+            headers,
+            msg_body: text(req.msg_body),
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+// str::lines()와 같이 "\n"이나 "\r\n"을 제거(끝의 "\r"만 있으면 그대로 둠)
+fn trim_line_ending(line: &[u8]) -> &[u8] {
+    match line.strip_suffix(b"\n") {
+        Some(line) => line.strip_suffix(b"\r").unwrap_or(line),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_parse_borrows_buffer() {
+        let buf = b"POST /orders?id=1 HTTP/1.1\r\nHost: localhost\r\nX-Id: 1\r\nx-id: 2\r\n\r\n{\"id\": 1}\r\n\r\nnext: x";
+        let req = HttpRequestRef::parse(buf);
+        assert_eq!(req.method, Method::Post);
+        assert_eq!(req.version, Version::V1_1);
+        assert_eq!(req.path, b"/orders?id=1");
+        assert_eq!(req.headers.len(), 3);
+        assert_eq!(req.header("host"), Some(&b"localhost"[..]));
+        assert_eq!(req.header("X-ID"), Some(&b"2"[..]));
+        assert_eq!(req.header("Cookie"), None);
+        assert_eq!(req.msg_body, b"{\"id\": 1}\r\n\r\nnext: x");
+        // 슬라이스는 버퍼 안을 가리킴
+        assert!(buf.as_ptr_range().contains(&req.path.as_ptr()));
+
+        let owned = HttpRequest::from(&req);
+        assert_eq!(owned.resource, Resource::Path("/orders?id=1".to_string()));
+        assert_eq!(owned.header("Host"), Some("localhost"));
+        assert_eq!(owned.msg_body, "{\"id\": 1}\r\n\r\nnext: x");
+
+        // UTF-8이 아닌 바이트도 파싱은 되고 변환할 때만 대체 문자로 바뀜
+        let req = HttpRequestRef::parse(b"GET /\xff HTTP/1.0\r\n\r\n");
+        assert_eq!(req.path, b"/\xff");
+        assert_eq!(HttpRequest::from(&req).resource, Resource::Path("/\u{fffd}".to_string()));
+    }
+
+    // 퍼징 시드도 HttpRequest::from(String)과 같은 결과여야 함
+    #[test]
+    fn test_fuzz_corpus_matches_owned_parser() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/parse_request");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let data = std::fs::read(&path).unwrap();
+            let expected: HttpRequest = String::from_utf8_lossy(&data).to_string().into();
+            assert_eq!(HttpRequest::from(&HttpRequestRef::parse(&data)), expected, "{:?}", path);
+        }
+    }
+
+    proptest! {
+        // 비ASCII 공백은 요청 행을 나누는 방식이 다르므로 ASCII 텍스트로 비교
+        #[test]
+        fn test_matches_owned_parser(text in "[ -~\t\r\n]{0,256}") {
+            let expected: HttpRequest = text.clone().into();
+            prop_assert_eq!(HttpRequest::from(&HttpRequestRef::parse(text.as_bytes())), expected);
+        }
+    }
+}
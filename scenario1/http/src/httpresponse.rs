@@ -1,13 +1,21 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::io::{Result, Write};
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+
+// 상태 행이나 헤더 한 행, 청크 크기 행의 최대 길이
+const MAX_LINE_BYTES: u64 = 8 * 1024;
 
 // 유도(derivable) 트레이트: 컴파일러에게 이런 트레이트의 구현을 유도할 것을 요청
 #[derive(Debug, PartialEq, Clone)] // derive(Clone)을 통해 객체의 깊은 복사를 할 수 있음
 pub struct HttpResponse<'a> { // 'a는 라이프타임 매개변수
-    version: &'a str,
-    status_code: &'a str,
-    status_text: &'a str,
-    headers: Option<HashMap<&'a str, &'a str>>,
+    // Cow를 사용하면 빌린 문자열과 런타임에 만든 문자열(Location, Set-Cookie 등)을 모두 담을 수 있음
+    version: Cow<'a, str>,
+    status_code: Cow<'a, str>,
+    status_text: Cow<'a, str>,
+    headers: Option<HashMap<Cow<'a, str>, Cow<'a, str>>>,
+    // Set-Cookie는 쉼표로 이어 붙일 수 없으므로 헤더 맵과 따로 한 행씩 보관
+    set_cookies: Vec<String>,
     body: Option<String>,
 }
 
@@ -18,6 +26,7 @@ impl<'a> Default for HttpResponse<'a> { // HttpResponse::default()로 구조체
             status_code: "200".into(),
             status_text: "OK".into(),
             headers: None,
+            set_cookies: Vec::new(),
             body: None,
         }
     }
@@ -34,49 +43,142 @@ impl<'a> HttpResponse<'a> {
         if status_code != "200" {
             response.status_code = status_code.into();
         };
-        response.headers = match &headers {
-            Some(_h) => headers,
+        response.headers = match headers {
+            Some(h) => Some(
+                h.into_iter()
+                    .map(|(k, v)| (Cow::Borrowed(k), Cow::Borrowed(v)))
+                    .collect(),
+            ),
             None => {
                 let mut h = HashMap::new();
-                h.insert("Content-Type", "text/html");
+                h.insert("Content-Type".into(), "text/html".into());
                 Some(h)
             }
         };
-        response.status_text = match response.status_code {
-            "200" => "OK".into(),
-            "400" => "Bad Request".into(),
-            "404" => "Not Found".into(),
-            "500" => "Internal Server Error".into(),
-            _ => "Not Found".into(),
-        };
+        response.status_text = status_text(&response.status_code).into();
         response.body = body;
         response
     }
 
+    // 헤더를 추가하거나 같은 이름의 헤더를 덮어씀
+    pub fn add_header(&mut self, key: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) {
+        self.headers
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value.into());
+    }
+
+    // 대소문자와 관계없이 이름이 같은 헤더를 지움
+    pub fn remove_header(&mut self, key: &str) {
+        if let Some(headers) = &mut self.headers {
+            headers.retain(|k, _| !k.eq_ignore_ascii_case(key));
+        }
+    }
+
+    // Set-Cookie 헤더를 한 행 추가(SetCookie 빌더나 업스트림에서 받은 값을 그대로 넘김)
+    pub fn add_cookie(&mut self, cookie: impl ToString) {
+        self.set_cookies.push(cookie.to_string());
+    }
+
+    // 상태 행과 헤더만 전송(바디를 따로 스트리밍하는 경우 사용)
+    // Content-Length나 Transfer-Encoding 헤더는 호출하는 쪽에서 넣어야 함
+    pub fn send_head(&self, write_stream: &mut impl Write) -> io::Result<()> {
+        let head = format!(
+            "{} {} {}\r\n{}\r\n",
+            self.version(),
+            self.status_code(),
+            self.status_text(),
+            self.headers()
+        );
+        write_stream.write_all(head.as_bytes())
+    }
+
     // Result<()>는 void와 유사, 성공 시 리턴값이 없고 실패 시 오류 정보를 리턴함
-    pub fn send_response(&self, write_stream: &mut impl Write) -> Result<()> {
+    pub fn send_response(&self, write_stream: &mut impl Write) -> io::Result<()> {
         let res = self.clone();
         let response_string: String = String::from(res);
-        let _ = write!(write_stream, "{}", response_string);
-        Ok(())
+        write_stream.write_all(response_string.as_bytes())?;
+        write_stream.flush()
+    }
+}
+
+// 상태 코드에 해당하는 사유 구문(reason phrase), 표에 없는 코드는 빈 사유 구문("HTTP/1.1 299 ")
+pub fn status_text(status_code: &str) -> &'static str {
+    match status_code {
+        "101" => "Switching Protocols",
+        "200" => "OK",
+        "201" => "Created",
+        "204" => "No Content",
+        "301" => "Moved Permanently",
+        "302" => "Found",
+        "303" => "See Other",
+        "304" => "Not Modified",
+        "307" => "Temporary Redirect",
+        "308" => "Permanent Redirect",
+        "400" => "Bad Request",
+        "401" => "Unauthorized",
+        "403" => "Forbidden",
+        "404" => "Not Found",
+        "405" => "Method Not Allowed",
+        "408" => "Request Timeout",
+        "413" => "Content Too Large",
+        "414" => "URI Too Long",
+        "415" => "Unsupported Media Type",
+        "426" => "Upgrade Required",
+        "429" => "Too Many Requests",
+        "431" => "Request Header Fields Too Large",
+        "500" => "Internal Server Error",
+        "501" => "Not Implemented",
+        "502" => "Bad Gateway",
+        "503" => "Service Unavailable",
+        "504" => "Gateway Timeout",
+        "505" => "HTTP Version Not Supported",
+        _ => "",
+    }
+}
+
+impl<'a> HttpResponse<'a> {
+    // 요청의 HTTP 버전으로 응답할 때 사용(기본값은 HTTP/1.1)
+    pub fn set_version(&mut self, version: impl Into<Cow<'a, str>>) {
+        self.version = version.into();
+    }
+
+    // 상태 코드와 사유 구문을 직접 지정(업스트림 응답처럼 알 수 없는 상태 코드를 그대로 전달할 때 사용)
+    pub fn set_status(&mut self, status_code: impl Into<Cow<'a, str>>, status_text: impl Into<Cow<'a, str>>) {
+        self.status_code = status_code.into();
+        self.status_text = status_text.into();
     }
 }
 
 impl<'a> HttpResponse<'a> { // getter 메서드 사용 시 데이터 멤버를 문자열로 변환할 수 있음
-    fn version(&self) -> &str {
-        self.version
+    pub fn version(&self) -> &str {
+        &self.version
     }
-    fn status_code(&self) -> &str {
-        self.status_code
+    pub fn status_code(&self) -> &str {
+        &self.status_code
     }
-    fn status_text(&self) -> &str {
-        self.status_text
+    pub fn status_text(&self) -> &str {
+        &self.status_text
+    }
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .as_ref()?
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_ref())
+    }
+    // add_cookie()로 추가한 Set-Cookie 헤더 값들
+    pub fn set_cookies(&self) -> &[String] {
+        &self.set_cookies
     }
     fn headers(&self) -> String {
-        let map: HashMap<&str, &str> = self.headers.clone().unwrap();
         let mut header_string: String = "".into();
-        for (k, v) in map.iter() {
-            header_string = format!("{}{}:{}\r\n", header_string, k, v);
+        if let Some(map) = &self.headers {
+            for (k, v) in map.iter() {
+                header_string = format!("{}{}:{}\r\n", header_string, k, v);
+            }
+        }
+        for cookie in &self.set_cookies {
+            header_string = format!("{}Set-Cookie:{}\r\n", header_string, cookie);
         }
         header_string
     }
@@ -91,23 +193,217 @@ impl<'a> HttpResponse<'a> { // getter 메서드 사용 시 데이터 멤버를 
 
 impl<'a> From<HttpResponse<'a>> for String {
     fn from(res: HttpResponse) -> String {
-        let res1 = res.clone();
+        // 바디 길이는 다시 계산하므로 파싱한 응답에 남아 있는 길이 관련 헤더는 빼고 씀
+        let headers: String = res
+            .headers
+            .iter()
+            .flatten()
+            .filter(|(k, _)| {
+                !k.eq_ignore_ascii_case("Content-Length") && !k.eq_ignore_ascii_case("Transfer-Encoding")
+            })
+            .map(|(k, v)| format!("{}:{}\r\n", k, v))
+            .chain(res.set_cookies.iter().map(|c| format!("Set-Cookie:{}\r\n", c)))
+            .collect();
         format!(
             "{} {} {}\r\n{}Content-Length: {}\r\n\r\n{}", // 개행 적용
-            &res1.version(),
-            &res1.status_code(),
-            &res1.status_text(),
-            &res1.headers(),
-            &res.body.unwrap().len(),
-            &res1.body()
+            &res.version(),
+            &res.status_code(),
+            &res.status_text(),
+            &headers,
+            &res.body().len(),
+            &res.body()
         )
     }
 }
 
+// 응답 파싱 오류
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Incomplete, // 빈 행이나 바디가 아직 다 오지 않음
+    InvalidStatusLine(String),
+    InvalidHeader(String),
+    InvalidContentLength(String),
+    InvalidChunk,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Incomplete => write!(f, "응답이 끝까지 오지 않았습니다"),
+            ParseError::InvalidStatusLine(line) => write!(f, "잘못된 상태 행: {}", line),
+            ParseError::InvalidHeader(line) => write!(f, "잘못된 헤더: {}", line),
+            ParseError::InvalidContentLength(value) => write!(f, "잘못된 Content-Length: {}", value),
+            ParseError::InvalidChunk => write!(f, "잘못된 청크 인코딩"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<'a> HttpResponse<'a> {
+    // 바디를 제외한 상태 행과 헤더 부분을 파싱
+    // 헤더 값의 앞뒤 공백은 제거하고, 같은 이름의 헤더가 여러 번 오면 쉼표로 이어 붙임(Set-Cookie는 set_cookies에 따로 보관)
+    pub fn parse_head(head: &str) -> Result<HttpResponse<'a>, ParseError> {
+        let mut lines = head.lines();
+        let status_line = lines.next().unwrap_or("");
+        let invalid = || ParseError::InvalidStatusLine(status_line.to_string());
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts.next().filter(|v| v.starts_with("HTTP/")).ok_or_else(invalid)?;
+        let status_code = parts
+            .next()
+            .filter(|c| c.len() == 3 && c.bytes().all(|b| b.is_ascii_digit()))
+            .ok_or_else(invalid)?;
+        let status_text = parts.next().unwrap_or("");
+
+        let mut headers: HashMap<Cow<'a, str>, Cow<'a, str>> = HashMap::new();
+        let mut set_cookies = Vec::new();
+        for line in lines.filter(|l| !l.is_empty()) {
+            let (key, value) = line
+                .split_once(':')
+                .filter(|(k, _)| !k.trim().is_empty())
+                .ok_or_else(|| ParseError::InvalidHeader(line.to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+            if key.eq_ignore_ascii_case("Set-Cookie") {
+                set_cookies.push(value.to_string());
+                continue;
+            }
+            match headers.iter_mut().find(|(k, _)| k.eq_ignore_ascii_case(key)) {
+                Some((_, existing)) => *existing = format!("{}, {}", existing, value).into(),
+                None => {
+                    headers.insert(key.to_string().into(), value.to_string().into());
+                }
+            }
+        }
+
+        Ok(HttpResponse {
+            version: version.to_string().into(),
+            status_code: status_code.to_string().into(),
+            status_text: status_text.to_string().into(),
+            headers: Some(headers),
+            set_cookies,
+            body: None,
+        })
+    }
+
+    pub fn set_body(&mut self, body: Option<String>) {
+        self.body = body;
+    }
+
+    // 빌린 문자열을 모두 복사해서 요청보다 오래 보관할 수 있는 응답으로 만듦
+    pub fn into_owned(self) -> HttpResponse<'static> {
+        let owned = |s: Cow<str>| -> Cow<'static, str> { Cow::Owned(s.into_owned()) };
+        HttpResponse {
+            version: owned(self.version),
+            status_code: owned(self.status_code),
+            status_text: owned(self.status_text),
+            headers: self
+                .headers
+                .map(|headers| headers.into_iter().map(|(k, v)| (owned(k), owned(v))).collect()),
+            set_cookies: self.set_cookies,
+            body: self.body,
+        }
+    }
+}
+
+// 전송된 응답 전체(상태 행, 헤더, 바디)를 HttpResponse로 변환(직렬화의 역방향)
+// 바디는 Transfer-Encoding: chunked면 디코딩하고, 아니면 Content-Length만큼(없으면 나머지 전부) 읽음
+// 바디가 UTF-8이 아니면 잘못된 바이트는 U+FFFD로 바뀜
+impl<'a> TryFrom<&[u8]> for HttpResponse<'a> {
+    type Error = ParseError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let end = bytes
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or(ParseError::Incomplete)?;
+        let head = String::from_utf8_lossy(&bytes[..end]);
+        let mut response = HttpResponse::parse_head(&head)?;
+        let mut rest = &bytes[end + 4..];
+
+        let body = if response.is_chunked() {
+            // 디코딩한 바디는 받은 바이트보다 길 수 없음
+            let limit = rest.len();
+            read_chunked(&mut rest, limit).map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => ParseError::Incomplete,
+                _ => ParseError::InvalidChunk,
+            })?
+        } else {
+            match response.content_length()? {
+                Some(length) if rest.len() < length => return Err(ParseError::Incomplete),
+                Some(length) => rest[..length].to_vec(),
+                None => rest.to_vec(),
+            }
+        };
+        response.body = Some(String::from_utf8_lossy(&body).into_owned());
+        Ok(response)
+    }
+}
+
+impl<'a> HttpResponse<'a> {
+    // Transfer-Encoding의 마지막 코딩이 chunked인지 여부
+    pub fn is_chunked(&self) -> bool {
+        self.header("Transfer-Encoding")
+            .is_some_and(|te| te.to_ascii_lowercase().trim_end().ends_with("chunked"))
+    }
+
+    pub fn content_length(&self) -> Result<Option<usize>, ParseError> {
+        self.header("Content-Length")
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| ParseError::InvalidContentLength(v.to_string()))
+            })
+            .transpose()
+    }
+}
+
+// CRLF(또는 LF)로 끝나는 한 행을 읽어서 줄바꿈을 뗀 문자열로 리턴
+pub(crate) fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE_BYTES).read_line(&mut line)?;
+    if !line.ends_with('\n') {
+        let kind = if (line.len() as u64) < MAX_LINE_BYTES {
+            io::ErrorKind::UnexpectedEof
+        } else {
+            io::ErrorKind::InvalidData
+        };
+        return Err(kind.into());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+// 청크 크기 행, 청크 데이터, CRLF를 반복해서 읽고 크기 0인 청크 뒤의 트레일러는 버림
+// 청크 크기는 상대가 보낸 값이므로 미리 버퍼를 잡지 않고, 디코딩한 바디가 limit를 넘으면 InvalidData
+pub(crate) fn read_chunked(reader: &mut impl BufRead, limit: usize) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        let size = line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+        if size == 0 {
+            while !read_line(reader)?.is_empty() {}
+            return Ok(body);
+        }
+        let total = body
+            .len()
+            .checked_add(size)
+            .filter(|&total| total <= limit)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("청크 바디가 {}바이트를 넘습니다", limit)))?;
+        reader.take(size as u64).read_to_end(&mut body)?;
+        if body.len() < total {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if !read_line(reader)?.is_empty() {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+    }
+}
+
 
 #[cfg(test)] // 러스트에서는 #[]가 애너테이션
 mod tests {
     use super::*;
+    use crate::cookie::SetCookie;
+    use proptest::prelude::*;
 
     #[test] // @Test 와 유사
     fn test_response_struct_creation_200() {
@@ -119,20 +415,49 @@ mod tests {
         );
 
         let response_expected = HttpResponse {
-            version: "HTTP/1.1",
-            status_code: "200",
-            status_text: "OK",
+            version: "HTTP/1.1".into(),
+            status_code: "200".into(),
+            status_text: "OK".into(),
             headers: {
                 let mut h = HashMap::new();
-                h.insert("Content-Type", "text/html");
+                h.insert("Content-Type".into(), "text/html".into());
                 Some(h)
             },
+            set_cookies: Vec::new(),
             body: Some("Item was shipped on 21st Dec 2020".into()),
         };
 
         assert_eq!(response_actual, response_expected);
     }
 
+    #[test]
+    fn test_status_text() {
+        assert_eq!(HttpResponse::new("201", None, None).status_text(), "Created");
+        assert_eq!(HttpResponse::new("302", None, None).status_text(), "Found");
+        assert_eq!(HttpResponse::new("404", None, None).status_text(), "Not Found");
+        // 알 수 없는 코드에 다른 코드의 사유 구문을 붙이지 않음
+        let s: String = HttpResponse::new("299", None, Some(String::new())).into();
+        assert!(s.starts_with("HTTP/1.1 299 \r\n"));
+        assert_eq!(HttpResponse::try_from(s.as_bytes()).unwrap().status_text(), "");
+    }
+
+    #[test]
+    fn test_into_owned() {
+        // 빌린 문자열이 사라진 뒤에도 응답을 쓸 수 있음
+        let owned: HttpResponse<'static> = {
+            let content_type = String::from("application/json");
+            let mut response = HttpResponse::new("200", None, Some("{}".into()));
+            response.add_header("Content-Type", content_type.as_str());
+            response.add_cookie("sid=abc");
+            response.into_owned()
+        };
+        assert_eq!(owned.status_code(), "200");
+        assert_eq!(owned.status_text(), "OK");
+        assert_eq!(owned.header("Content-Type"), Some("application/json"));
+        assert_eq!(owned.set_cookies(), ["sid=abc".to_string()]);
+        assert_eq!(owned.body(), "{}");
+    }
+
     #[test]
     fn test_response_struct_creation_404() {
 
@@ -143,14 +468,15 @@ mod tests {
         );
 
         let response_expected = HttpResponse {
-            version: "HTTP/1.1",
-            status_code: "404",
-            status_text: "Not Found",
+            version: "HTTP/1.1".into(),
+            status_code: "404".into(),
+            status_text: "Not Found".into(),
             headers: {
                 let mut h = HashMap::new();
-                h.insert("Content-Type", "text/html");
+                h.insert("Content-Type".into(), "text/html".into());
                 Some(h)
             },
+            set_cookies: Vec::new(),
             body: Some("Item was shipped on 21st Dec 2020".into()),
         };
 
@@ -161,14 +487,15 @@ mod tests {
     fn test_http_response_creation() {
 
         let response_expected = HttpResponse {
-            version: "HTTP/1.1",
-            status_code: "404",
-            status_text: "Not Found",
+            version: "HTTP/1.1".into(),
+            status_code: "404".into(),
+            status_text: "Not Found".into(),
             headers: {
                 let mut h = HashMap::new();
-                h.insert("Content-Type", "text/html");
+                h.insert("Content-Type".into(), "text/html".into());
                 Some(h)
             },
+            set_cookies: Vec::new(),
             body: Some("Item was shipped on 21st Dec 2020".into()),
         };
 
@@ -178,4 +505,107 @@ mod tests {
         assert_eq!(http_string, response_actual);
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_chunked_response() {
+        let bytes = b"HTTP/1.0 201 Created\r\nTransfer-Encoding: chunked\r\nVary: Accept\r\nvary: Origin\r\n\r\n4;x=y\r\nWiki\r\n5\r\npedia\r\n0\r\nTrailer: 1\r\n\r\n";
+        let resp = HttpResponse::try_from(&bytes[..]).unwrap();
+        assert_eq!(resp.version(), "HTTP/1.0");
+        assert_eq!(resp.status_code(), "201");
+        assert_eq!(resp.status_text(), "Created");
+        assert_eq!(resp.header("Vary"), Some("Accept, Origin"));
+        assert_eq!(resp.body(), "Wikipedia");
+
+        // 직렬화할 때는 Transfer-Encoding 대신 다시 계산한 Content-Length를 씀
+        let s: String = resp.into();
+        assert!(s.contains("Content-Length: 9\r\n"));
+        assert!(!s.to_ascii_lowercase().contains("transfer-encoding"));
+    }
+
+    #[test]
+    fn test_set_cookie_lines_are_not_merged() {
+        let mut resp = HttpResponse::new("200", None, Some(String::new()));
+        resp.add_cookie(SetCookie::new("sid", "abc").path("/").http_only(true));
+        resp.add_cookie("theme=dark; Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+        let s: String = resp.into();
+        assert!(s.contains("Set-Cookie:sid=abc; Path=/; HttpOnly\r\n"));
+        assert!(s.contains("Set-Cookie:theme=dark; Expires=Wed, 21 Oct 2015 07:28:00 GMT\r\n"));
+
+        // 파싱할 때도 Expires 안의 쉼표 때문에 이어 붙이지 않고 한 행씩 보관
+        let parsed = HttpResponse::try_from(s.as_bytes()).unwrap();
+        assert_eq!(
+            parsed.set_cookies(),
+            ["sid=abc; Path=/; HttpOnly", "theme=dark; Expires=Wed, 21 Oct 2015 07:28:00 GMT"]
+        );
+        assert_eq!(parsed.header("Set-Cookie"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |bytes: &[u8]| HttpResponse::try_from(bytes).map(|_| ());
+        assert_eq!(parse(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n"), Err(ParseError::Incomplete));
+        assert_eq!(parse(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nab"), Err(ParseError::Incomplete));
+        assert!(matches!(parse(b"HTTP/1.1 2000 OK\r\n\r\n"), Err(ParseError::InvalidStatusLine(_))));
+        assert!(matches!(parse(b"HTTP/1.1 200 OK\r\nbad header\r\n\r\n"), Err(ParseError::InvalidHeader(_))));
+        assert!(matches!(
+            parse(b"HTTP/1.1 200 OK\r\nContent-Length: x\r\n\r\n"),
+            Err(ParseError::InvalidContentLength(_))
+        ));
+        assert_eq!(
+            parse(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n"),
+            Err(ParseError::InvalidChunk)
+        );
+        // 청크 크기가 받은 바이트보다 크거나 더하면 넘치는 경우에도 버퍼를 잡지 않고 오류
+        assert_eq!(
+            parse(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nfffffffffffffff\r\nabc\r\n0\r\n\r\n"),
+            Err(ParseError::InvalidChunk)
+        );
+        assert_eq!(
+            parse(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\nffffffffffffffff\r\nabc\r\n0\r\n\r\n"),
+            Err(ParseError::InvalidChunk)
+        );
+        // 선언한 크기보다 데이터가 먼저 끝나면 아직 다 오지 않은 응답
+        assert_eq!(
+            parse(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nab"),
+            Err(ParseError::Incomplete)
+        );
+    }
+
+    proptest! {
+        // 직렬화한 응답을 다시 파싱하면 Content-Length 헤더만 추가된 같은 응답이 되어야 함
+        #[test]
+        fn test_response_round_trip(
+            status_code in 100u16..600,
+            status_text in "[A-Za-z][A-Za-z ]{0,20}",
+            headers in proptest::collection::hash_map("[a-z][a-z0-9-]{0,15}", "[!-~]([ -~]{0,30}[!-~])?", 0..8),
+            set_cookies in proptest::collection::vec("[!-~]([ -~]{0,30}[!-~])?", 0..3),
+            body in "(?s).{0,64}",
+        ) {
+            let headers: HashMap<Cow<str>, Cow<str>> = headers
+                .into_iter()
+                .filter(|(k, _)| !matches!(k.as_str(), "content-length" | "transfer-encoding" | "set-cookie"))
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect();
+            let resp = HttpResponse {
+                version: "HTTP/1.1".into(),
+                status_code: status_code.to_string().into(),
+                status_text: status_text.into(),
+                headers: Some(headers),
+                set_cookies,
+                body: Some(body),
+            };
+
+            let mut expected = resp.clone();
+            expected.add_header("Content-Length", resp.body().len().to_string());
+            let s: String = resp.into();
+            let parsed = HttpResponse::try_from(s.as_bytes()).unwrap();
+            prop_assert_eq!(parsed, expected);
+        }
+
+        // 임의의 바이트를 파싱해도 패닉 없이 오류를 리턴해야 함
+        #[test]
+        fn test_parse_arbitrary_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..256)) {
+            let _ = HttpResponse::try_from(&bytes[..]);
+        }
+    }
+}
@@ -1,2 +1,7 @@
+pub mod client;
+pub mod cookie;
+pub mod form;
+pub mod framing;
 pub mod httprequest;
+pub mod httprequestref;
 pub mod httpresponse;
\ No newline at end of file
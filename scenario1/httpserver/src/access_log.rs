@@ -0,0 +1,270 @@
+use super::config::{AccessLogFormat, AccessLogSection};
+use chrono::{DateTime, Local};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// 요청 하나에 대한 액세스 로그 항목
+#[derive(Debug)]
+pub struct AccessLogEntry<'a> {
+    pub peer: Option<SocketAddr>,
+    pub time: DateTime<Local>,
+    pub request_line: &'a str,
+    pub status: &'a str,
+    pub bytes_sent: usize,
+    pub referer: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+    pub duration: Duration,
+}
+
+impl<'a> AccessLogEntry<'a> {
+    // Common Log Format: host ident authuser [date] "request" status bytes
+    pub fn common(&self) -> String {
+        format!(
+            "{} - - [{}] \"{}\" {} {}",
+            self.host(),
+            self.time.format("%d/%b/%Y:%H:%M:%S %z"),
+            escape(self.request_line),
+            self.status,
+            self.bytes(),
+        )
+    }
+
+    // Combined Log Format 뒤에 처리 시간(밀리초)을 덧붙임
+    pub fn combined(&self) -> String {
+        format!(
+            "{} \"{}\" \"{}\" {:.3}",
+            self.common(),
+            escape(self.referer.unwrap_or("-")),
+            escape(self.user_agent.unwrap_or("-")),
+            self.duration.as_secs_f64() * 1000.0,
+        )
+    }
+
+    pub fn json(&self) -> String {
+        let mut parts = self.request_line.splitn(3, ' ');
+        serde_json::json!({
+            "time": self.time.to_rfc3339(),
+            "peer": self.peer.map(|p| p.ip().to_string()),
+            "method": parts.next().filter(|m| !m.is_empty()),
+            "target": parts.next(),
+            "protocol": parts.next(),
+            "status": self.status.parse::<u16>().ok(),
+            "bytes_sent": self.bytes_sent,
+            "referer": self.referer,
+            "user_agent": self.user_agent,
+            "duration_ms": self.duration.as_secs_f64() * 1000.0,
+        })
+        .to_string()
+    }
+
+    pub fn format(&self, format: AccessLogFormat) -> String {
+        match format {
+            AccessLogFormat::Common => self.common(),
+            AccessLogFormat::Combined => self.combined(),
+            AccessLogFormat::Json => self.json(),
+        }
+    }
+
+    fn host(&self) -> String {
+        self.peer
+            .map(|p| p.ip().to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    fn bytes(&self) -> String {
+        if self.bytes_sent == 0 {
+            "-".to_string()
+        } else {
+            self.bytes_sent.to_string()
+        }
+    }
+}
+
+// 로그 필드 안의 따옴표와 제어 문자를 이스케이프해서 한 행이 깨지지 않도록 함
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 여러 워커 스레드가 공유하는 액세스 로거
+pub struct AccessLog {
+    format: AccessLogFormat,
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl AccessLog {
+    pub fn new(section: &AccessLogSection) -> io::Result<AccessLog> {
+        let output: Box<dyn Write + Send> = if section.output == "stdout" {
+            Box::new(io::stdout())
+        } else {
+            Box::new(RotatingFile::open(
+                PathBuf::from(&section.output),
+                section.max_file_bytes,
+                section.max_files,
+            )?)
+        };
+        Ok(AccessLog::with_writer(section.format, output))
+    }
+
+    pub fn with_writer(format: AccessLogFormat, output: Box<dyn Write + Send>) -> AccessLog {
+        AccessLog {
+            format,
+            output: Mutex::new(output),
+        }
+    }
+
+    pub fn log(&self, entry: &AccessLogEntry) {
+        // 파일 회전이 행 중간에서 일어나지 않도록 한 번에 기록
+        let line = format!("{}\n", entry.format(self.format));
+        let mut output = self.output.lock().unwrap();
+        let _ = output.write_all(line.as_bytes());
+        let _ = output.flush();
+    }
+}
+
+// 크기가 max_bytes를 넘으면 access.log -> access.log.1 -> access.log.2 ... 로 돌려가며 보관
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<RotatingFile> {
+        let file = open_append(&path)?;
+        let written = file.metadata()?.len();
+        Ok(RotatingFile {
+            path,
+            file,
+            written,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            // 보관 파일이 없으면 현재 파일을 비우고 다시 씀
+            self.file = File::create(&self.path)?;
+        } else {
+            for i in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, i);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, i + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+            self.file = open_append(&self.path)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry() -> AccessLogEntry<'static> {
+        AccessLogEntry {
+            peer: Some("127.0.0.1:50000".parse().unwrap()),
+            time: Local.with_ymd_and_hms(2020, 12, 21, 10, 30, 0).unwrap(),
+            request_line: "GET /api/shipping/orders HTTP/1.1",
+            status: "200",
+            bytes_sent: 120,
+            referer: Some("http://localhost:3000/"),
+            user_agent: Some("curl/7.64.1 \"test\""),
+            duration: Duration::from_micros(1500),
+        }
+    }
+
+    #[test]
+    fn test_combined_log_format() {
+        let line = entry().combined();
+        let time = Local
+            .with_ymd_and_hms(2020, 12, 21, 10, 30, 0)
+            .unwrap()
+            .format("%d/%b/%Y:%H:%M:%S %z")
+            .to_string();
+        assert_eq!(
+            line,
+            format!(
+                "127.0.0.1 - - [{}] \"GET /api/shipping/orders HTTP/1.1\" 200 120 \
+                 \"http://localhost:3000/\" \"curl/7.64.1 \\\"test\\\"\" 1.500",
+                time
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_log_format() {
+        let value: serde_json::Value = serde_json::from_str(&entry().json()).unwrap();
+        assert_eq!(value["peer"], "127.0.0.1");
+        assert_eq!(value["method"], "GET");
+        assert_eq!(value["target"], "/api/shipping/orders");
+        assert_eq!(value["status"], 200);
+        assert_eq!(value["bytes_sent"], 120);
+        assert_eq!(value["duration_ms"], 1.5);
+    }
+
+    #[test]
+    fn test_rotating_file_keeps_max_files() {
+        let dir = std::env::temp_dir().join(format!("httpserver-log-{}", std::process::id()));
+        let path = dir.join("access.log");
+        let mut file = RotatingFile::open(path.clone(), 10, 2).unwrap();
+        for line in ["first-line\n", "second-line\n", "third-line\n", "fourth-line\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth-line\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "third-line\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 2)).unwrap(), "second-line\n");
+        assert!(!rotated_path(&path, 3).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
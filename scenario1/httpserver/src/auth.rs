@@ -0,0 +1,295 @@
+use super::config::AuthSection;
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use http::httprequest::HttpRequest;
+use http::httpresponse::HttpResponse;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+// 인증 실패 이유, 401 응답의 챌린지에 반영
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    MissingCredentials,
+    InvalidCredentials,
+    InvalidToken,
+}
+
+// 라우트 그룹 하나의 인증 설정([auth.<이름>])으로 요청을 검사
+// Basic은 htpasswd 파일의 bcrypt/argon2 해시로, Bearer는 설정된 토큰 목록으로 확인
+pub struct Authenticator {
+    realm: String,
+    users: HashMap<String, String>, // 사용자 이름 -> 비밀번호 해시
+    tokens: Vec<String>,
+    // 해시 검증은 일부러 느리므로 사용자마다 마지막으로 검증에 성공한 비밀번호를 기억
+    // 메모리나 코어 덤프에서 빠르게 대입할 수 있는 해시가 남지 않도록 프로세스마다 새로 만든 키의 HMAC으로 보관
+    verified: Mutex<HashMap<String, [u8; 32]>>,
+    cache_key: [u8; 32],
+}
+
+impl Authenticator {
+    pub fn new(section: &AuthSection) -> Authenticator {
+        let users = match &section.htpasswd {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(contents) => parse_htpasswd(&contents, &path.display().to_string()),
+                Err(e) => {
+                    eprintln!(
+                        "htpasswd 파일 {}을(를) 읽을 수 없습니다: {}",
+                        path.display(),
+                        e
+                    );
+                    HashMap::new()
+                }
+            },
+            None => HashMap::new(),
+        };
+        let mut cache_key = [0; 32];
+        getrandom::getrandom(&mut cache_key).expect("운영체제 난수 생성기를 사용할 수 없습니다");
+        Authenticator {
+            realm: section.realm.clone(),
+            users,
+            tokens: section.tokens.clone(),
+            verified: Mutex::new(HashMap::new()),
+            cache_key,
+        }
+    }
+
+    pub fn check(&self, req: &HttpRequest) -> Result<(), AuthError> {
+        let authorization = req.header("Authorization").unwrap_or("");
+        let (scheme, credentials) = authorization.split_once(' ').unwrap_or((authorization, ""));
+        let credentials = credentials.trim();
+
+        if scheme.eq_ignore_ascii_case("Basic") && !self.users.is_empty() {
+            if self.check_basic(credentials) {
+                return Ok(());
+            }
+            Err(AuthError::InvalidCredentials)
+        } else if scheme.eq_ignore_ascii_case("Bearer") && !self.tokens.is_empty() {
+            let valid = self
+                .tokens
+                .iter()
+                .any(|t| constant_time_eq(t.as_bytes(), credentials.as_bytes()));
+            if valid {
+                return Ok(());
+            }
+            Err(AuthError::InvalidToken)
+        } else {
+            Err(AuthError::MissingCredentials)
+        }
+    }
+
+    fn check_basic(&self, credentials: &str) -> bool {
+        let decoded = match BASE64_STANDARD
+            .decode(credentials)
+            .ok()
+            .and_then(|d| String::from_utf8(d).ok())
+        {
+            Some(decoded) => decoded,
+            None => return false,
+        };
+        let (user, password) = match decoded.split_once(':') {
+            Some(pair) => pair,
+            None => return false,
+        };
+        let hash = match self.users.get(user) {
+            Some(hash) => hash,
+            None => return false,
+        };
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.cache_key).expect("HMAC은 모든 키 길이를 허용함");
+        mac.update(user.as_bytes());
+        mac.update(b":");
+        mac.update(password.as_bytes());
+        let digest: [u8; 32] = mac.finalize().into_bytes().into();
+        let cached = self.verified.lock().unwrap().get(user).copied();
+        if cached.is_some_and(|cached| constant_time_eq(&cached, &digest)) {
+            return true;
+        }
+        if !verify_password(password, hash) {
+            return false;
+        }
+        self.verified
+            .lock()
+            .unwrap()
+            .insert(user.to_string(), digest);
+        true
+    }
+
+    // 인증에 실패한 클라이언트에 보낼 401 응답
+    // 사용할 수 있는 방식마다 WWW-Authenticate 챌린지를 보냄(한 헤더에 쉼표로 나열)
+    pub fn challenge<'a>(&self, error: AuthError) -> HttpResponse<'a> {
+        let realm = self.realm.replace(['"', '\\'], "");
+        let mut challenges = Vec::new();
+        if !self.users.is_empty() {
+            challenges.push(format!("Basic realm=\"{}\", charset=\"UTF-8\"", realm));
+        }
+        if !self.tokens.is_empty() {
+            let error = match error {
+                AuthError::InvalidToken => ", error=\"invalid_token\"",
+                _ => "",
+            };
+            challenges.push(format!("Bearer realm=\"{}\"{}", realm, error));
+        }
+        let mut resp = HttpResponse::new("401", None, Some(String::new()));
+        resp.add_header("WWW-Authenticate", challenges.join(", "));
+        resp
+    }
+}
+
+// "사용자:해시" 형식의 행들, 빈 행과 #으로 시작하는 행은 무시
+// bcrypt($2a$, $2b$, $2y$)와 argon2($argon2id$ 등)가 아닌 해시는 경고하고 건너뜀
+fn parse_htpasswd(contents: &str, source: &str) -> HashMap<String, String> {
+    let mut users = HashMap::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once(':') {
+            Some((user, hash)) if is_supported_hash(hash) => {
+                users.insert(user.to_string(), hash.to_string());
+            }
+            _ => eprintln!(
+                "{}:{}: bcrypt나 argon2 해시가 아니므로 건너뜁니다",
+                source,
+                i + 1
+            ),
+        }
+    }
+    users
+}
+
+fn is_supported_hash(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2y$", "$argon2"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    if hash.starts_with("$argon2") {
+        PasswordHash::new(hash).is_ok_and(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+    } else {
+        bcrypt::verify(password, hash).unwrap_or(false)
+    }
+}
+
+// 일치하는 위치에 따라 비교 시간이 달라지지 않도록 모든 바이트를 비교
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argon2::password_hash::{PasswordHasher, SaltString};
+    use sha2::Digest;
+
+    fn request(authorization: Option<&str>) -> HttpRequest {
+        let header = authorization
+            .map(|a| format!("Authorization: {}\r\n", a))
+            .unwrap_or_default();
+        format!("GET /api/shipping/orders HTTP/1.1\r\n{}\r\n", header).into()
+    }
+
+    fn basic(user: &str, password: &str) -> String {
+        format!(
+            "Basic {}",
+            BASE64_STANDARD.encode(format!("{}:{}", user, password))
+        )
+    }
+
+    fn authenticator(htpasswd: Option<&str>, tokens: &[&str]) -> Authenticator {
+        Authenticator {
+            realm: "shipping".to_string(),
+            users: htpasswd
+                .map(|h| parse_htpasswd(h, "test"))
+                .unwrap_or_default(),
+            tokens: tokens.iter().map(|t| t.to_string()).collect(),
+            verified: Mutex::new(HashMap::new()),
+            cache_key: [7; 32],
+        }
+    }
+
+    #[test]
+    fn test_basic_with_bcrypt_and_argon2() {
+        let bcrypt_hash = bcrypt::hash("s3cret", 4).unwrap();
+        let salt = SaltString::from_b64("c29tZXNhbHRzb21lc2FsdA").unwrap();
+        let argon2_hash = Argon2::default()
+            .hash_password(b"hunter2", &salt)
+            .unwrap()
+            .to_string();
+        let htpasswd = format!(
+            "# 직원 계정\nalice:{}\nbob:{}\ncarol:plaintext\n",
+            bcrypt_hash, argon2_hash
+        );
+        let auth = authenticator(Some(&htpasswd), &[]);
+        assert_eq!(auth.users.len(), 2);
+
+        assert!(auth
+            .check(&request(Some(&basic("alice", "s3cret"))))
+            .is_ok());
+        assert!(auth.check(&request(Some(&basic("bob", "hunter2")))).is_ok());
+        // 캐시된 비밀번호로도 같은 결과
+        assert!(auth
+            .check(&request(Some(&basic("alice", "s3cret"))))
+            .is_ok());
+        // 캐시에는 솔트 없는 비밀번호 해시가 아니라 프로세스 키로 만든 HMAC이 남음
+        let cached = auth.verified.lock().unwrap()["alice"];
+        assert_ne!(cached, <[u8; 32]>::from(sha2::Sha256::digest(b"s3cret")));
+        assert!(auth
+            .check(&request(Some(&basic("alice", "wrong"))))
+            .is_err());
+        assert!(auth
+            .check(&request(Some(&basic("carol", "plaintext"))))
+            .is_err());
+        assert!(auth.check(&request(Some("Basic !!!"))).is_err());
+
+        let error = auth.check(&request(None)).unwrap_err();
+        assert_eq!(error, AuthError::MissingCredentials);
+        let resp = auth.challenge(error);
+        assert_eq!(resp.status_code(), "401");
+        assert_eq!(
+            resp.header("WWW-Authenticate"),
+            Some("Basic realm=\"shipping\", charset=\"UTF-8\"")
+        );
+    }
+
+    #[test]
+    fn test_bearer_tokens() {
+        let auth = authenticator(None, &["token-a", "token-b"]);
+        assert!(auth.check(&request(Some("Bearer token-b"))).is_ok());
+        assert!(auth.check(&request(Some("bearer  token-a "))).is_ok());
+
+        let resp = auth.challenge(auth.check(&request(Some("Bearer token-c"))).unwrap_err());
+        assert_eq!(
+            resp.header("WWW-Authenticate"),
+            Some("Bearer realm=\"shipping\", error=\"invalid_token\"")
+        );
+        // Basic을 설정하지 않은 그룹에는 Basic 자격 증명이 통하지 않음
+        let resp = auth.challenge(
+            auth.check(&request(Some(&basic("token-a", ""))))
+                .unwrap_err(),
+        );
+        assert_eq!(
+            resp.header("WWW-Authenticate"),
+            Some("Bearer realm=\"shipping\"")
+        );
+
+        // 둘 다 설정하면 챌린지를 모두 보냄
+        let both = authenticator(
+            Some(&format!("a:{}", bcrypt::hash("x", 4).unwrap())),
+            &["t"],
+        );
+        assert_eq!(
+            both.challenge(AuthError::MissingCredentials)
+                .header("WWW-Authenticate"),
+            Some("Basic realm=\"shipping\", charset=\"UTF-8\", Bearer realm=\"shipping\"")
+        );
+    }
+}
@@ -0,0 +1,1176 @@
+use super::listener;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 설정은 기본값 -> 설정 파일(TOML) -> 환경변수 -> 명령행 플래그 순서로 덮어씀
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub server: ServerSection,
+    #[serde(rename = "static")]
+    pub static_files: StaticSection,
+    pub cache: CacheSection,
+    pub log: LogSection,
+    pub access_log: AccessLogSection,
+    pub session: SessionSection,
+    pub tls: Option<TlsSection>,
+    pub cors: Option<CorsSection>,
+    // 기본 호스트(어떤 가상 호스트와도 일치하지 않는 요청)의 라우트 테이블
+    pub routes: Vec<RouteSection>,
+    pub upstreams: HashMap<String, UpstreamSection>,
+    // 라우트의 auth가 가리키는 인증 설정([auth.<이름>])
+    pub auth: HashMap<String, AuthSection>,
+    // 라우트의 rate_limit이 가리키는 요청 한도([rate_limits.<이름>])
+    pub rate_limits: HashMap<String, RateLimitSection>,
+    pub vhosts: Vec<VirtualHostSection>,
+    // 가상 호스트별로 기본 설정에 덮어쓴 결과, resolve_vhosts()에서 만들어짐
+    #[serde(skip)]
+    sites: Vec<ServerConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    // "호스트:포트" 또는 유닉스 도메인 소켓 경로("unix:/run/httpserver.sock")
+    pub bind: Vec<String>,
+    pub workers: usize,
+    pub read_timeout_secs: u64,
+    pub write_timeout_secs: u64,
+    pub header_read_timeout_secs: u64,
+    // 응답 후 다음 요청을 기다리는 시간, 0이면 요청 하나마다 커넥션을 닫음
+    pub keep_alive_timeout_secs: u64,
+    pub max_request_line_bytes: usize,
+    pub max_header_count: usize,
+    pub max_header_bytes: usize,
+    pub max_body_bytes: usize,
+    // 요청 스머글링에 쓰일 수 있는 모호한 요청(Content-Length와 Transfer-Encoding을 함께 보냄,
+    // 헤더 이름과 콜론 사이의 공백, 토큰에 쓸 수 없는 문자)을 400으로 거부
+    // false면 Transfer-Encoding을 우선하고 응답 후 커넥션을 닫으며, 헤더 이름의 공백은 무시
+    pub strict_parsing: bool,
+    // 종료(SIGINT, SIGTERM) 후 처리 중인 커넥션이 끝나기를 기다리는 최대 시간
+    pub shutdown_timeout_secs: u64,
+    // 유닉스 도메인 소켓 파일의 권한(8진수, 예: 0o660)
+    pub unix_socket_mode: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StaticSection {
+    pub public_path: PathBuf,
+    pub data_path: PathBuf,
+    // HTML 파일을 템플릿으로 렌더링(false면 파일을 그대로 응답)
+    pub templates: bool,
+}
+
+// 경로 접두사가 일치하는 첫 번째 라우트의 핸들러가 요청을 처리
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteSection {
+    pub prefix: String,
+    pub handler: HandlerKind,
+    // handler = "proxy"일 때 요청을 전달할 업스트림 이름과 접두사 제거 여부
+    #[serde(default)]
+    pub upstream: Option<String>,
+    #[serde(default)]
+    pub strip_prefix: bool,
+    // handler = "web_socket"일 때 커넥션을 처리할 엔드포인트 이름(WEBSOCKET_ENDPOINTS 중 하나)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    // 이 라우트로 오는 요청을 인증할 auth 설정 이름, 없으면 인증 없이 처리
+    #[serde(default)]
+    pub auth: Option<String>,
+    // 클라이언트별 요청 한도를 적용할 rate_limits 설정 이름
+    #[serde(default)]
+    pub rate_limit: Option<String>,
+}
+
+// 웹소켓 라우트에 지정할 수 있는 엔드포인트
+pub const WEBSOCKET_ENDPOINTS: &[&str] = &["order_status"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandlerKind {
+    WebService,
+    Static,
+    Proxy,
+    WebSocket,
+    Form,
+    Session,
+    Metrics,
+    NotFound,
+}
+
+impl RouteSection {
+    fn new(prefix: &str, handler: HandlerKind) -> RouteSection {
+        RouteSection {
+            prefix: prefix.to_string(),
+            handler,
+            upstream: None,
+            strip_prefix: false,
+            endpoint: None,
+            auth: None,
+            rate_limit: None,
+        }
+    }
+
+    // 접두사가 경로 세그먼트 단위로 일치하는지 검사("/api"는 "/api", "/api/..."와 일치)
+    pub fn matches(&self, path: &str) -> bool {
+        let prefix = self.prefix.trim_end_matches('/');
+        match path.strip_prefix(prefix) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/') || prefix.is_empty(),
+            None => false,
+        }
+    }
+}
+
+pub fn default_routes() -> Vec<RouteSection> {
+    vec![
+        RouteSection::new("/api", HandlerKind::WebService),
+        RouteSection::new("/metrics", HandlerKind::Metrics),
+        RouteSection::new("/", HandlerKind::Static),
+    ]
+}
+
+// 리버스 프록시가 요청을 라운드 로빈으로 나눠 보내는 업스트림 서버 묶음
+// 연속으로 max_fails번 실패한 서버는 fail_timeout_secs 동안 후보에서 빠짐(패시브 헬스 체크)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpstreamSection {
+    pub servers: Vec<String>,
+    pub max_fails: u32,
+    pub fail_timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub read_timeout_secs: u64,
+}
+
+impl Default for UpstreamSection {
+    fn default() -> Self {
+        UpstreamSection {
+            servers: Vec::new(),
+            max_fails: 3,
+            fail_timeout_secs: 10,
+            connect_timeout_secs: 5,
+            read_timeout_secs: 30,
+        }
+    }
+}
+
+// Host 헤더로 선택되는 가상 호스트, 지정하지 않은 항목은 기본 설정을 그대로 사용
+// hostnames에는 정확한 이름이나 *.example.com 같은 와일드카드 서브도메인을 쓸 수 있음
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VirtualHostSection {
+    pub hostnames: Vec<String>,
+    pub public_path: Option<PathBuf>,
+    pub data_path: Option<PathBuf>,
+    pub cache: Option<CacheSection>,
+    pub routes: Option<Vec<RouteSection>>,
+}
+
+// 파일 확장자별 Cache-Control 정책, 일치하는 확장자가 없으면 default를 사용
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheSection {
+    pub default: Option<String>,
+    pub extensions: HashMap<String, String>,
+}
+
+// HTTPS 리스너 설정, 첫 번째 인증서가 SNI가 없거나 일치하지 않을 때의 기본 인증서가 됨
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsSection {
+    pub bind: Vec<String>,
+    pub certificates: Vec<CertificateSection>,
+    #[serde(default)]
+    pub redirect_http: bool,
+    pub hsts: Option<HstsSection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CertificateSection {
+    #[serde(default)]
+    pub hostnames: Vec<String>,
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HstsSection {
+    pub max_age_secs: u64,
+    pub include_subdomains: bool,
+    pub preload: bool,
+}
+
+impl Default for HstsSection {
+    fn default() -> Self {
+        HstsSection {
+            max_age_secs: 365 * 24 * 60 * 60,
+            include_subdomains: false,
+            preload: false,
+        }
+    }
+}
+
+impl HstsSection {
+    // Strict-Transport-Security 헤더 값
+    pub fn header_value(&self) -> String {
+        let mut value = format!("max-age={}", self.max_age_secs);
+        if self.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if self.preload {
+            value.push_str("; preload");
+        }
+        value
+    }
+}
+
+impl TlsSection {
+    // HTTP -> HTTPS 리다이렉트에서 사용할 HTTPS 포트(첫 번째 바인드 주소 기준)
+    pub fn https_port(&self) -> Option<u16> {
+        self.bind
+            .first()
+            .and_then(|addr| addr.rsplit_once(':'))
+            .and_then(|(_, port)| port.parse().ok())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogSection {
+    pub level: LogLevel,
+}
+
+// output은 "stdout" 또는 로그 파일 경로, 파일은 max_file_bytes를 넘으면 회전함
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccessLogSection {
+    pub enabled: bool,
+    pub format: AccessLogFormat,
+    pub output: String,
+    pub max_file_bytes: u64,
+    pub max_files: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogFormat {
+    Common,
+    #[default]
+    Combined,
+    Json,
+}
+
+impl Default for AccessLogSection {
+    fn default() -> Self {
+        AccessLogSection {
+            enabled: true,
+            format: AccessLogFormat::default(),
+            output: "stdout".to_string(),
+            max_file_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+impl std::str::FromStr for AccessLogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "common" => Ok(AccessLogFormat::Common),
+            "combined" => Ok(AccessLogFormat::Combined),
+            "json" => Ok(AccessLogFormat::Json),
+            _ => Err("common, combined, json 중 하나여야 합니다".to_string()),
+        }
+    }
+}
+
+// 세션 쿠키와 저장소 설정
+// store는 memory(프로세스 메모리) 또는 file(path 디렉터리에 세션마다 JSON 파일 하나)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionSection {
+    pub cookie_name: String,
+    pub ttl_secs: u64, // 마지막 요청 뒤로 이 시간 동안 요청이 없으면 만료
+    pub store: SessionStoreKind,
+    pub path: PathBuf,
+    // 이 키의 값이 바뀌면(로그인, 권한 변경) 세션 ID를 새로 발급
+    pub privileged_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStoreKind {
+    #[default]
+    Memory,
+    File,
+}
+
+impl Default for SessionSection {
+    fn default() -> Self {
+        SessionSection {
+            cookie_name: "sid".to_string(),
+            ttl_secs: 30 * 60,
+            store: SessionStoreKind::default(),
+            path: PathBuf::from(format!("{}/sessions", env!("CARGO_MANIFEST_DIR"))),
+            privileged_keys: vec!["user".to_string(), "role".to_string()],
+        }
+    }
+}
+
+// 라우트 그룹의 인증 방식, htpasswd(Basic)와 tokens(Bearer) 중 하나 이상이 필요
+// htpasswd는 "사용자:해시" 행들(bcrypt 또는 argon2 해시, 예: htpasswd -nbB 사용자 비밀번호)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthSection {
+    pub realm: String,
+    pub htpasswd: Option<PathBuf>,
+    pub tokens: Vec<String>,
+}
+
+impl Default for AuthSection {
+    fn default() -> Self {
+        AuthSection {
+            realm: "httpserver".to_string(),
+            htpasswd: None,
+            tokens: Vec::new(),
+        }
+    }
+}
+
+// 클라이언트마다 토큰 버킷으로 요청 수를 제한(한도를 넘으면 429)
+// window_secs 동안 requests개의 비율로 토큰이 다시 차고, 한 번에 burst개까지 몰아서 보낼 수 있음
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitSection {
+    pub requests: u32,
+    pub window_secs: u64,
+    pub burst: u32, // 0이면 requests와 같음
+    pub key: RateLimitKey,
+}
+
+// 클라이언트를 구분하는 기준, token은 라우트의 인증을 통과한 Authorization 헤더 값(그 밖의 요청은 IP)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitKey {
+    #[default]
+    Peer,
+    Token,
+}
+
+impl Default for RateLimitSection {
+    fn default() -> Self {
+        RateLimitSection {
+            requests: 60,
+            window_secs: 60,
+            burst: 0,
+            key: RateLimitKey::default(),
+        }
+    }
+}
+
+// 다른 오리진의 브라우저 요청을 허용하는 CORS 설정(섹션이 없으면 CORS 헤더를 보내지 않음)
+// allowed_origins의 "*"는 모든 오리진을 허용(allow_credentials와 함께 쓸 수 없음)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsSection {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub allow_credentials: bool, // 쿠키나 Authorization 헤더를 담은 요청을 허용
+    pub max_age_secs: u64, // 브라우저가 프리플라이트 결과를 캐시하는 시간
+}
+
+impl CorsSection {
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+}
+
+impl Default for CorsSection {
+    fn default() -> Self {
+        CorsSection {
+            allowed_origins: Vec::new(),
+            allowed_methods: vec!["GET".to_string(), "POST".to_string(), "DELETE".to_string()],
+            allowed_headers: vec!["Content-Type".to_string(), "Authorization".to_string()],
+            allow_credentials: false,
+            max_age_secs: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            server: ServerSection::default(),
+            static_files: StaticSection::default(),
+            cache: CacheSection::default(),
+            log: LogSection::default(),
+            access_log: AccessLogSection::default(),
+            session: SessionSection::default(),
+            tls: None,
+            cors: None,
+            routes: default_routes(),
+            upstreams: HashMap::new(),
+            auth: HashMap::new(),
+            rate_limits: HashMap::new(),
+            vhosts: Vec::new(),
+            sites: Vec::new(),
+        }
+    }
+}
+
+impl Default for ServerSection {
+    fn default() -> Self {
+        ServerSection {
+            bind: vec!["localhost:3000".to_string()],
+            workers: 4,
+            read_timeout_secs: 30,
+            write_timeout_secs: 30,
+            header_read_timeout_secs: 10,
+            keep_alive_timeout_secs: 5,
+            max_request_line_bytes: 8 * 1024,
+            max_header_count: 100,
+            max_header_bytes: 8 * 1024,
+            max_body_bytes: 1024 * 1024,
+            strict_parsing: true,
+            shutdown_timeout_secs: 30,
+            unix_socket_mode: 0o660,
+        }
+    }
+}
+
+impl Default for StaticSection {
+    fn default() -> Self {
+        StaticSection {
+            public_path: PathBuf::from(format!("{}/public", env!("CARGO_MANIFEST_DIR"))),
+            data_path: PathBuf::from(format!("{}/data", env!("CARGO_MANIFEST_DIR"))),
+            templates: true,
+        }
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            _ => Err("error, warn, info, debug 중 하나여야 합니다".to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io { path: PathBuf, source: std::io::Error },
+    Parse { path: PathBuf, message: String },
+    InvalidValue { key: String, value: String, reason: String },
+    UnknownFlag(String),
+    MissingFlagValue(String),
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "설정 파일 {}을(를) 읽을 수 없습니다: {}", path.display(), source)
+            }
+            ConfigError::Parse { path, message } => {
+                write!(f, "설정 파일 {} 파싱 실패: {}", path.display(), message)
+            }
+            ConfigError::InvalidValue { key, value, reason } => {
+                write!(f, "{}의 값 '{}'이(가) 잘못되었습니다: {}", key, value, reason)
+            }
+            ConfigError::UnknownFlag(flag) => write!(f, "알 수 없는 플래그: {}", flag),
+            ConfigError::MissingFlagValue(flag) => write!(f, "{} 플래그에 값이 없습니다", flag),
+            ConfigError::Invalid(problems) => {
+                writeln!(f, "설정 검증 실패:")?;
+                for problem in problems {
+                    writeln!(f, "  - {}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl ServerConfig {
+    // 프로세스의 명령행 인자와 환경변수로 설정을 로드
+    pub fn load() -> Result<ServerConfig, ConfigError> {
+        let args: Vec<String> = env::args().skip(1).collect();
+        let vars: HashMap<String, String> = env::vars().collect();
+        ServerConfig::load_from(&args, &vars)
+    }
+
+    pub fn load_from(
+        args: &[String],
+        vars: &HashMap<String, String>,
+    ) -> Result<ServerConfig, ConfigError> {
+        let flags = parse_flags(args)?;
+
+        // 설정 파일 경로: --config 플래그 > HTTPSERVER_CONFIG 환경변수 > 크레이트 루트의 httpserver.toml
+        let explicit_path = flags
+            .get("config")
+            .or_else(|| vars.get("HTTPSERVER_CONFIG"))
+            .map(PathBuf::from);
+        let mut config = match explicit_path {
+            Some(path) => ServerConfig::from_file(&path)?,
+            None => {
+                let path = PathBuf::from(format!("{}/httpserver.toml", env!("CARGO_MANIFEST_DIR")));
+                if path.exists() {
+                    ServerConfig::from_file(&path)?
+                } else {
+                    ServerConfig::default()
+                }
+            }
+        };
+
+        // 환경변수로 덮어씀(기존의 PUBLIC_PATH, DATA_PATH도 그대로 지원)
+        for (var, key) in ENV_KEYS {
+            if let Some(value) = vars.get(*var) {
+                config.set(key, value)?;
+            }
+        }
+
+        // 명령행 플래그가 가장 높은 우선순위를 가짐
+        for (key, value) in flags.iter().filter(|(k, _)| k.as_str() != "config") {
+            config.set(key, value)?;
+        }
+
+        config.validate()?;
+        config.resolve_vhosts();
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<ServerConfig, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut config: ServerConfig =
+            toml::from_str(&contents).map_err(|e| ConfigError::Parse {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })?;
+
+        // 설정 파일 안의 상대 경로는 설정 파일이 있는 디렉터리를 기준으로 해석
+        if let Some(base) = path.parent() {
+            config.static_files.public_path = base.join(&config.static_files.public_path);
+            config.static_files.data_path = base.join(&config.static_files.data_path);
+            if config.access_log.output != "stdout" {
+                config.access_log.output =
+                    base.join(&config.access_log.output).display().to_string();
+            }
+            config.session.path = base.join(&config.session.path);
+            for auth in config.auth.values_mut() {
+                if let Some(htpasswd) = &mut auth.htpasswd {
+                    *htpasswd = base.join(&*htpasswd);
+                }
+            }
+            if let Some(tls) = &mut config.tls {
+                for cert in &mut tls.certificates {
+                    cert.cert_path = base.join(&cert.cert_path);
+                    cert.key_path = base.join(&cert.key_path);
+                }
+            }
+            for vhost in &mut config.vhosts {
+                for path in [&mut vhost.public_path, &mut vhost.data_path].into_iter().flatten() {
+                    *path = base.join(&*path);
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    // 환경변수와 플래그가 공유하는 키 이름으로 값 하나를 덮어씀
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        match key {
+            "bind" => {
+                self.server.bind = value
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+            }
+            "workers" => self.server.workers = parse_value(key, value)?,
+            "read-timeout" => self.server.read_timeout_secs = parse_value(key, value)?,
+            "write-timeout" => self.server.write_timeout_secs = parse_value(key, value)?,
+            "header-read-timeout" => {
+                self.server.header_read_timeout_secs = parse_value(key, value)?
+            }
+            "keep-alive-timeout" => self.server.keep_alive_timeout_secs = parse_value(key, value)?,
+            "max-request-line-bytes" => {
+                self.server.max_request_line_bytes = parse_value(key, value)?
+            }
+            "max-header-count" => self.server.max_header_count = parse_value(key, value)?,
+            "max-header-bytes" => self.server.max_header_bytes = parse_value(key, value)?,
+            "max-body-bytes" => self.server.max_body_bytes = parse_value(key, value)?,
+            "strict-parsing" => self.server.strict_parsing = parse_value(key, value)?,
+            "shutdown-timeout" => self.server.shutdown_timeout_secs = parse_value(key, value)?,
+            "unix-socket-mode" => {
+                // 플래그와 환경변수는 "660"이나 "0o660" 모두 8진수로 해석
+                let digits = value.strip_prefix("0o").unwrap_or(value);
+                self.server.unix_socket_mode =
+                    u32::from_str_radix(digits, 8).map_err(|e| ConfigError::InvalidValue {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                        reason: e.to_string(),
+                    })?;
+            }
+            "public-path" => self.static_files.public_path = PathBuf::from(value),
+            "data-path" => self.static_files.data_path = PathBuf::from(value),
+            "log-level" => self.log.level = parse_value(key, value)?,
+            "access-log" => self.access_log.output = value.to_string(),
+            "access-log-format" => self.access_log.format = parse_value(key, value)?,
+            _ => return Err(ConfigError::UnknownFlag(format!("--{}", key))),
+        }
+        Ok(())
+    }
+
+    // 시작 시점에 모든 문제를 한꺼번에 모아서 보고
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if self.server.bind.is_empty() {
+            problems.push("server.bind에 최소 하나의 주소가 필요합니다".to_string());
+        }
+        for addr in &self.server.bind {
+            if let Err(reason) = validate_listen_addr(addr) {
+                problems.push(format!("server.bind '{}': {}", addr, reason));
+            }
+        }
+        if self.server.unix_socket_mode > 0o777 {
+            problems.push(format!("server.unix_socket_mode {:o}은(는) 0o777 이하여야 합니다", self.server.unix_socket_mode));
+        }
+        if self.server.workers == 0 {
+            problems.push("server.workers는 1 이상이어야 합니다".to_string());
+        }
+        if self.server.read_timeout_secs == 0 {
+            problems.push("server.read_timeout_secs는 1 이상이어야 합니다".to_string());
+        }
+        if self.server.write_timeout_secs == 0 {
+            problems.push("server.write_timeout_secs는 1 이상이어야 합니다".to_string());
+        }
+        if self.server.header_read_timeout_secs == 0 {
+            problems.push("server.header_read_timeout_secs는 1 이상이어야 합니다".to_string());
+        }
+        if self.server.max_request_line_bytes == 0 {
+            problems.push("server.max_request_line_bytes는 1 이상이어야 합니다".to_string());
+        }
+        if self.server.max_header_bytes == 0 {
+            problems.push("server.max_header_bytes는 1 이상이어야 합니다".to_string());
+        }
+        if !self.static_files.public_path.is_dir() {
+            problems.push(format!(
+                "static.public_path '{}'은(는) 디렉터리가 아닙니다",
+                self.static_files.public_path.display()
+            ));
+        }
+        if !self.static_files.data_path.is_dir() {
+            problems.push(format!(
+                "static.data_path '{}'은(는) 디렉터리가 아닙니다",
+                self.static_files.data_path.display()
+            ));
+        }
+
+        let cookie_name = &self.session.cookie_name;
+        if cookie_name.is_empty()
+            || !cookie_name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+        {
+            problems.push(format!(
+                "session.cookie_name '{}'에는 영문자, 숫자, '_', '-'만 쓸 수 있습니다",
+                cookie_name
+            ));
+        }
+        if self.session.ttl_secs == 0 {
+            problems.push("session.ttl_secs는 1 이상이어야 합니다".to_string());
+        }
+
+        if self.access_log.enabled && self.access_log.output.is_empty() {
+            problems.push("access_log.output은 stdout 또는 파일 경로여야 합니다".to_string());
+        }
+        if self.access_log.max_file_bytes == 0 {
+            problems.push("access_log.max_file_bytes는 1 이상이어야 합니다".to_string());
+        }
+
+        for (name, upstream) in &self.upstreams {
+            if upstream.servers.is_empty() {
+                problems.push(format!("upstreams.{}에 최소 하나의 서버가 필요합니다", name));
+            }
+            for addr in &upstream.servers {
+                if let Err(reason) = validate_bind_addr(addr) {
+                    problems.push(format!("upstreams.{} '{}': {}", name, addr, reason));
+                }
+            }
+            if upstream.max_fails == 0 {
+                problems.push(format!("upstreams.{}.max_fails는 1 이상이어야 합니다", name));
+            }
+            if upstream.connect_timeout_secs == 0 || upstream.read_timeout_secs == 0 {
+                problems.push(format!("upstreams.{}의 타임아웃은 1 이상이어야 합니다", name));
+            }
+        }
+        if let Some(cors) = &self.cors {
+            if cors.allowed_origins.is_empty() {
+                problems.push("cors.allowed_origins에 최소 하나의 오리진이 필요합니다".to_string());
+            }
+            for origin in cors.allowed_origins.iter().filter(|o| o.as_str() != "*") {
+                if let Err(reason) = validate_origin(origin) {
+                    problems.push(format!("cors.allowed_origins '{}': {}", origin, reason));
+                }
+            }
+            if cors.allow_credentials && cors.allows_any_origin() {
+                problems.push(
+                    "cors.allow_credentials = true이면 allowed_origins에 \"*\"를 쓸 수 없습니다"
+                        .to_string(),
+                );
+            }
+            if cors.allowed_methods.iter().any(|m| m.is_empty() || m.bytes().any(|b| !b.is_ascii_uppercase())) {
+                problems.push("cors.allowed_methods는 GET, POST처럼 대문자 메서드 이름이어야 합니다".to_string());
+            }
+        }
+        for (name, limit) in &self.rate_limits {
+            if limit.requests == 0 || limit.window_secs == 0 {
+                problems.push(format!(
+                    "rate_limits.{}의 requests와 window_secs는 1 이상이어야 합니다",
+                    name
+                ));
+            }
+        }
+        for (name, auth) in &self.auth {
+            match &auth.htpasswd {
+                Some(path) if !path.is_file() => problems.push(format!(
+                    "auth.{}.htpasswd '{}'은(는) 파일이 아닙니다",
+                    name,
+                    path.display()
+                )),
+                None if auth.tokens.is_empty() => problems.push(format!(
+                    "auth.{}에 htpasswd나 tokens 중 하나 이상이 필요합니다",
+                    name
+                )),
+                _ => {}
+            }
+            if auth.tokens.iter().any(|token| token.is_empty()) {
+                problems.push(format!("auth.{}.tokens에 빈 토큰이 있습니다", name));
+            }
+        }
+        let vhost_routes = self.vhosts.iter().filter_map(|v| v.routes.as_ref()).flatten();
+        for route in self.routes.iter().chain(vhost_routes) {
+            if let Some(name) = &route.rate_limit {
+                if !self.rate_limits.contains_key(name) {
+                    problems.push(format!(
+                        "라우트 '{}'의 요청 한도 '{}'이(가) rate_limits에 없습니다",
+                        route.prefix, name
+                    ));
+                }
+            }
+            if let Some(name) = &route.auth {
+                if !self.auth.contains_key(name) {
+                    problems.push(format!(
+                        "라우트 '{}'의 인증 설정 '{}'이(가) auth에 없습니다",
+                        route.prefix, name
+                    ));
+                }
+            }
+            if route.handler == HandlerKind::WebSocket {
+                match &route.endpoint {
+                    Some(name) if WEBSOCKET_ENDPOINTS.contains(&name.as_str()) => {}
+                    Some(name) => problems.push(format!(
+                        "라우트 '{}'의 웹소켓 엔드포인트 '{}'을(를) 알 수 없습니다({} 중 하나)",
+                        route.prefix,
+                        name,
+                        WEBSOCKET_ENDPOINTS.join(", ")
+                    )),
+                    None => problems.push(format!(
+                        "웹소켓 라우트 '{}'에 endpoint가 필요합니다",
+                        route.prefix
+                    )),
+                }
+            }
+            if route.handler != HandlerKind::Proxy {
+                continue;
+            }
+            match &route.upstream {
+                Some(name) if self.upstreams.contains_key(name) => {}
+                Some(name) => problems.push(format!(
+                    "라우트 '{}'의 업스트림 '{}'이(가) upstreams에 없습니다",
+                    route.prefix, name
+                )),
+                None => problems.push(format!(
+                    "프록시 라우트 '{}'에 upstream이 필요합니다",
+                    route.prefix
+                )),
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for vhost in &self.vhosts {
+            if vhost.hostnames.is_empty() {
+                problems.push("vhosts 항목마다 최소 하나의 hostnames가 필요합니다".to_string());
+            }
+            for name in &vhost.hostnames {
+                if !seen.insert(name.to_ascii_lowercase()) {
+                    problems.push(format!("vhosts에 '{}'이(가) 중복되었습니다", name));
+                }
+            }
+            for path in [&vhost.public_path, &vhost.data_path].into_iter().flatten() {
+                if !path.is_dir() {
+                    problems.push(format!("vhost 경로 '{}'은(는) 디렉터리가 아닙니다", path.display()));
+                }
+            }
+        }
+
+        if let Some(tls) = &self.tls {
+            if tls.bind.is_empty() {
+                problems.push("tls.bind에 최소 하나의 주소가 필요합니다".to_string());
+            }
+            for addr in &tls.bind {
+                if listener::unix_path(addr).is_some() {
+                    problems.push(format!("tls.bind '{}': 유닉스 도메인 소켓은 TLS를 지원하지 않습니다", addr));
+                } else if let Err(reason) = validate_bind_addr(addr) {
+                    problems.push(format!("tls.bind '{}': {}", addr, reason));
+                }
+            }
+            if tls.certificates.is_empty() {
+                problems.push("tls.certificates에 최소 하나의 인증서가 필요합니다".to_string());
+            }
+            for cert in &tls.certificates {
+                for path in [&cert.cert_path, &cert.key_path] {
+                    if !path.is_file() {
+                        problems.push(format!("tls 인증서 파일 '{}'이(가) 없습니다", path.display()));
+                    }
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    // 가상 호스트마다 기본 설정에 덮어쓴 설정을 미리 만들어 둠
+    pub fn resolve_vhosts(&mut self) {
+        let mut base = self.clone();
+        base.vhosts.clear();
+        base.sites.clear();
+
+        self.sites = self
+            .vhosts
+            .iter()
+            .map(|vhost| {
+                let mut site = base.clone();
+                if let Some(path) = &vhost.public_path {
+                    site.static_files.public_path = path.clone();
+                }
+                if let Some(path) = &vhost.data_path {
+                    site.static_files.data_path = path.clone();
+                }
+                if let Some(cache) = &vhost.cache {
+                    site.cache = cache.clone();
+                }
+                if let Some(routes) = &vhost.routes {
+                    site.routes = routes.clone();
+                }
+                site
+            })
+            .collect();
+    }
+
+    // Host 헤더 값에 해당하는 사이트 설정, 일치하는 가상 호스트가 없으면 기본 설정
+    // 정확한 이름이 와일드카드보다 우선하고, 와일드카드는 더 긴(구체적인) 것이 우선함
+    pub fn site_for(&self, host: &str) -> &ServerConfig {
+        let host = normalize_host(host);
+        let mut best: Option<(usize, &ServerConfig)> = None;
+        for (vhost, site) in self.vhosts.iter().zip(&self.sites) {
+            for name in &vhost.hostnames {
+                let name = name.to_ascii_lowercase();
+                let score = if name == host {
+                    usize::MAX
+                } else if let Some(suffix) = name.strip_prefix("*.") {
+                    if host.ends_with(&format!(".{}", suffix)) {
+                        suffix.len()
+                    } else {
+                        continue;
+                    }
+                } else {
+                    continue;
+                };
+                if best.is_none_or(|(best_score, _)| score > best_score) {
+                    best = Some((score, site));
+                }
+            }
+        }
+        best.map(|(_, site)| site).unwrap_or(self)
+    }
+
+    // 파일 이름의 확장자에 해당하는 Cache-Control 값
+    pub fn cache_policy(&self, file_name: &str) -> Option<&str> {
+        let extension = Path::new(file_name).extension().and_then(|e| e.to_str());
+        extension
+            .and_then(|ext| self.cache.extensions.get(ext))
+            .or(self.cache.default.as_ref())
+            .map(|s| s.as_str())
+    }
+}
+
+// 포트와 마지막 점을 떼고 소문자로 바꾼 호스트 이름("Example.COM.:3000" -> "example.com")
+fn normalize_host(host: &str) -> String {
+    let host = host.trim();
+    let host = match host.strip_prefix('[') {
+        // [::1]:3000 형태의 IPv6 주소
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => host.split(':').next().unwrap_or(host),
+    };
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+// 환경변수 이름과 설정 키의 대응
+const ENV_KEYS: &[(&str, &str)] = &[
+    ("HTTPSERVER_BIND", "bind"),
+    ("HTTPSERVER_WORKERS", "workers"),
+    ("HTTPSERVER_READ_TIMEOUT", "read-timeout"),
+    ("HTTPSERVER_WRITE_TIMEOUT", "write-timeout"),
+    ("HTTPSERVER_HEADER_READ_TIMEOUT", "header-read-timeout"),
+    ("HTTPSERVER_KEEP_ALIVE_TIMEOUT", "keep-alive-timeout"),
+    ("HTTPSERVER_MAX_REQUEST_LINE_BYTES", "max-request-line-bytes"),
+    ("HTTPSERVER_MAX_HEADER_COUNT", "max-header-count"),
+    ("HTTPSERVER_MAX_HEADER_BYTES", "max-header-bytes"),
+    ("HTTPSERVER_MAX_BODY_BYTES", "max-body-bytes"),
+    ("HTTPSERVER_STRICT_PARSING", "strict-parsing"),
+    ("HTTPSERVER_SHUTDOWN_TIMEOUT", "shutdown-timeout"),
+    ("HTTPSERVER_UNIX_SOCKET_MODE", "unix-socket-mode"),
+    ("PUBLIC_PATH", "public-path"),
+    ("DATA_PATH", "data-path"),
+    ("HTTPSERVER_LOG_LEVEL", "log-level"),
+    ("HTTPSERVER_ACCESS_LOG", "access-log"),
+    ("HTTPSERVER_ACCESS_LOG_FORMAT", "access-log-format"),
+];
+
+// --key value 또는 --key=value 형태의 플래그를 파싱
+fn parse_flags(args: &[String]) -> Result<HashMap<String, String>, ConfigError> {
+    let mut flags = HashMap::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            return Err(ConfigError::UnknownFlag(arg.clone()));
+        };
+        let (key, value) = match flag.split_once('=') {
+            Some((k, v)) => (k.to_string(), v.to_string()),
+            None => match iter.next() {
+                Some(v) => (flag.to_string(), v.clone()),
+                None => return Err(ConfigError::MissingFlagValue(arg.clone())),
+            },
+        };
+        flags.insert(key, value);
+    }
+    Ok(flags)
+}
+
+fn parse_value<T>(key: &str, value: &str) -> Result<T, ConfigError>
+where
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|e: T::Err| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+// 오리진은 "scheme://host[:port]" 형식(경로나 끝의 '/' 없음)
+fn validate_origin(origin: &str) -> Result<(), String> {
+    let host = match origin.split_once("://") {
+        Some(("http" | "https", host)) => host,
+        _ => return Err("http:// 또는 https://로 시작해야 합니다".to_string()),
+    };
+    if host.is_empty() || host.contains('/') {
+        return Err("경로 없이 scheme://host[:port] 형식이어야 합니다".to_string());
+    }
+    Ok(())
+}
+
+// server.bind는 유닉스 도메인 소켓 경로도 허용
+fn validate_listen_addr(addr: &str) -> Result<(), String> {
+    match listener::unix_path(addr) {
+        Some(path) if path.as_os_str().is_empty() => Err("소켓 파일 경로가 비어 있습니다".to_string()),
+        Some(_) => Ok(()),
+        None => validate_bind_addr(addr),
+    }
+}
+
+// 호스트:포트 형태인지만 검사(이름 해석은 바인딩할 때 수행)
+fn validate_bind_addr(addr: &str) -> Result<(), String> {
+    let (host, port) = addr
+        .rsplit_once(':')
+        .ok_or_else(|| "호스트:포트 형식이어야 합니다".to_string())?;
+    if host.is_empty() {
+        return Err("호스트가 비어 있습니다".to_string());
+    }
+    port.parse::<u16>()
+        .map(|_| ())
+        .map_err(|_| format!("포트 '{}'이(가) 올바르지 않습니다", port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let config = ServerConfig::default();
+        assert_eq!(config.server.bind, vec!["localhost:3000".to_string()]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_toml_env_and_flags_are_layered() {
+        let dir = env::temp_dir().join(format!("httpserver-config-{}", std::process::id()));
+        fs::create_dir_all(dir.join("www")).unwrap();
+        fs::create_dir_all(dir.join("data")).unwrap();
+        let path = dir.join("server.toml");
+        fs::write(
+            &path,
+            "[server]\nbind = [\"127.0.0.1:8080\"]\nworkers = 2\n\n\
+             [static]\npublic_path = \"www\"\ndata_path = \"data\"\n\n\
+             [cache]\ndefault = \"no-cache\"\n\n[cache.extensions]\ncss = \"max-age=60\"\n",
+        )
+        .unwrap();
+
+        let mut vars = HashMap::new();
+        vars.insert("HTTPSERVER_CONFIG".to_string(), path.display().to_string());
+        vars.insert("HTTPSERVER_WORKERS".to_string(), "8".to_string());
+        vars.insert("HTTPSERVER_LOG_LEVEL".to_string(), "debug".to_string());
+
+        let config =
+            ServerConfig::load_from(&args(&["--workers", "16", "--read-timeout=5"]), &vars)
+                .unwrap();
+
+        assert_eq!(config.server.bind, vec!["127.0.0.1:8080".to_string()]);
+        assert_eq!(config.server.workers, 16);
+        assert_eq!(config.server.read_timeout_secs, 5);
+        assert_eq!(config.log.level, LogLevel::Debug);
+        assert_eq!(config.static_files.public_path, dir.join("www"));
+        assert_eq!(config.cache_policy("styles.css"), Some("max-age=60"));
+        assert_eq!(config.cache_policy("index.html"), Some("no-cache"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        let mut config = ServerConfig::default();
+        config.server.bind = vec!["localhost".to_string(), "localhost:99999".to_string()];
+        config.server.workers = 0;
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("'localhost'"));
+        assert!(message.contains("'localhost:99999'"));
+        assert!(message.contains("server.workers"));
+    }
+
+    #[test]
+    fn test_unix_socket_settings() {
+        let mut config = ServerConfig::default();
+        config.server.bind = vec!["unix:/run/httpserver.sock".to_string(), "127.0.0.1:3000".to_string()];
+        assert!(config.validate().is_ok());
+
+        config.set("unix-socket-mode", "0o600").unwrap();
+        assert_eq!(config.server.unix_socket_mode, 0o600);
+        config.set("unix-socket-mode", "640").unwrap();
+        assert_eq!(config.server.unix_socket_mode, 0o640);
+        assert!(config.set("unix-socket-mode", "0o680").is_err());
+
+        config.server.bind = vec!["unix:".to_string()];
+        config.server.unix_socket_mode = 0o1777;
+        config.tls = Some(TlsSection {
+            bind: vec!["unix:/run/https.sock".to_string()],
+            certificates: Vec::new(),
+            redirect_http: false,
+            hsts: None,
+        });
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("server.bind 'unix:'"));
+        assert!(message.contains("server.unix_socket_mode"));
+        assert!(message.contains("tls.bind 'unix:/run/https.sock'"));
+    }
+
+    #[test]
+    fn test_cors_validation() {
+        let mut config = ServerConfig {
+            cors: Some(CorsSection {
+                allowed_origins: vec!["*".to_string(), "https://shop.example/".to_string()],
+                allow_credentials: true,
+                ..CorsSection::default()
+            }),
+            ..ServerConfig::default()
+        };
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("'https://shop.example/'"));
+        assert!(message.contains("allow_credentials"));
+
+        config.cors = Some(CorsSection {
+            allowed_origins: vec!["http://localhost:8080".to_string()],
+            ..CorsSection::default()
+        });
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_route_auth_must_exist() {
+        let mut config = ServerConfig::default();
+        config.routes[0].auth = Some("staff".to_string());
+        config.auth.insert("empty".to_string(), AuthSection::default());
+        config.auth.insert(
+            "missing".to_string(),
+            AuthSection {
+                htpasswd: Some(PathBuf::from("/nonexistent/htpasswd")),
+                ..AuthSection::default()
+            },
+        );
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("'staff'"));
+        assert!(message.contains("auth.empty"));
+        assert!(message.contains("auth.missing.htpasswd"));
+    }
+
+    #[test]
+    fn test_invalid_flag_value() {
+        let err = ServerConfig::load_from(&args(&["--workers", "many"]), &HashMap::new())
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidValue { .. }));
+        let err = ServerConfig::load_from(&args(&["--port", "1"]), &HashMap::new()).unwrap_err();
+        assert!(matches!(err, ConfigError::UnknownFlag(_)));
+    }
+}
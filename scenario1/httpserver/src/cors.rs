@@ -0,0 +1,145 @@
+use super::config::CorsSection;
+use http::httprequest::{HttpRequest, Method};
+use http::httpresponse::HttpResponse;
+
+// 브라우저가 실제 요청을 보내기 전에 허용 여부를 묻는 OPTIONS 요청인지 검사
+pub fn is_preflight(req: &HttpRequest) -> bool {
+    req.method == Method::Options
+        && req.header("Origin").is_some()
+        && req.header("Access-Control-Request-Method").is_some()
+}
+
+// 프리플라이트 응답(인증이나 라우팅 없이 서버가 바로 응답)
+// 오리진, 메서드, 헤더 중 하나라도 허용되지 않으면 허용 헤더를 빼서 브라우저가 실제 요청을 막게 함
+pub fn preflight<'a>(req: &HttpRequest, cors: &CorsSection) -> HttpResponse<'a> {
+    let mut resp = HttpResponse::new("200", None, Some(String::new()));
+    let method = req.header("Access-Control-Request-Method").unwrap_or("");
+    let method_allowed = cors.allowed_methods.iter().any(|m| m == method);
+    let headers_allowed = req
+        .header("Access-Control-Request-Headers")
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|h| !h.is_empty())
+        .all(|h| cors.allowed_headers.iter().any(|allowed| allowed.eq_ignore_ascii_case(h)));
+
+    if allowed_origin(req, cors).is_some() && method_allowed && headers_allowed {
+        resp.add_header("Access-Control-Allow-Methods", cors.allowed_methods.join(", "));
+        if !cors.allowed_headers.is_empty() {
+            resp.add_header("Access-Control-Allow-Headers", cors.allowed_headers.join(", "));
+        }
+        resp.add_header("Access-Control-Max-Age", cors.max_age_secs.to_string());
+    }
+    resp
+}
+
+// 허용된 오리진에서 온 요청의 응답(프리플라이트 포함)에 CORS 헤더를 추가
+// 프록시한 업스트림이 이미 CORS 헤더를 보냈으면 그대로 둠
+pub fn apply(req: &HttpRequest, cors: &CorsSection, resp: &mut HttpResponse) {
+    if resp.header("Access-Control-Allow-Origin").is_some() {
+        return;
+    }
+    // 오리진마다 응답이 달라지므로 캐시가 오리진별로 저장하도록 알림
+    if !cors.allows_any_origin() {
+        let vary = match resp.header("Vary") {
+            Some(vary) => format!("{}, Origin", vary),
+            None => "Origin".to_string(),
+        };
+        resp.add_header("Vary", vary);
+    }
+    let origin = match allowed_origin(req, cors) {
+        Some(origin) => origin,
+        None => return,
+    };
+    resp.add_header("Access-Control-Allow-Origin", origin.to_string());
+    if cors.allow_credentials {
+        resp.add_header("Access-Control-Allow-Credentials", "true");
+    }
+}
+
+// 응답에 쓸 Access-Control-Allow-Origin 값("*"를 허용하면 "*", 아니면 요청의 Origin)
+fn allowed_origin<'r>(req: &'r HttpRequest, cors: &CorsSection) -> Option<&'r str> {
+    let origin = req.header("Origin")?;
+    if cors.allows_any_origin() {
+        Some("*")
+    } else if cors.allowed_origins.iter().any(|o| o.eq_ignore_ascii_case(origin)) {
+        Some(origin)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cors(origins: &[&str], allow_credentials: bool) -> CorsSection {
+        CorsSection {
+            allowed_origins: origins.iter().map(|o| o.to_string()).collect(),
+            allow_credentials,
+            ..CorsSection::default()
+        }
+    }
+
+    fn request(method: &str, headers: &str) -> HttpRequest {
+        format!("{} /api/shipping/orders HTTP/1.1\r\n{}\r\n", method, headers).into()
+    }
+
+    #[test]
+    fn test_preflight() {
+        let cors = cors(&["https://shop.example"], true);
+        let req = request(
+            "OPTIONS",
+            "Origin: https://shop.example\r\nAccess-Control-Request-Method: POST\r\n\
+             Access-Control-Request-Headers: content-type, authorization\r\n",
+        );
+        assert!(is_preflight(&req));
+        let mut resp = preflight(&req, &cors);
+        apply(&req, &cors, &mut resp);
+        assert_eq!(resp.status_code(), "200");
+        assert_eq!(resp.header("Access-Control-Allow-Origin"), Some("https://shop.example"));
+        assert_eq!(resp.header("Access-Control-Allow-Credentials"), Some("true"));
+        assert_eq!(resp.header("Access-Control-Allow-Methods"), Some("GET, POST, DELETE"));
+        assert_eq!(resp.header("Access-Control-Allow-Headers"), Some("Content-Type, Authorization"));
+        assert_eq!(resp.header("Access-Control-Max-Age"), Some("600"));
+
+        // 허용하지 않은 메서드나 헤더를 요청하면 허용 헤더를 보내지 않음
+        let req = request(
+            "OPTIONS",
+            "Origin: https://shop.example\r\nAccess-Control-Request-Method: PUT\r\n",
+        );
+        assert_eq!(preflight(&req, &cors).header("Access-Control-Allow-Methods"), None);
+        let req = request(
+            "OPTIONS",
+            "Origin: https://shop.example\r\nAccess-Control-Request-Method: GET\r\n\
+             Access-Control-Request-Headers: X-Debug\r\n",
+        );
+        assert_eq!(preflight(&req, &cors).header("Access-Control-Allow-Methods"), None);
+
+        // Access-Control-Request-Method가 없는 OPTIONS는 일반 요청
+        assert!(!is_preflight(&request("OPTIONS", "Origin: https://shop.example\r\n")));
+    }
+
+    #[test]
+    fn test_apply_to_actual_response() {
+        let cors_section = cors(&["https://shop.example"], false);
+        let mut resp = HttpResponse::new("200", None, Some("[]".to_string()));
+        resp.add_header("Vary", "Accept");
+        apply(&request("GET", "Origin: https://shop.example\r\n"), &cors_section, &mut resp);
+        assert_eq!(resp.header("Access-Control-Allow-Origin"), Some("https://shop.example"));
+        assert_eq!(resp.header("Access-Control-Allow-Credentials"), None);
+        assert_eq!(resp.header("Vary"), Some("Accept, Origin"));
+
+        // 허용하지 않은 오리진은 CORS 헤더 없이 Vary만 추가
+        let mut resp = HttpResponse::new("200", None, Some("[]".to_string()));
+        apply(&request("GET", "Origin: https://evil.example\r\n"), &cors_section, &mut resp);
+        assert_eq!(resp.header("Access-Control-Allow-Origin"), None);
+        assert_eq!(resp.header("Vary"), Some("Origin"));
+
+        // "*"는 오리진과 관계없이 "*"로 응답
+        let mut resp = HttpResponse::new("200", None, Some("[]".to_string()));
+        apply(&request("GET", "Origin: https://any.example\r\n"), &cors(&["*"], false), &mut resp);
+        assert_eq!(resp.header("Access-Control-Allow-Origin"), Some("*"));
+        assert_eq!(resp.header("Vary"), None);
+    }
+}
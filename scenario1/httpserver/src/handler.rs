@@ -1,17 +1,24 @@
-use http::{httprequest::HttpRequest, httpresponse::HttpResponse};
+use super::config::{LogLevel, ServerConfig};
+use super::metrics::Metrics;
+use super::router::Reply;
+use super::session::Session;
+use super::sse::{self, Event, EventSource};
+use super::template::{self, TemplateError};
+use super::websocket::{Message, WebSocketHandler, WebSocketSender};
+use http::form::{FormError, FormLimits};
+use http::{httprequest::HttpRequest, httprequest::Method, httpresponse::HttpResponse};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::env;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub trait Handler {
-    fn handle(req: &HttpRequest) -> HttpResponse;
+    fn handle<'a>(req: &'a HttpRequest, config: &'a ServerConfig) -> HttpResponse<'a>;
 
-    // httpserver 루트 폴더 안의 퍼블릭 디렉터리에서 파일을 로드할 때 사용
-    fn load_file(file_name: &str) -> Option<String> {
-        let default_path = format!("{}/public", env!("CARGO_MANIFEST_DIR"));
-        let public_path = env::var("PUBLIC_PATH").unwrap_or(default_path);
-        let full_path = format!("{}/{}", public_path, file_name);
+    // 설정된 퍼블릭 디렉터리에서 파일을 로드할 때 사용
+    fn load_file(config: &ServerConfig, file_name: &str) -> Option<String> {
+        let full_path = config.static_files.public_path.join(file_name);
 
         let contents = fs::read_to_string(full_path);
         contents.ok()
@@ -31,47 +38,60 @@ pub struct PageNotFoundHandler;
 
 pub struct WebServiceHandler;
 
+pub struct FormHandler;
+
+pub struct SessionHandler;
+
+pub struct MetricsHandler;
+
 impl Handler for PageNotFoundHandler {
-    fn handle(_req: &HttpRequest) -> HttpResponse {
-        HttpResponse::new("404", None, Self::load_file("404.html"))
+    fn handle<'a>(req: &'a HttpRequest, config: &'a ServerConfig) -> HttpResponse<'a> {
+        not_found_page(req, config)
     }
 }
 
 impl Handler for StaticPageHandler {
-    fn handle(req: &HttpRequest) -> HttpResponse {
+    fn handle<'a>(req: &'a HttpRequest, config: &'a ServerConfig) -> HttpResponse<'a> {
         // 요청된 정적 페이지 리소스의 경로를 가져옴
         let http::httprequest::Resource::Path(s) = &req.resource;
 
         // URI 파싱
-        let route: Vec<&str> = s.split("/").collect();
-        match route[1] {
-            "" => HttpResponse::new("200", None, Self::load_file("index.html")),
-            "health" => HttpResponse::new("200", None, Self::load_file("health.html")),
-            path => match Self::load_file(path) {
-                Some(contents) => {
-                    let mut map: HashMap<&str, &str> = HashMap::new();
-                    if path.ends_with(".css") {
-                        map.insert("Content-Type", "text/css");
-                    } else if path.ends_with(".js") {
-                        map.insert("Content-Type", "text/javascript");
-                    } else {
-                        map.insert("Content-Type", "text/html");
-                    }
-                    HttpResponse::new("200", Some(map), Some(contents))
+        let route: Vec<&str> = s.split('/').collect();
+        // 경로가 '/'로 시작하지 않으면(origin-form이 아닌 요청 대상) 찾을 파일이 없음
+        let file_name = match route.get(1) {
+            Some(&"") => "index.html",
+            Some(&"health") => "health.html",
+            Some(&path) => path,
+            None => return not_found_page(req, config),
+        };
+        // HTML 파일은 요청 데이터로 렌더링한 템플릿
+        match Self::load_file(config, file_name).map(|contents| render_page(req, config, file_name, contents)) {
+            Some(Ok(contents)) => {
+                let mut map: HashMap<&str, &str> = HashMap::new();
+                if file_name.ends_with(".css") {
+                    map.insert("Content-Type", "text/css");
+                } else if file_name.ends_with(".js") {
+                    map.insert("Content-Type", "text/javascript");
+                } else {
+                    map.insert("Content-Type", "text/html");
                 }
-                None => HttpResponse::new("404", None, Self::load_file("404.html")),
-            },
-        }
 
+                // 설정된 캐시 정책을 확장자별로 적용
+                if let Some(policy) = config.cache_policy(file_name) {
+                    map.insert("Cache-Control", policy);
+                }
+                HttpResponse::new("200", Some(map), Some(contents))
+            }
+            Some(Err(e)) => template_error(config, file_name, e),
+            None => not_found_page(req, config),
+        }
     }
 }
 
 // 디스크에서 orders.json 파일 로드
 impl WebServiceHandler {
-    fn load_json() -> Vec<OrderStatus> {
-        let default_path = format!("{}/data", env!("CARGO_MANIFEST_DIR"));
-        let data_path = env::var("DATA_PATH").unwrap_or(default_path);
-        let full_path = format!("{}/{}", data_path, "orders.json");
+    fn load_json(config: &ServerConfig) -> Vec<OrderStatus> {
+        let full_path = config.static_files.data_path.join("orders.json");
         let json_contents = fs::read_to_string(full_path);
         let orders: Vec<OrderStatus> =
             serde_json::from_str(json_contents.unwrap().as_str()).unwrap();
@@ -79,23 +99,472 @@ impl WebServiceHandler {
     }
 }
 
+impl WebServiceHandler {
+    // /api/shipping/orders를 이벤트 스트림으로 요청하면 주문 목록이 바뀔 때마다 보내는 변경 피드
+    pub fn stream<'a>(req: &'a HttpRequest, config: &'a ServerConfig) -> Reply<'a> {
+        let http::httprequest::Resource::Path(s) = &req.resource;
+        let route: Vec<&str> = s.split('/').collect();
+        match (route.get(2).copied(), route.get(3).copied()) {
+            (Some("shipping"), Some("orders")) => sse::stream(req, Box::new(OrderFeed::new(config))),
+            _ => Self::handle(req, config).into(),
+        }
+    }
+}
+
 // Handler 트레이트 구현
 impl Handler for WebServiceHandler {
-    fn handle(req: &HttpRequest) -> HttpResponse {
+    fn handle<'a>(req: &'a HttpRequest, config: &'a ServerConfig) -> HttpResponse<'a> {
         let http::httprequest::Resource::Path(s) = &req.resource;
 
         // URI 파싱
-        let route: Vec<&str> = s.split("/").collect();
+        let route: Vec<&str> = s.split('/').collect();
 
         // 라우트가 /api/shipping/orders이면 JSON을 리턴
-        match route[2] {
-            "shipping" if route.len() > 2 && route[3] == "orders" => {
-                let body = Some(serde_json::to_string(&Self::load_json()).unwrap());
+        match route.get(2).copied() {
+            Some("shipping") if route.get(3) == Some(&"orders") => {
+                let body = Some(serde_json::to_string(&Self::load_json(config)).unwrap());
                 let mut headers: HashMap<&str, &str> = HashMap::new();
                 headers.insert("Content-Type", "application/json");
                 HttpResponse::new("200", Some(headers), body)
             }
-            _ => HttpResponse::new("404", None, Self::load_file("404.html")),
+            _ => not_found_page(req, config),
+        }
+    }
+}
+
+// 폼 제출을 받아서 필드와 저장한 파일의 목록을 JSON으로 리턴
+// 파일은 data_path/uploads에 저장하고, 같은 이름의 파일이 있으면 앞에 번호를 붙임
+impl FormHandler {
+    pub fn handle<'a>(req: &HttpRequest, body: &[u8], config: &'a ServerConfig) -> HttpResponse<'a> {
+        let limits = FormLimits {
+            max_file_bytes: config.server.max_body_bytes as u64,
+            ..FormLimits::default()
+        };
+        // 바이너리 파일이 들어 있을 수 있는 multipart 바디는 원본 바이트에서 파싱
+        let form = match req.content_type().as_deref() {
+            Some("multipart/form-data") => req.multipart(body, &limits),
+            _ => req.form(&limits),
+        };
+        let form = match form {
+            Ok(form) => form,
+            Err(e) => {
+                let status = match e {
+                    FormError::UnsupportedMediaType(_) => "415",
+                    FormError::Malformed(_) => "400",
+                    FormError::TooLarge => "413",
+                    FormError::Io(_) => "500",
+                };
+                return HttpResponse::new(status, None, Some(String::new()));
+            }
+        };
+
+        let upload_dir = config.static_files.data_path.join("uploads");
+        let mut files = Vec::new();
+        for file in form.files {
+            let (name, size) = (file.name.clone(), file.size);
+            let saved_as = upload_name(&upload_dir, file.filename.as_deref().unwrap_or(""));
+            if fs::create_dir_all(&upload_dir)
+                .and_then(|_| file.persist(&upload_dir.join(&saved_as)))
+                .is_err()
+            {
+                return HttpResponse::new("500", None, Some(String::new()));
+            }
+            files.push(serde_json::json!({ "name": name, "saved_as": saved_as, "size": size }));
+        }
+
+        let fields: Vec<_> = form
+            .fields
+            .iter()
+            .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+            .collect();
+        let body = serde_json::json!({ "fields": fields, "files": files }).to_string();
+        let mut headers: HashMap<&str, &str> = HashMap::new();
+        headers.insert("Content-Type", "application/json");
+        HttpResponse::new("200", Some(headers), Some(body))
+    }
+}
+
+// 클라이언트가 보낸 파일 이름에서 경로를 떼고 안전한 문자만 남긴 저장용 이름
+fn upload_name(dir: &Path, filename: &str) -> String {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or("");
+    let name: String = base
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        .collect();
+    let name = match name.trim_start_matches('.') {
+        "" => "upload".to_string(),
+        name => name.to_string(),
+    };
+    let mut candidate = name.clone();
+    let mut n = 1;
+    while dir.join(&candidate).exists() {
+        candidate = format!("{}-{}", n, name);
+        n += 1;
+    }
+    candidate
+}
+
+// 프론트엔드가 자기 세션 값을 다루는 엔드포인트, 응답은 세션의 모든 값을 담은 JSON
+// GET은 조회, POST는 폼 필드로 값을 설정(빈 값이면 삭제), DELETE는 세션을 없앰
+// 권한 키(로그인한 사용자 등)는 서버만 바꿀 수 있으므로 POST로 설정하면 403
+impl SessionHandler {
+    pub fn handle<'a>(req: &HttpRequest, session: &Session, privileged_keys: &[String]) -> HttpResponse<'a> {
+        match req.method {
+            Method::Get => {}
+            Method::Post => {
+                let form = match req.form(&FormLimits::default()) {
+                    Ok(form) => form,
+                    Err(FormError::UnsupportedMediaType(_)) => {
+                        return HttpResponse::new("415", None, Some(String::new()))
+                    }
+                    Err(_) => return HttpResponse::new("400", None, Some(String::new())),
+                };
+                if form.fields.iter().any(|(key, _)| privileged_keys.contains(key)) {
+                    return HttpResponse::new("403", None, Some(String::new()));
+                }
+                for (key, value) in form.fields {
+                    if value.is_empty() {
+                        session.remove(&key);
+                    } else {
+                        session.insert(key, value);
+                    }
+                }
+            }
+            Method::Delete => session.destroy(),
+            _ => {
+                let mut resp = HttpResponse::new("405", None, Some(String::new()));
+                resp.add_header("Allow", "GET, POST, DELETE");
+                return resp;
+            }
+        }
+
+        let values: BTreeMap<_, _> = session.values().into_iter().collect();
+        let mut headers: HashMap<&str, &str> = HashMap::new();
+        headers.insert("Content-Type", "application/json");
+        headers.insert("Cache-Control", "no-store");
+        HttpResponse::new("200", Some(headers), Some(serde_json::to_string(&values).unwrap()))
+    }
+}
+
+// 서버 지표를 Prometheus가 수집하는 텍스트 형식으로 응답
+impl MetricsHandler {
+    pub fn handle<'a>(metrics: &Metrics) -> HttpResponse<'a> {
+        let mut headers: HashMap<&str, &str> = HashMap::new();
+        headers.insert("Content-Type", "text/plain; version=0.0.4; charset=utf-8");
+        headers.insert("Cache-Control", "no-store");
+        HttpResponse::new("200", Some(headers), Some(metrics.render()))
+    }
+}
+
+// 웹소켓으로 주문 상태를 보내는 엔드포인트(order_status)
+// 연결되면 현재 주문 목록을 보내고, 이후 orders.json이 바뀔 때마다 다시 보냄
+// 클라이언트가 "refresh"를 보내면 바로 다시 보냄
+pub struct OrderStatusSocket {
+    orders_path: PathBuf,
+    last_modified: Option<SystemTime>,
+}
+
+impl OrderStatusSocket {
+    pub fn new(config: &ServerConfig) -> OrderStatusSocket {
+        OrderStatusSocket {
+            orders_path: config.static_files.data_path.join("orders.json"),
+            last_modified: None,
+        }
+    }
+
+    fn send_orders(&mut self, socket: &WebSocketSender) {
+        self.last_modified = modified(&self.orders_path);
+        socket.send_text(orders_json(&self.orders_path));
+    }
+}
+
+impl WebSocketHandler for OrderStatusSocket {
+    fn on_open(&mut self, socket: &WebSocketSender) {
+        self.send_orders(socket);
+    }
+
+    fn on_message(&mut self, socket: &WebSocketSender, message: Message) {
+        if message == Message::Text("refresh".to_string()) {
+            self.send_orders(socket);
+        }
+    }
+
+    fn on_idle(&mut self, socket: &WebSocketSender) {
+        if modified(&self.orders_path) != self.last_modified {
+            self.send_orders(socket);
+        }
+    }
+}
+
+// 이벤트 스트림으로 주문 목록의 변경을 보내는 피드
+// 이벤트 ID는 orders.json의 수정 시각이라서 재연결한 클라이언트가 이미 최신 목록을 받았으면 다시 보내지 않음
+pub struct OrderFeed {
+    orders_path: PathBuf,
+    version: Option<String>,
+}
+
+impl OrderFeed {
+    pub fn new(config: &ServerConfig) -> OrderFeed {
+        OrderFeed {
+            orders_path: config.static_files.data_path.join("orders.json"),
+            version: None,
+        }
+    }
+
+    fn current_version(&self) -> Option<String> {
+        modified(&self.orders_path)
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_millis().to_string())
+    }
+
+    fn snapshot(&self) -> Event {
+        Event {
+            event: Some("orders".to_string()),
+            id: self.version.clone(),
+            data: orders_json(&self.orders_path),
+        }
+    }
+}
+
+impl EventSource for OrderFeed {
+    fn open(&mut self, last_event_id: Option<&str>) -> Vec<Event> {
+        self.version = self.current_version();
+        if last_event_id.is_some() && last_event_id == self.version.as_deref() {
+            return Vec::new();
+        }
+        vec![self.snapshot()]
+    }
+
+    fn poll(&mut self) -> Vec<Event> {
+        let version = self.current_version();
+        if version == self.version {
+            return Vec::new();
+        }
+        self.version = version;
+        vec![self.snapshot()]
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// 파일이 없거나 형식이 잘못되었으면 빈 목록
+fn load_orders(path: &Path) -> Vec<OrderStatus> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn orders_json(path: &Path) -> String {
+    serde_json::to_string(&load_orders(path)).unwrap()
+}
+
+// 404.html 페이지로 응답
+fn not_found_page<'a>(req: &HttpRequest, config: &ServerConfig) -> HttpResponse<'a> {
+    let page = StaticPageHandler::load_file(config, "404.html").map(|contents| render_page(req, config, "404.html", contents));
+    match page.transpose() {
+        Ok(body) => HttpResponse::new("404", None, body),
+        Err(e) => template_error(config, "404.html", e),
+    }
+}
+
+// static.templates가 켜져 있으면 HTML 파일을 템플릿으로 렌더링(인클루드도 퍼블릭 디렉터리에서 찾음)
+fn render_page(req: &HttpRequest, config: &ServerConfig, file_name: &str, contents: String) -> Result<String, TemplateError> {
+    if !config.static_files.templates || !file_name.ends_with(".html") {
+        return Ok(contents);
+    }
+    let load = |name: &str| {
+        if name.split('/').any(|part| part == "..") {
+            None
+        } else {
+            StaticPageHandler::load_file(config, name)
         }
+    };
+    template::render(&contents, &page_context(req, config), &load)
+}
+
+// 템플릿에서 쓸 수 있는 데이터
+// path(요청 경로), version(서버 버전), orders(data_path/orders.json),
+// order_summary.total(주문 수), order_summary.statuses(상태별 status, count)
+fn page_context(req: &HttpRequest, config: &ServerConfig) -> serde_json::Value {
+    let http::httprequest::Resource::Path(path) = &req.resource;
+    let orders = load_orders(&config.static_files.data_path.join("orders.json"));
+    let mut statuses: BTreeMap<&str, usize> = BTreeMap::new();
+    for order in &orders {
+        *statuses.entry(order.order_status.as_str()).or_default() += 1;
+    }
+    let statuses: Vec<serde_json::Value> = statuses
+        .into_iter()
+        .map(|(status, count)| serde_json::json!({ "status": status, "count": count }))
+        .collect();
+    serde_json::json!({
+        "path": path,
+        "version": env!("CARGO_PKG_VERSION"),
+        "orders": orders,
+        "order_summary": { "total": orders.len(), "statuses": statuses },
+    })
+}
+
+// 템플릿 오류는 로그에 남기고 500으로 응답
+fn template_error<'a>(config: &ServerConfig, file_name: &str, e: TemplateError) -> HttpResponse<'a> {
+    if config.log.level >= LogLevel::Error {
+        eprintln!("{}: {}", file_name, e);
+    }
+    HttpResponse::new("500", None, Some(String::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_order_feed_resumes_and_reports_changes() {
+        let dir = std::env::temp_dir().join(format!("httpserver-feed-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("orders.json");
+        fs::write(&path, r#"[{"order_id":1,"order_date":"21 Jan 2020","order_status":"Delivered"}]"#)
+            .unwrap();
+        let mut config = ServerConfig::default();
+        config.static_files.data_path = dir.clone();
+
+        // 처음 연결하면 현재 목록을 보내고 수정 시각을 ID로 붙임
+        let mut feed = OrderFeed::new(&config);
+        let events = feed.open(None);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.as_deref(), Some("orders"));
+        assert!(events[0].data.contains("Delivered"));
+        let id = events[0].id.clone().unwrap();
+        assert!(feed.poll().is_empty());
+
+        // 같은 ID로 재연결하면 다시 보내지 않고, 다른 ID면 현재 목록을 보냄
+        assert!(OrderFeed::new(&config).open(Some(&id)).is_empty());
+        assert_eq!(OrderFeed::new(&config).open(Some("0")).len(), 1);
+
+        // 파일이 바뀌면 새 ID로 보냄
+        fs::write(&path, "[]").unwrap();
+        let later = SystemTime::now() + Duration::from_secs(5);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        let events = feed.poll();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "[]");
+        assert_ne!(events[0].id.as_deref(), Some(id.as_str()));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_session_handler() {
+        let session = Session::default();
+        let privileged = vec!["user".to_string()];
+        let request = |s: &str| -> HttpRequest { s.to_string().into() };
+        let post = |body: &str| {
+            request(&format!(
+                "POST /session HTTP/1.1\r\nContent-Type: application/x-www-form-urlencoded\r\n\r\n{}",
+                body
+            ))
+        };
+
+        let resp = SessionHandler::handle(&post("theme=dark&cart=3"), &session, &privileged);
+        assert_eq!(resp.body(), r#"{"cart":"3","theme":"dark"}"#);
+        let resp = SessionHandler::handle(&post("cart="), &session, &privileged);
+        assert_eq!(resp.body(), r#"{"theme":"dark"}"#);
+
+        // 권한 키는 바꿀 수 없음
+        let resp = SessionHandler::handle(&post("user=admin"), &session, &privileged);
+        assert_eq!(resp.status_code(), "403");
+        assert_eq!(session.values().get("user"), None);
+
+        let resp = SessionHandler::handle(&request("GET /session HTTP/1.1\r\n\r\n"), &session, &privileged);
+        assert_eq!(resp.body(), r#"{"theme":"dark"}"#);
+        let resp = SessionHandler::handle(&request("PUT /session HTTP/1.1\r\n\r\n"), &session, &privileged);
+        assert_eq!(resp.status_code(), "405");
+        let resp = SessionHandler::handle(&request("DELETE /session HTTP/1.1\r\n\r\n"), &session, &privileged);
+        assert_eq!(resp.body(), "{}");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_form_handler_saves_uploads() {
+        let dir = std::env::temp_dir().join(format!("httpserver-form-{}", std::process::id()));
+        fs::create_dir_all(dir.join("uploads")).unwrap();
+        fs::write(dir.join("uploads").join("label.png"), "old").unwrap();
+        let mut config = ServerConfig::default();
+        config.static_files.data_path = dir.clone();
+
+        let head = "POST /upload HTTP/1.1\r\nContent-Type: multipart/form-data; boundary=b\r\n\r\n";
+        let mut body = b"--b\r\nContent-Disposition: form-data; name=\"order_id\"\r\n\r\n3\r\n\
+            --b\r\nContent-Disposition: form-data; name=\"label\"; filename=\"../../label.png\"\r\n\r\n"
+            .to_vec();
+        body.extend_from_slice(&[0x89, b'P', b'N', b'G', 0xff]);
+        body.extend_from_slice(b"\r\n--b--\r\n");
+        let req: HttpRequest = head.to_string().into();
+
+        // 경로는 떼고, 이미 있는 파일은 덮어쓰지 않고 번호를 붙여 저장
+        let resp = FormHandler::handle(&req, &body, &config);
+        assert_eq!(resp.status_code(), "200");
+        let json: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        assert_eq!(json["fields"][0]["value"], "3");
+        assert_eq!(json["files"][0]["saved_as"], "1-label.png");
+        assert_eq!(json["files"][0]["size"], 5);
+        let saved = fs::read(dir.join("uploads").join("1-label.png")).unwrap();
+        assert_eq!(saved, [0x89, b'P', b'N', b'G', 0xff]);
+        assert_eq!(fs::read_to_string(dir.join("uploads").join("label.png")).unwrap(), "old");
+
+        // 지원하지 않는 바디와 잘못된 바디
+        let req: HttpRequest = "POST /upload HTTP/1.1\r\nContent-Type: text/plain\r\n\r\n".to_string().into();
+        assert_eq!(FormHandler::handle(&req, b"x", &config).status_code(), "415");
+        let req: HttpRequest = head.to_string().into();
+        assert_eq!(FormHandler::handle(&req, b"--b\r\n", &config).status_code(), "400");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_static_page_templates() {
+        let dir = std::env::temp_dir().join(format!("httpserver-templates-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("orders.json"),
+            r#"[{"order_id":1,"order_date":"21 Jan 2020","order_status":"Delivered"},
+                {"order_id":2,"order_date":"2 Feb 2020","order_status":"Pending"},
+                {"order_id":3,"order_date":"3 Feb 2020","order_status":"Pending"}]"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("index.html"),
+            "{{ order_summary.total }}{% for s in order_summary.statuses %} {{ s.status }}={{ s.count }}{% endfor %}\
+             {% include \"footer.html\" %}",
+        )
+        .unwrap();
+        fs::write(dir.join("footer.html"), "|{{ version }}").unwrap();
+        fs::write(dir.join("404.html"), "missing {{ path }}").unwrap();
+        fs::write(dir.join("broken.html"), "{% for order in orders %}").unwrap();
+        fs::write(dir.join("styles.css"), "p { color: red; } {{ path }}").unwrap();
+        let mut config = ServerConfig::default();
+        config.static_files.public_path = dir.clone();
+        config.static_files.data_path = dir.clone();
+        let get = |path: &str, config: &ServerConfig| {
+            let req: HttpRequest = format!("GET {} HTTP/1.1\r\n\r\n", path).into();
+            let resp = StaticPageHandler::handle(&req, config);
+            (resp.status_code().to_string(), resp.body().to_string())
+        };
+
+        let version = env!("CARGO_PKG_VERSION");
+        assert_eq!(get("/", &config), ("200".to_string(), format!("3 Delivered=1 Pending=2|{}", version)));
+        // 요청 경로는 이스케이프해서 출력
+        assert_eq!(get("/<b>", &config), ("404".to_string(), "missing /&lt;b&gt;".to_string()));
+        assert_eq!(get("/broken.html", &config).0, "500");
+        // '/'로 시작하지 않는 요청 대상은 패닉 없이 404
+        for target in ["x", "http:x"] {
+            assert_eq!(get(target, &config), ("404".to_string(), format!("missing {}", target)));
+        }
+        // HTML이 아닌 파일과 templates = false면 그대로 응답
+        assert_eq!(get("/styles.css", &config).1, "p { color: red; } {{ path }}");
+        config.static_files.templates = false;
+        assert_eq!(get("/broken.html", &config), ("200".to_string(), "{% for order in orders %}".to_string()));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
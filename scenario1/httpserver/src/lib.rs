@@ -0,0 +1,21 @@
+pub mod access_log;
+pub mod auth;
+pub mod config;
+pub mod cors;
+pub mod handler;
+pub mod lifecycle;
+pub mod listener;
+pub mod metrics;
+pub mod pool;
+pub mod proxy;
+pub mod ratelimit;
+pub mod reader;
+pub mod router;
+pub mod server;
+pub mod session;
+pub mod sse;
+pub mod state;
+pub mod template;
+pub mod testing;
+pub mod tls;
+pub mod websocket;
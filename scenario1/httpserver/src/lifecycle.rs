@@ -0,0 +1,275 @@
+use super::config::{LogLevel, ServerConfig};
+use super::listener::{self, Listener};
+use super::state::AppState;
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM, SIGUSR2};
+use signal_hook::iterator::Signals;
+use std::env;
+use std::io;
+use std::net::TcpListener;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::UnixListener;
+use std::os::unix::process::CommandExt;
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+// 새 프로세스에 넘겨준 리스닝 소켓("바인딩 주소=fd"를 쉼표로 구분)
+const LISTEN_FDS_ENV: &str = "HTTPSERVER_LISTEN_FDS";
+
+// 실행 중인 서버를 밖에서 제어(종료, 설정 다시 로드, 리스닝 소켓 넘기기)
+// 모든 리스너가 하나의 핸들을 공유하고, 테스트에서는 서버를 띄운 뒤 shutdown()으로 멈춤
+#[derive(Clone)]
+pub struct ServerHandle {
+    state: Arc<RwLock<Arc<AppState>>>,
+    listeners: Arc<Mutex<Vec<(String, RawFd)>>>, // 바인딩 주소와 리스닝 소켓
+    handed_off: Arc<AtomicBool>, // 새 프로세스가 소켓을 이어받았으면 유닉스 도메인 소켓 파일을 지우지 않음
+}
+
+impl ServerHandle {
+    pub fn new(state: AppState) -> ServerHandle {
+        ServerHandle {
+            state: Arc::new(RwLock::new(Arc::new(state))),
+            listeners: Arc::new(Mutex::new(Vec::new())),
+            handed_off: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // 새 커넥션은 현재 상태로 처리하고, 처리 중인 커넥션은 받을 때의 상태를 끝까지 씀
+    pub fn state(&self) -> Arc<AppState> {
+        Arc::clone(&self.state.read().unwrap())
+    }
+
+    // 새 커넥션을 받지 않게 하고, Server::run()은 처리 중인 커넥션이 끝나면(최대 shutdown_timeout_secs) 리턴
+    pub fn shutdown(&self) {
+        self.state().shut_down();
+    }
+
+    pub fn reload(&self, config: ServerConfig) {
+        let mut state = self.state.write().unwrap();
+        *state = Arc::new(state.reload(config));
+    }
+
+    // Server::run()이 리스닝을 시작하고 끝낼 때 등록하고 해제
+    pub fn add_listener(&self, addr: &str, listener: &Listener) {
+        self.listeners.lock().unwrap().push((addr.to_string(), listener.as_raw_fd()));
+    }
+
+    pub fn remove_listener(&self, addr: &str) {
+        self.listeners.lock().unwrap().retain(|(bound, _)| bound != addr);
+    }
+
+    // 같은 실행 파일과 인자로 새 프로세스를 시작하면서 리스닝 소켓을 넘기고, 이 프로세스는 종료를 시작
+    // 소켓은 닫히지 않고 새 프로세스가 이어서 받으므로 재시작하는 동안에도 커넥션이 거부되지 않음
+    pub fn hand_off(&self) -> io::Result<u32> {
+        let listeners = self.listeners.lock().unwrap().clone();
+        let fds: Vec<RawFd> = listeners.iter().map(|(_, fd)| *fd).collect();
+        let value = listeners
+            .iter()
+            .map(|(addr, fd)| format!("{}={}", addr, fd))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let mut command = Command::new(env::current_exe()?);
+        command.args(env::args_os().skip(1)).env(LISTEN_FDS_ENV, value);
+        // 표준 라이브러리가 만든 소켓은 exec할 때 닫히므로(FD_CLOEXEC) 자식 프로세스에서만 플래그를 해제
+        unsafe {
+            command.pre_exec(move || {
+                for &fd in &fds {
+                    if libc::fcntl(fd, libc::F_SETFD, 0) == -1 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+        self.handed_off.store(true, Ordering::SeqCst);
+        self.shutdown();
+        Ok(child.id())
+    }
+
+    pub fn handed_off(&self) -> bool {
+        self.handed_off.load(Ordering::SeqCst)
+    }
+}
+
+// 이전 프로세스가 넘겨준 리스닝 소켓 중 addr에 바인딩된 것
+pub fn inherited_listener(addr: &str) -> Option<Listener> {
+    let fd = listen_fd(&env::var(LISTEN_FDS_ENV).ok()?, addr)?;
+    // 다시 exec할 때 닫히도록 플래그를 되돌림(열려 있지 않은 fd면 실패하므로 새로 바인딩)
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+        return None;
+    }
+    // 넘겨받은 fd는 이 리스너만 소유함
+    Some(match listener::unix_path(addr) {
+        Some(_) => Listener::Unix(unsafe { UnixListener::from_raw_fd(fd) }),
+        None => Listener::Tcp(unsafe { TcpListener::from_raw_fd(fd) }),
+    })
+}
+
+fn listen_fd(value: &str, addr: &str) -> Option<RawFd> {
+    value.split(',').find_map(|entry| {
+        let (bound, fd) = entry.rsplit_once('=')?;
+        if bound == addr {
+            fd.parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+// 리스닝 소켓에 받을 커넥션이 생길 때까지 최대 timeout 동안 기다림
+pub fn wait_readable(listener: &Listener, timeout: Duration) -> bool {
+    let mut pollfd = libc::pollfd {
+        fd: listener.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    unsafe { libc::poll(&mut pollfd, 1, timeout.as_millis() as libc::c_int) > 0 }
+}
+
+// 시그널을 처리하는 스레드를 시작
+// SIGINT, SIGTERM은 우아한 종료(종료 중에 한 번 더 받으면 즉시 종료), SIGHUP은 설정 다시 로드, SIGUSR2는 무중단 재시작
+pub fn handle_signals(handle: ServerHandle) -> io::Result<()> {
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP, SIGUSR2])?;
+    thread::Builder::new().name("signals".to_string()).spawn(move || {
+        for signal in signals.forever() {
+            let state = handle.state();
+            let info = state.config.log.level >= LogLevel::Info;
+            match signal {
+                SIGHUP => match ServerConfig::load() {
+                    Ok(mut config) => {
+                        for section in keep_startup_sections(&state.config, &mut config) {
+                            eprintln!(
+                                "{} 설정 변경은 다시 로드로 적용되지 않습니다(재시작하거나 SIGUSR2로 새 프로세스에 넘겨야 함)",
+                                section
+                            );
+                        }
+                        handle.reload(config);
+                        if info {
+                            println!("Configuration reloaded");
+                        }
+                    }
+                    Err(e) => eprintln!("설정을 다시 로드할 수 없습니다: {}", e),
+                },
+                SIGUSR2 if state.is_shutting_down() => {}
+                SIGUSR2 => match handle.hand_off() {
+                    Ok(pid) if info => println!("Handed off listeners to process {}", pid),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("새 프로세스를 시작할 수 없습니다: {}", e),
+                },
+                _ if state.is_shutting_down() => process::exit(1),
+                _ => {
+                    if info {
+                        println!("Shutting down");
+                    }
+                    handle.shutdown();
+                }
+            }
+        }
+    })?;
+    Ok(())
+}
+
+// 리스닝 소켓, 워커 풀, TLS 인증서, 액세스 로그 파일은 시작할 때 한 번만 만들므로 다시 로드해도 바뀌지 않음
+// 바뀐 항목은 이전 값으로 되돌려서 실제로 쓰는 설정과 맞추고, 알릴 수 있도록 항목 이름을 리턴
+fn keep_startup_sections(current: &ServerConfig, config: &mut ServerConfig) -> Vec<&'static str> {
+    let mut kept = Vec::new();
+    if config.server.bind != current.server.bind {
+        config.server.bind = current.server.bind.clone();
+        kept.push("server.bind");
+    }
+    if config.server.workers != current.server.workers {
+        config.server.workers = current.server.workers;
+        kept.push("server.workers");
+    }
+    if config.server.unix_socket_mode != current.server.unix_socket_mode {
+        config.server.unix_socket_mode = current.server.unix_socket_mode;
+        kept.push("server.unix_socket_mode");
+    }
+    if config.tls != current.tls {
+        config.tls = current.tls.clone();
+        kept.push("tls");
+    }
+    if config.access_log != current.access_log {
+        config.access_log = current.access_log.clone();
+        kept.push("access_log");
+    }
+    if !kept.is_empty() {
+        // 가상 호스트 설정도 기본 설정에서 복사하므로 되돌린 값으로 다시 만듦
+        config.resolve_vhosts();
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listen_fd() {
+        let value = "0.0.0.0:3000=3,[::]:3443=4";
+        assert_eq!(listen_fd(value, "0.0.0.0:3000"), Some(3));
+        assert_eq!(listen_fd(value, "[::]:3443"), Some(4));
+        assert_eq!(listen_fd(value, "127.0.0.1:3000"), None);
+        assert_eq!(listen_fd("", "0.0.0.0:3000"), None);
+    }
+
+    #[test]
+    fn test_reload_keeps_metrics_and_sessions() {
+        let handle = ServerHandle::new(AppState::new(ServerConfig::default()));
+        let before = handle.state();
+        before.metrics.connection_queued();
+
+        let mut config = ServerConfig::default();
+        config.server.max_body_bytes = 10;
+        handle.reload(config.clone());
+        let after = handle.state();
+        assert_eq!(after.config.server.max_body_bytes, 10);
+        assert!(Arc::ptr_eq(&before.metrics, &after.metrics));
+        assert!(Arc::ptr_eq(&before.sessions, &after.sessions));
+        // 처리 중인 커넥션은 이전 설정을 계속 씀
+        assert_eq!(before.config.server.max_body_bytes, ServerConfig::default().server.max_body_bytes);
+
+        // 세션 설정이 바뀌면 새 저장소를 만듦
+        config.session.ttl_secs += 1;
+        handle.reload(config);
+        assert!(!Arc::ptr_eq(&before.sessions, &handle.state().sessions));
+
+        // 종료 상태는 다시 로드한 상태와 이전 상태가 공유
+        handle.shutdown();
+        assert!(before.is_shutting_down());
+        assert!(handle.state().metrics.render().contains("httpserver_pool_queue_depth 1\n"));
+    }
+    #[test]
+    fn test_reload_keeps_startup_sections() {
+        let current = ServerConfig::default();
+        let mut config = ServerConfig::default();
+        config.access_log.enabled = !current.access_log.enabled;
+        config.server.max_body_bytes = 10;
+        assert_eq!(keep_startup_sections(&current, &mut config), vec!["access_log"]);
+        assert_eq!(config.access_log, current.access_log);
+        assert_eq!(config.server.max_body_bytes, 10);
+
+        let mut unchanged = ServerConfig::default();
+        assert!(keep_startup_sections(&current, &mut unchanged).is_empty());
+    }
+
+    #[test]
+    fn test_reload_keeps_workers() {
+        let handle = ServerHandle::new(AppState::new(ServerConfig::default()));
+        let workers = handle.state().config.server.workers;
+
+        // 워커 풀은 시작할 때 만들어지므로 다시 로드해도 보고되는 설정은 실제 워커 수와 같음
+        let mut config = ServerConfig::default();
+        config.server.workers = workers + 4;
+        config.server.bind = vec!["127.0.0.1:9999".to_string()];
+        let current = handle.state();
+        assert_eq!(keep_startup_sections(&current.config, &mut config), vec!["server.bind", "server.workers"]);
+        handle.reload(config);
+        assert_eq!(handle.state().config.server.workers, workers);
+        assert_eq!(handle.state().config.server.bind, ServerConfig::default().server.bind);
+    }
+}
@@ -0,0 +1,141 @@
+use std::fs::{self, Permissions};
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+
+// 바인딩 주소가 이 접두사로 시작하면 유닉스 도메인 소켓 경로(예: "unix:/run/httpserver.sock")
+const UNIX_PREFIX: &str = "unix:";
+
+// TCP 또는 유닉스 도메인 소켓 리스너(라우팅과 핸들러는 같고 커넥션을 받는 방법만 다름)
+pub enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+pub enum Connection {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+// 유닉스 도메인 소켓 주소면 소켓 파일 경로
+pub fn unix_path(addr: &str) -> Option<&Path> {
+    addr.strip_prefix(UNIX_PREFIX).map(Path::new)
+}
+
+impl Listener {
+    // 유닉스 도메인 소켓은 남아 있는 소켓 파일을 정리하고 바인딩한 뒤 mode로 권한을 설정
+    pub fn bind(addr: &str, mode: u32) -> io::Result<Listener> {
+        match unix_path(addr) {
+            Some(path) => {
+                remove_stale_socket(path)?;
+                let listener = UnixListener::bind(path)?;
+                fs::set_permissions(path, Permissions::from_mode(mode))?;
+                Ok(Listener::Unix(listener))
+            }
+            None => Ok(Listener::Tcp(TcpListener::bind(addr)?)),
+        }
+    }
+
+    pub fn accept(&self) -> io::Result<Connection> {
+        match self {
+            Listener::Tcp(listener) => listener.accept().map(|(stream, _)| Connection::Tcp(stream)),
+            Listener::Unix(listener) => listener.accept().map(|(stream, _)| Connection::Unix(stream)),
+        }
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Listener::Tcp(listener) => listener.set_nonblocking(nonblocking),
+            Listener::Unix(listener) => listener.set_nonblocking(nonblocking),
+        }
+    }
+}
+
+impl AsRawFd for Listener {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Listener::Tcp(listener) => listener.as_raw_fd(),
+            Listener::Unix(listener) => listener.as_raw_fd(),
+        }
+    }
+}
+
+impl From<TcpListener> for Listener {
+    fn from(listener: TcpListener) -> Self {
+        Listener::Tcp(listener)
+    }
+}
+
+impl Connection {
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Connection::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            Connection::Unix(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+}
+
+// 이전 프로세스가 비정상 종료하면서 남긴 소켓 파일을 지움
+// 소켓 파일이 아니거나 다른 프로세스가 리스닝 중이면 지우지 않고 에러
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{}은(는) 소켓 파일이 아닙니다", path.display()),
+        ));
+    }
+    match UnixStream::connect(path) {
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{}을(를) 다른 프로세스가 사용 중입니다", path.display()),
+        )),
+        Err(_) => fs::remove_file(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_unix_socket() {
+        let dir = std::env::temp_dir().join(format!("httpserver-uds-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server.sock");
+        let addr = format!("unix:{}", path.display());
+        assert_eq!(unix_path(&addr), Some(path.as_path()));
+        assert_eq!(unix_path("127.0.0.1:3000"), None);
+
+        // 설정한 권한으로 소켓 파일을 만듦
+        let listener = Listener::bind(&addr, 0o600).unwrap();
+        assert!(matches!(listener, Listener::Unix(_)));
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        // 리스닝 중인 소켓은 지우지 않음
+        let error = Listener::bind(&addr, 0o600).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+
+        // 리스너가 닫히고 남은 소켓 파일은 지우고 다시 바인딩
+        drop(listener);
+        assert!(path.exists());
+        let listener = Listener::bind(&addr, 0o660).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o660);
+        drop(listener);
+
+        // 소켓이 아닌 파일은 지우지 않음
+        fs::remove_file(&path).unwrap();
+        fs::write(&path, "data").unwrap();
+        assert!(Listener::bind(&addr, 0o660).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "data");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
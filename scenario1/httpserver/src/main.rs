@@ -1,12 +1,73 @@
-mod handler;
-mod router;
-mod server;
-use server::Server;
+use httpserver::access_log::AccessLog;
+use httpserver::config::ServerConfig;
+use httpserver::lifecycle::{self, ServerHandle};
+use httpserver::server::Server;
+use httpserver::state::AppState;
+use httpserver::tls;
+use std::process;
+use std::sync::Arc;
+use std::thread;
 
 fn main() {
-    // 서버 시작
-    let server = Server::new("localhost:3000");
+    // 설정 파일, 환경변수, 명령행 플래그를 합쳐서 설정을 로드
+    let config = match ServerConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
 
-    // 서버 실행
-    server.run();
+    // 설정과 업스트림 상태를 모든 리스너와 워커가 공유(SIGHUP을 받으면 핸들이 새 상태로 교체)
+    let handle = ServerHandle::new(AppState::new(config));
+    if let Err(e) = lifecycle::handle_signals(handle.clone()) {
+        eprintln!("시그널 핸들러를 등록할 수 없습니다: {}", e);
+        process::exit(1);
+    }
+    let state = handle.state();
+    let config = &state.config;
+
+    // HTTPS 리스너용 인증서를 미리 로드
+    // 인증서와 액세스 로그는 여기서 한 번만 만들므로 SIGHUP으로 [tls], [access_log]를 바꿔도 적용되지 않음
+    let tls = match config.tls.as_ref().map(tls::load_tls_config).transpose() {
+        Ok(tls) => tls,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    // 모든 리스너가 하나의 액세스 로그를 공유
+    let access_log = if config.access_log.enabled {
+        match AccessLog::new(&config.access_log) {
+            Ok(access_log) => Some(Arc::new(access_log)),
+            Err(e) => {
+                eprintln!("액세스 로그 {}을(를) 열 수 없습니다: {}", config.access_log.output, e);
+                process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let server = |addr| {
+        let server = Server::new(addr, handle.clone());
+        match &access_log {
+            Some(access_log) => server.with_access_log(Arc::clone(access_log)),
+            None => server,
+        }
+    };
+
+    // 바인딩할 주소마다 서버를 시작해서 실행(모든 리스너가 종료하고 커넥션을 정리하면 리턴)
+    thread::scope(|s| {
+        for addr in &config.server.bind {
+            let server = server(addr);
+            s.spawn(move || server.run());
+        }
+        if let (Some(section), Some(tls)) = (&config.tls, &tls) {
+            for addr in &section.bind {
+                let server = server(addr).with_tls(Arc::clone(tls));
+                s.spawn(move || server.run());
+            }
+        }
+    });
 }
\ No newline at end of file
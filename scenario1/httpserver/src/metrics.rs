@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// 응답 시간 히스토그램의 버킷 상한(초), 마지막 +Inf 버킷은 count와 같음
+const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+// 라우트에 일치하지 않는 요청(잘못된 요청 포함)의 route 레이블
+pub const NO_ROUTE: &str = "none";
+
+// /metrics에서 Prometheus 텍스트 형식으로 내보내는 서버 지표
+// route 레이블은 요청 경로가 아니라 라우트 접두사라서 시계열 개수가 라우트 수로 제한됨
+pub struct Metrics {
+    requests: Mutex<BTreeMap<(String, String), u64>>, // (라우트, 상태 코드) -> 요청 수
+    latency: Mutex<BTreeMap<String, Histogram>>,      // 라우트 -> 응답 시간
+    active_connections: AtomicUsize,
+    queued_connections: AtomicUsize, // 수락했지만 아직 워커가 처리하지 않은 커넥션
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()], // 상한 이하인 관측 수(누적하지 않은 값)
+    sum: f64,
+    count: u64,
+}
+
+// 커넥션을 처리하는 동안 active_connections에 포함(drop되면 빠짐)
+pub struct ConnectionGuard<'m> {
+    metrics: &'m Metrics,
+}
+
+impl Drop for ConnectionGuard<'_> {
+    fn drop(&mut self) {
+        self.metrics.active_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics {
+            requests: Mutex::new(BTreeMap::new()),
+            latency: Mutex::new(BTreeMap::new()),
+            active_connections: AtomicUsize::new(0),
+            queued_connections: AtomicUsize::new(0),
+        }
+    }
+
+    // 응답을 보낸 요청 하나를 기록
+    pub fn observe(&self, route: &str, status: &str, duration: Duration) {
+        *self
+            .requests
+            .lock()
+            .unwrap()
+            .entry((route.to_string(), status.to_string()))
+            .or_insert(0) += 1;
+
+        let seconds = duration.as_secs_f64();
+        let mut latency = self.latency.lock().unwrap();
+        let histogram = latency.entry(route.to_string()).or_default();
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|&le| seconds <= le) {
+            histogram.buckets[i] += 1;
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    // 워커 풀에 커넥션을 넘길 때와 워커가 꺼냈을 때 호출
+    pub fn connection_queued(&self) {
+        self.queued_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn connection_started(&self) -> ConnectionGuard<'_> {
+        self.queued_connections.fetch_sub(1, Ordering::Relaxed);
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard { metrics: self }
+    }
+
+    // Prometheus 텍스트 형식(version 0.0.4)
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP httpserver_requests_total Requests by route prefix and status code.\n");
+        out.push_str("# TYPE httpserver_requests_total counter\n");
+        for ((route, status), count) in self.requests.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "httpserver_requests_total{{route=\"{}\",status=\"{}\"}} {}",
+                escape(route),
+                escape(status),
+                count
+            );
+        }
+
+        out.push_str("# HELP httpserver_request_duration_seconds Time to read, handle and send a request.\n");
+        out.push_str("# TYPE httpserver_request_duration_seconds histogram\n");
+        for (route, histogram) in self.latency.lock().unwrap().iter() {
+            let route = escape(route);
+            let mut cumulative = 0;
+            for (le, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "httpserver_request_duration_seconds_bucket{{route=\"{}\",le=\"{}\"}} {}",
+                    route, le, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "httpserver_request_duration_seconds_bucket{{route=\"{}\",le=\"+Inf\"}} {}",
+                route, histogram.count
+            );
+            let _ = writeln!(out, "httpserver_request_duration_seconds_sum{{route=\"{}\"}} {}", route, histogram.sum);
+            let _ = writeln!(out, "httpserver_request_duration_seconds_count{{route=\"{}\"}} {}", route, histogram.count);
+        }
+
+        out.push_str("# HELP httpserver_active_connections Connections currently being served.\n");
+        out.push_str("# TYPE httpserver_active_connections gauge\n");
+        let _ = writeln!(out, "httpserver_active_connections {}", self.active_connections.load(Ordering::Relaxed));
+
+        out.push_str("# HELP httpserver_pool_queue_depth Accepted connections waiting for a worker thread.\n");
+        out.push_str("# TYPE httpserver_pool_queue_depth gauge\n");
+        let _ = writeln!(out, "httpserver_pool_queue_depth {}", self.queued_connections.load(Ordering::Relaxed));
+        out
+    }
+}
+
+// 레이블 값의 역슬래시, 큰따옴표, 개행을 이스케이프
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_text() {
+        let metrics = Metrics::new();
+        metrics.observe("/api", "200", Duration::from_millis(3));
+        metrics.observe("/api", "200", Duration::from_millis(40));
+        metrics.observe("/api", "404", Duration::from_secs(20));
+        metrics.observe("/\"x\"", "200", Duration::ZERO);
+
+        metrics.connection_queued();
+        metrics.connection_queued();
+        let guard = metrics.connection_started();
+
+        let text = metrics.render();
+        assert!(text.contains("httpserver_requests_total{route=\"/api\",status=\"200\"} 2\n"));
+        assert!(text.contains("httpserver_requests_total{route=\"/api\",status=\"404\"} 1\n"));
+        assert!(text.contains("httpserver_requests_total{route=\"/\\\"x\\\"\",status=\"200\"} 1\n"));
+        assert!(text.contains("httpserver_request_duration_seconds_bucket{route=\"/api\",le=\"0.005\"} 1\n"));
+        assert!(text.contains("httpserver_request_duration_seconds_bucket{route=\"/api\",le=\"0.05\"} 2\n"));
+        assert!(text.contains("httpserver_request_duration_seconds_bucket{route=\"/api\",le=\"10\"} 2\n"));
+        assert!(text.contains("httpserver_request_duration_seconds_bucket{route=\"/api\",le=\"+Inf\"} 3\n"));
+        assert!(text.contains("httpserver_request_duration_seconds_count{route=\"/api\"} 3\n"));
+        assert!(text.contains("httpserver_active_connections 1\n"));
+        assert!(text.contains("httpserver_pool_queue_depth 1\n"));
+
+        drop(guard);
+        assert!(metrics.render().contains("httpserver_active_connections 0\n"));
+    }
+}
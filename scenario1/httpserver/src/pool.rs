@@ -0,0 +1,149 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 워커 스레드가 실행할 작업(커넥션 하나를 처리하는 클로저)
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+// 고정된 개수의 워커 스레드로 커넥션을 처리하는 스레드 풀
+pub struct ThreadPool {
+    workers: Vec<Worker>,
+    sender: Option<mpsc::Sender<Job>>,
+}
+
+impl ThreadPool {
+    pub fn new(size: usize) -> ThreadPool {
+        assert!(size > 0);
+
+        // 모든 워커가 하나의 수신자를 공유하므로 Arc<Mutex<_>>로 감쌈
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..size)
+            .map(|id| Worker::new(id, Arc::clone(&receiver)))
+            .collect();
+
+        ThreadPool {
+            workers,
+            sender: Some(sender),
+        }
+    }
+
+    pub fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(Box::new(f));
+        }
+    }
+}
+
+impl ThreadPool {
+    // 대기 중인 작업까지 처리하고 워커가 끝나기를 최대 timeout 동안 기다림
+    // 그때까지 끝나지 않은 워커(SSE, WebSocket처럼 오래 유지되는 커넥션)는 분리하고 그 개수를 리턴
+    pub fn shutdown(mut self, timeout: Duration) -> usize {
+        drop(self.sender.take());
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline && self.workers.iter().any(|w| w.is_running()) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let mut running = 0;
+        for worker in &mut self.workers {
+            if worker.is_running() {
+                worker.thread.take();
+                running += 1;
+            }
+        }
+        running
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // 송신자를 닫으면 워커들의 recv()가 에러를 리턴하면서 루프가 끝남
+        drop(self.sender.take());
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+struct Worker {
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Worker {
+        let thread = thread::Builder::new()
+            .name(format!("worker-{}", id))
+            .spawn(move || loop {
+                // lock은 recv()가 끝나는 즉시 반환되므로 작업은 잠금 없이 실행됨
+                let message = receiver.lock().unwrap().recv();
+                match message {
+                    // 요청 하나를 처리하다 패닉이 나도 워커는 계속 다음 작업을 받음
+                    // 패닉 메시지는 기본 훅이 출력하고, 해당 커넥션은 작업과 함께 닫힘
+                    Ok(job) => {
+                        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                    }
+                    Err(_) => break,
+                }
+            })
+            .unwrap();
+
+        Worker {
+            thread: Some(thread),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_shutdown_drains_queue_and_times_out() {
+        // 대기 중인 작업도 모두 처리한 뒤 끝남
+        let pool = ThreadPool::new(1);
+        let done = Arc::new(AtomicUsize::new(0));
+        for _ in 0..3 {
+            let done = Arc::clone(&done);
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(20));
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        assert_eq!(pool.shutdown(Duration::from_secs(5)), 0);
+        assert_eq!(done.load(Ordering::SeqCst), 3);
+
+        // 제한 시간 안에 끝나지 않은 워커는 기다리지 않음
+        let pool = ThreadPool::new(2);
+        pool.execute(|| thread::sleep(Duration::from_secs(2)));
+        let started = Instant::now();
+        assert_eq!(pool.shutdown(Duration::from_millis(100)), 1);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_panicking_job_keeps_worker() {
+        let pool = ThreadPool::new(1);
+        let done = Arc::new(AtomicUsize::new(0));
+        for _ in 0..3 {
+            pool.execute(|| panic!("작업 실패"));
+            let done = Arc::clone(&done);
+            pool.execute(move || {
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        assert_eq!(pool.shutdown(Duration::from_secs(5)), 0);
+        assert_eq!(done.load(Ordering::SeqCst), 3);
+    }
+}
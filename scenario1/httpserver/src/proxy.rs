@@ -0,0 +1,626 @@
+use super::config::{RouteSection, UpstreamSection};
+use super::router::Reply;
+use super::state::{RequestContext, StreamedBody};
+use http::httprequest::{HttpRequest, Method, Resource, Version};
+use http::httpresponse::HttpResponse;
+use std::collections::HashMap;
+use std::io::{self, Cursor, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// 프록시가 업스트림으로 전달하지 않는 홉 간(hop-by-hop) 헤더
+const HOP_BY_HOP: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "upgrade",
+];
+
+// 업스트림 응답 헤더 부분의 최대 크기
+const MAX_RESPONSE_HEAD_BYTES: usize = 64 * 1024;
+
+// 업스트림 서버 묶음과 라운드 로빈 위치, 서버별 헬스 상태
+pub struct UpstreamPool {
+    servers: Vec<UpstreamServer>,
+    next: AtomicUsize,
+    max_fails: u32,
+    fail_timeout: Duration,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+}
+
+struct UpstreamServer {
+    addr: String,
+    health: Mutex<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    fails: u32,
+    down_until: Option<Instant>,
+}
+
+impl UpstreamPool {
+    pub fn new(section: &UpstreamSection) -> UpstreamPool {
+        UpstreamPool {
+            servers: section
+                .servers
+                .iter()
+                .map(|addr| UpstreamServer {
+                    addr: addr.clone(),
+                    health: Mutex::new(Health::default()),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+            max_fails: section.max_fails,
+            fail_timeout: Duration::from_secs(section.fail_timeout_secs),
+            connect_timeout: Duration::from_secs(section.connect_timeout_secs),
+            read_timeout: Duration::from_secs(section.read_timeout_secs),
+        }
+    }
+
+    // 라운드 로빈으로 다음 서버를 고름, 다운 상태인 서버는 건너뜀
+    fn pick(&self) -> Option<&UpstreamServer> {
+        let count = self.servers.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        (0..count)
+            .map(|i| &self.servers[(start + i) % count])
+            .find(|server| server.is_available(now))
+    }
+
+    fn report_success(&self, server: &UpstreamServer) {
+        let mut health = server.health.lock().unwrap();
+        health.fails = 0;
+        health.down_until = None;
+    }
+
+    // 연속 실패가 max_fails에 도달하면 fail_timeout 동안 후보에서 제외
+    fn report_failure(&self, server: &UpstreamServer) {
+        let mut health = server.health.lock().unwrap();
+        health.fails += 1;
+        if health.fails >= self.max_fails {
+            health.fails = 0;
+            health.down_until = Some(Instant::now() + self.fail_timeout);
+        }
+    }
+
+    // 서버 하나와 요청/응답을 주고받음, 요청 바디는 body에서 읽는 대로 보내고 응답 바디는 업스트림 소켓에서 그대로 스트리밍
+    fn exchange(
+        &self,
+        server: &UpstreamServer,
+        head: &str,
+        body: &mut dyn Read,
+    ) -> Result<Reply<'static>, ExchangeError> {
+        let connect_error = |error| ExchangeError {
+            error,
+            request_sent: false,
+        };
+        let addr = server
+            .addr
+            .to_socket_addrs()
+            .map_err(connect_error)?
+            .next()
+            .ok_or_else(|| connect_error(io::ErrorKind::AddrNotAvailable.into()))?;
+        let mut stream = TcpStream::connect_timeout(&addr, self.connect_timeout).map_err(connect_error)?;
+        let _ = stream.set_read_timeout(Some(self.read_timeout));
+        let _ = stream.set_write_timeout(Some(self.read_timeout));
+
+        let sent_error = |error| ExchangeError {
+            error,
+            request_sent: true,
+        };
+        stream.write_all(head.as_bytes()).map_err(sent_error)?;
+        io::copy(body, &mut stream).map_err(sent_error)?;
+        let head_request = head.starts_with("HEAD ");
+
+        // 응답 헤더 부분을 빈 행까지 읽음
+        let mut buffer = Vec::new();
+        let mut chunk = [0; 4096];
+        let head_end = loop {
+            if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if buffer.len() > MAX_RESPONSE_HEAD_BYTES {
+                return Err(sent_error(io::ErrorKind::InvalidData.into()));
+            }
+            let n = stream.read(&mut chunk).map_err(sent_error)?;
+            if n == 0 {
+                return Err(sent_error(io::ErrorKind::UnexpectedEof.into()));
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        };
+        let rest = buffer.split_off(head_end);
+        let head = String::from_utf8(buffer)
+            .map_err(|_| sent_error(io::ErrorKind::InvalidData.into()))?;
+        let (response, content_length) =
+            upstream_response(&head).ok_or_else(|| sent_error(io::ErrorKind::InvalidData.into()))?;
+
+        // HEAD에 대한 응답과 204/304 응답은 Content-Length가 있어도 바디가 없음(RFC 9112 6.3)
+        // 헤더의 Content-Length는 그대로 두고 바디만 비움
+        // 업스트림에 Connection: close를 보냈으므로 그 밖의 길이를 모르는 바디는 소켓이 닫힐 때 끝남
+        let bodyless = head_request || matches!(response.status_code(), "204" | "304");
+        let body = Cursor::new(rest).chain(stream);
+        let body_stream: Box<dyn Read> = match content_length {
+            _ if bodyless => Box::new(io::empty()),
+            Some(length) => Box::new(body.take(length)),
+            None => Box::new(body),
+        };
+        Ok(Reply {
+            response,
+            body_stream: Some(body_stream),
+            upgrade: None,
+        })
+    }
+}
+
+impl UpstreamServer {
+    fn is_available(&self, now: Instant) -> bool {
+        match self.health.lock().unwrap().down_until {
+            Some(until) => until <= now,
+            None => true,
+        }
+    }
+}
+
+struct ExchangeError {
+    error: io::Error,
+    // 요청을 이미 보냈다면 멱등이 아닌 요청은 다른 서버로 재시도하지 않음
+    request_sent: bool,
+}
+
+pub struct ProxyHandler;
+
+impl ProxyHandler {
+    // 접두사가 일치하는 요청을 업스트림으로 전달
+    // Content-Length 바디는 서버가 읽어 두지 않고 넘긴 리더(body, max_body_bytes 이하)에서 읽는 대로 보냄
+    // 청크 인코딩 바디는 서버가 디코딩해서 읽어 둔 원본 바이트(ctx.body)를 보냄
+    // (msg_body는 UTF-8로 바꾼 값이라 바이너리 업로드가 손상됨)
+    pub fn forward<'a>(
+        req: &'a HttpRequest,
+        route: &RouteSection,
+        pool: &UpstreamPool,
+        ctx: &RequestContext,
+        mut body: Option<StreamedBody>,
+    ) -> Reply<'a> {
+        let idempotent = !matches!(req.method, Method::Post | Method::Patch);
+        let mut attempted = false;
+        let mut timed_out = false;
+
+        for _ in 0..pool.servers.len() {
+            let Some(server) = pool.pick() else {
+                break;
+            };
+            attempted = true;
+            let body_length = body.as_ref().map_or(ctx.body.len(), |body| body.length);
+            let head = upstream_request_head(req, route, ctx, &server.addr, body_length);
+            let mut buffered = ctx.body;
+            let result = match body.as_mut() {
+                Some(body) => pool.exchange(server, &head, &mut *body.reader),
+                None => pool.exchange(server, &head, &mut buffered),
+            };
+            match result {
+                Ok(reply) => {
+                    pool.report_success(server);
+                    return reply;
+                }
+                Err(e) => {
+                    pool.report_failure(server);
+                    timed_out = matches!(
+                        e.error.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    );
+                    // 스트리밍한 바디는 이미 커넥션에서 읽어 버렸으므로 다시 보낼 수 없음
+                    if e.request_sent && (!idempotent || body.is_some()) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        // 사용할 수 있는 서버가 없으면 503, 응답을 기다리다 시간이 지나면 504, 그 밖의 실패는 502
+        let status_code = if !attempted {
+            "503"
+        } else if timed_out {
+            "504"
+        } else {
+            "502"
+        };
+        HttpResponse::new(status_code, None, Some(String::new())).into()
+    }
+}
+
+// 업스트림으로 보낼 요청 행과 헤더
+// Host를 업스트림 주소로 바꾸고 X-Forwarded-For/Proto/Host를 추가
+fn upstream_request_head(
+    req: &HttpRequest,
+    route: &RouteSection,
+    ctx: &RequestContext,
+    upstream_addr: &str,
+    body_length: usize,
+) -> String {
+    let Resource::Path(path) = &req.resource;
+    let target = if route.strip_prefix {
+        let stripped = path
+            .strip_prefix(route.prefix.trim_end_matches('/'))
+            .unwrap_or(path);
+        if stripped.starts_with('/') {
+            stripped.to_string()
+        } else {
+            format!("/{}", stripped)
+        }
+    } else {
+        path.clone()
+    };
+
+    // Connection 헤더에 나열된 헤더도 홉 간 헤더로 취급
+    let headers: HashMap<String, &str> = req
+        .headers
+        .iter()
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim()))
+        .collect();
+    let connection_tokens: Vec<String> = headers
+        .get("connection")
+        .map(|v| v.split(',').map(|t| t.trim().to_ascii_lowercase()).collect())
+        .unwrap_or_default();
+
+    // HTTP/1.0 클라이언트의 요청은 업스트림에도 HTTP/1.0으로 보내서 청크 인코딩 응답을 받지 않도록 함
+    let version = if req.version == Version::V1_0 { "HTTP/1.0" } else { "HTTP/1.1" };
+    let mut head = format!(
+        "{} {} {}\r\nHost: {}\r\n",
+        req.method.as_str(),
+        target,
+        version,
+        upstream_addr
+    );
+    for (key, value) in &req.headers {
+        let name = key.trim().to_ascii_lowercase();
+        let skip = HOP_BY_HOP.contains(&name.as_str())
+            || connection_tokens.contains(&name)
+            || matches!(
+                name.as_str(),
+                "host" | "content-length" | "transfer-encoding" | "x-forwarded-for" | "x-forwarded-proto" | "x-forwarded-host"
+            );
+        if !skip {
+            head.push_str(&format!("{}: {}\r\n", key.trim(), value.trim()));
+        }
+    }
+
+    // 기존 X-Forwarded-For 뒤에 클라이언트 주소를 덧붙임
+    let client = ctx.peer.map(|p| p.ip().to_string());
+    let forwarded_for = match (headers.get("x-forwarded-for"), client) {
+        (Some(prev), Some(client)) => Some(format!("{}, {}", prev, client)),
+        (Some(prev), None) => Some(prev.to_string()),
+        (None, client) => client,
+    };
+    if let Some(forwarded_for) = forwarded_for {
+        head.push_str(&format!("X-Forwarded-For: {}\r\n", forwarded_for));
+    }
+    let proto = if ctx.secure { "https" } else { "http" };
+    head.push_str(&format!("X-Forwarded-Proto: {}\r\n", proto));
+    if let Some(host) = headers.get("host") {
+        head.push_str(&format!("X-Forwarded-Host: {}\r\n", host));
+    }
+
+    if body_length > 0 || matches!(req.method, Method::Post | Method::Put | Method::Patch) {
+        head.push_str(&format!("Content-Length: {}\r\n", body_length));
+    }
+    head.push_str("Connection: close\r\n\r\n");
+    head
+}
+
+// 업스트림 응답의 상태 행과 헤더를 HttpResponse로 변환하고 홉 간 헤더를 뺌
+// 바디 길이를 알 수 있으면 Content-Length도 함께 리턴
+fn upstream_response(head: &str) -> Option<(HttpResponse<'static>, Option<u64>)> {
+    let mut response = HttpResponse::parse_head(head).ok()?;
+    for name in HOP_BY_HOP {
+        response.remove_header(name);
+    }
+
+    // 청크 인코딩이면 길이 제한 없이 소켓이 닫힐 때까지 그대로 전달
+    let content_length = if response.is_chunked() {
+        None
+    } else {
+        response.content_length().ok()?.map(|length| length as u64)
+    };
+    Some((response, content_length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{HandlerKind, ServerConfig};
+    use crate::session::Session;
+    use crate::state::AppState;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    // 요청 하나를 받아서 받은 헤더 부분을 바디로 돌려주는 로컬 업스트림
+    fn spawn_upstream(name: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            let mut chunk = [0; 1024];
+            while !received.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut chunk).unwrap();
+                received.extend_from_slice(&chunk[..n]);
+            }
+            let received = String::from_utf8(received).unwrap();
+            let body = format!("{}\n{}", name, received);
+            write!(
+                stream,
+                "HTTP/1.1 201 Created\r\nContent-Length: {}\r\nX-Upstream: {}\r\nSet-Cookie: a=1; Path=/\r\nSet-Cookie: b=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                name,
+                body
+            )
+            .unwrap();
+            received
+        });
+        (addr, handle)
+    }
+
+    fn proxy_route() -> RouteSection {
+        RouteSection {
+            prefix: "/courses".to_string(),
+            handler: HandlerKind::Proxy,
+            upstream: Some("tutors".to_string()),
+            strip_prefix: true,
+            endpoint: None,
+            auth: None,
+            rate_limit: None,
+        }
+    }
+
+    fn pool(servers: Vec<String>) -> UpstreamPool {
+        UpstreamPool::new(&UpstreamSection {
+            servers,
+            max_fails: 1,
+            ..UpstreamSection::default()
+        })
+    }
+
+    fn read_body(reply: Reply) -> String {
+        let mut body = String::new();
+        reply.body_stream.unwrap().read_to_string(&mut body).unwrap();
+        body
+    }
+
+    #[test]
+    fn test_forward_rewrites_host_and_adds_forwarded_headers() {
+        let (addr, upstream) = spawn_upstream("a");
+        let state = AppState::new(ServerConfig::default());
+        let ctx = RequestContext {
+            state: &state,
+            peer: Some("10.0.0.7:50000".parse().unwrap()),
+            secure: true,
+            body: &[],
+            session: &Session::default(),
+        };
+        let req: HttpRequest = String::from(
+            "GET /courses/1?page=2 HTTP/1.1\r\nHost: tutors.localhost:3000\r\nX-Forwarded-For: 192.0.2.1\r\nConnection: keep-alive\r\nAccept: */*\r\n\r\n",
+        )
+        .into();
+
+        let reply = ProxyHandler::forward(&req, &proxy_route(), &pool(vec![addr.clone()]), &ctx, None);
+        assert_eq!(reply.response.status_code(), "201");
+        assert_eq!(reply.response.status_text(), "Created");
+        assert_eq!(reply.response.header("X-Upstream"), Some("a"));
+        assert_eq!(reply.response.header("Connection"), None);
+        assert_eq!(
+            reply.response.set_cookies(),
+            ["a=1; Path=/", "b=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT"]
+        );
+        assert!(read_body(reply).starts_with("a\n"));
+
+        let received = upstream.join().unwrap();
+        assert!(received.starts_with("GET /1?page=2 HTTP/1.1\r\n"));
+        assert!(received.contains(&format!("Host: {}\r\n", addr)));
+        assert!(received.contains("X-Forwarded-For: 192.0.2.1, 10.0.0.7\r\n"));
+        assert!(received.contains("X-Forwarded-Proto: https\r\n"));
+        assert!(received.contains("X-Forwarded-Host: tutors.localhost:3000\r\n"));
+        assert!(received.contains("Accept: */*\r\n"));
+        assert!(received.contains("Connection: close\r\n"));
+        assert!(!received.contains("keep-alive"));
+    }
+
+    #[test]
+    fn test_forward_binary_body() {
+        // 헤더와 Content-Length만큼의 바디를 받아서 받은 바디를 돌려주는 업스트림
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let upstream = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            let mut chunk = [0; 1024];
+            let head_end = loop {
+                if let Some(pos) = received.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+                let n = stream.read(&mut chunk).unwrap();
+                received.extend_from_slice(&chunk[..n]);
+            };
+            let head = String::from_utf8(received[..head_end].to_vec()).unwrap();
+            let length: usize = head
+                .lines()
+                .find_map(|line| line.strip_prefix("Content-Length: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            while received.len() < head_end + length {
+                let n = stream.read(&mut chunk).unwrap();
+                received.extend_from_slice(&chunk[..n]);
+            }
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").unwrap();
+            received.split_off(head_end)
+        });
+
+        let body = [0x89, b'P', b'N', b'G', 0xff, 0xfe, 0x00, b'\r', b'\n'];
+        let state = AppState::new(ServerConfig::default());
+        let ctx = RequestContext {
+            state: &state,
+            peer: None,
+            secure: false,
+            body: &body,
+            session: &Session::default(),
+        };
+        let head = "POST /courses/upload HTTP/1.1\r\nHost: localhost\r\nContent-Type: image/png\r\n\r\n";
+        let mut bytes = head.as_bytes().to_vec();
+        bytes.extend_from_slice(&body);
+        let req: HttpRequest = String::from_utf8_lossy(&bytes).to_string().into();
+
+        // UTF-8로 바꾼 msg_body가 아닌 원본 바이트와 그 길이를 보냄
+        let reply = ProxyHandler::forward(&req, &proxy_route(), &pool(vec![addr]), &ctx, None);
+        assert_eq!(reply.response.status_code(), "204");
+        assert_eq!(upstream.join().unwrap(), body);
+    }
+
+    #[test]
+    fn test_forward_streams_body_from_reader() {
+        // 받은 바디의 바이트 수를 돌려주는 업스트림
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let upstream = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            let mut chunk = [0; 4096];
+            let head_end = loop {
+                if let Some(pos) = received.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+                let n = stream.read(&mut chunk).unwrap();
+                received.extend_from_slice(&chunk[..n]);
+            };
+            while received.len() < head_end + 100_000 {
+                let n = stream.read(&mut chunk).unwrap();
+                received.extend_from_slice(&chunk[..n]);
+            }
+            let body = (received.len() - head_end).to_string();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+            String::from_utf8_lossy(&received[..head_end]).into_owned()
+        });
+
+        let state = AppState::new(ServerConfig::default());
+        let ctx = RequestContext {
+            state: &state,
+            peer: None,
+            secure: false,
+            body: &[],
+            session: &Session::default(),
+        };
+        let req: HttpRequest =
+            String::from("PUT /courses/upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 100000\r\n\r\n").into();
+
+        // 서버가 읽어 두지 않은 바디를 리더에서 읽는 대로 업스트림에 보냄
+        let mut reader = io::repeat(b'x').take(100_000);
+        let body = StreamedBody {
+            reader: &mut reader,
+            length: 100_000,
+        };
+        let reply = ProxyHandler::forward(&req, &proxy_route(), &pool(vec![addr]), &ctx, Some(body));
+        assert_eq!(read_body(reply), "100000");
+        assert_eq!(reader.limit(), 0);
+        assert!(upstream.join().unwrap().contains("Content-Length: 100000\r\n"));
+    }
+
+    #[test]
+    fn test_head_response_has_no_body() {
+        // HEAD에 Content-Length만 보내고 바디 없이 커넥션을 열어 두는 업스트림
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (done, wait) = mpsc::channel::<()>();
+        let upstream = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            let mut chunk = [0; 1024];
+            while !received.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut chunk).unwrap();
+                received.extend_from_slice(&chunk[..n]);
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\n").unwrap();
+            let _ = wait.recv();
+            String::from_utf8(received).unwrap()
+        });
+
+        let state = AppState::new(ServerConfig::default());
+        let ctx = RequestContext {
+            state: &state,
+            peer: None,
+            secure: false,
+            body: &[],
+            session: &Session::default(),
+        };
+        let req: HttpRequest = String::from("HEAD /courses/1 HTTP/1.1\r\nHost: localhost\r\n\r\n").into();
+
+        // 바디를 기다리지 않고 바로 끝나며 Content-Length는 업스트림 값을 그대로 전달
+        let started = Instant::now();
+        let reply = ProxyHandler::forward(&req, &proxy_route(), &pool(vec![addr]), &ctx, None);
+        assert_eq!(reply.response.status_code(), "200");
+        assert_eq!(reply.response.header("Content-Length"), Some("11"));
+        assert_eq!(read_body(reply), "");
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        done.send(()).unwrap();
+        assert!(upstream.join().unwrap().starts_with("HEAD /1 HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn test_round_robin_skips_failed_upstream() {
+        // 바인딩한 뒤 바로 닫아서 연결이 거부되는 주소를 만듦
+        let dead = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let (first, first_upstream) = spawn_upstream("first");
+        let (second, second_upstream) = spawn_upstream("second");
+        let pool = pool(vec![dead, first, second]);
+
+        let state = AppState::new(ServerConfig::default());
+        let ctx = RequestContext {
+            state: &state,
+            peer: None,
+            secure: false,
+            body: &[],
+            session: &Session::default(),
+        };
+        let req: HttpRequest = String::from("GET /courses HTTP/1.1\r\nHost: localhost\r\n\r\n").into();
+
+        // 첫 번째 요청은 죽은 서버에서 실패한 뒤 다음 서버로 재시도
+        let first_body = read_body(ProxyHandler::forward(&req, &proxy_route(), &pool, &ctx, None));
+        let second_body = read_body(ProxyHandler::forward(&req, &proxy_route(), &pool, &ctx, None));
+        assert!(first_body.starts_with("first\n"));
+        assert!(second_body.starts_with("second\n"));
+        assert!(first_upstream.join().unwrap().starts_with("GET / HTTP/1.1\r\n"));
+        second_upstream.join().unwrap();
+
+        // 죽은 서버는 다운 상태가 되어 후보에서 빠짐
+        assert!(!pool.servers[0].is_available(Instant::now()));
+
+        // 모든 서버가 실패하면 502, 후보가 하나도 없으면 503
+        let reply = ProxyHandler::forward(&req, &proxy_route(), &pool, &ctx, None);
+        assert_eq!(reply.response.status_code(), "502");
+        let reply = ProxyHandler::forward(&req, &proxy_route(), &pool, &ctx, None);
+        assert_eq!(reply.response.status_code(), "503");
+    }
+
+    #[test]
+    fn test_upstream_response_merges_duplicates() {
+        let (response, length) = upstream_response(
+            "HTTP/1.1 404 Not Here\r\nVary: Accept\r\nVary: Origin\r\nKeep-Alive: timeout=5\r\nContent-Length: 7\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(response.status_code(), "404");
+        assert_eq!(response.status_text(), "Not Here");
+        assert_eq!(response.header("Vary"), Some("Accept, Origin"));
+        assert_eq!(response.header("Keep-Alive"), None);
+        assert_eq!(length, Some(7));
+
+        assert!(upstream_response("garbage\r\n\r\n").is_none());
+    }
+}
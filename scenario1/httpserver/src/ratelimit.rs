@@ -0,0 +1,224 @@
+use super::config::{RateLimitKey, RateLimitSection};
+use http::httprequest::HttpRequest;
+use http::httpresponse::HttpResponse;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// 다시 가득 찬 버킷을 한꺼번에 지우는 주기
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+// 한 그룹이 보관하는 버킷의 최대 개수, 넘으면 가장 오래 쓰지 않은 버킷을 지움
+const MAX_BUCKETS: usize = 10_000;
+
+// 라우트 그룹 하나([rate_limits.<이름>])의 클라이언트별 토큰 버킷
+// 요청마다 토큰 하나를 쓰고, 토큰은 초당 requests / window_secs개씩 burst개까지 다시 참
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    key: RateLimitKey,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    last_sweep: Mutex<Instant>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+// 요청 하나에 대한 판정, 응답의 RateLimit-* 헤더에 그대로 씀
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    limit: u64,
+    remaining: u64,
+    reset_secs: u64,       // 버킷이 다시 가득 찰 때까지 남은 시간
+    retry_after_secs: u64, // 다음 토큰이 생길 때까지 남은 시간(거부된 경우)
+}
+
+impl RateLimiter {
+    pub fn new(section: &RateLimitSection) -> RateLimiter {
+        let capacity = if section.burst == 0 { section.requests } else { section.burst };
+        RateLimiter {
+            capacity: capacity as f64,
+            refill_per_sec: section.requests as f64 / section.window_secs as f64,
+            key: section.key,
+            buckets: Mutex::new(HashMap::new()),
+            last_sweep: Mutex::new(Instant::now()),
+        }
+    }
+
+    // 요청을 보낸 클라이언트의 버킷에서 토큰 하나를 꺼냄
+    // authenticated는 라우트의 인증을 통과한 요청인지(key = "token"일 때만 의미 있음)
+    pub fn acquire(&self, req: &HttpRequest, peer: Option<SocketAddr>, authenticated: bool) -> Decision {
+        self.acquire_at(&self.client_key(req, peer, authenticated), Instant::now())
+    }
+
+    pub fn keys_by_token(&self) -> bool {
+        self.key == RateLimitKey::Token
+    }
+
+    // key = "token"이면 인증을 통과한 Authorization 헤더 값, 아니면 피어 IP
+    // 인증하지 않은 값은 클라이언트가 요청마다 바꿔서 새 버킷을 받을 수 있으므로 쓰지 않음
+    fn client_key(&self, req: &HttpRequest, peer: Option<SocketAddr>, authenticated: bool) -> String {
+        if self.key == RateLimitKey::Token && authenticated {
+            if let Some(authorization) = req.header("Authorization") {
+                return format!("token:{}", authorization);
+            }
+        }
+        match peer {
+            Some(peer) => format!("ip:{}", peer.ip()),
+            None => "ip:unknown".to_string(),
+        }
+    }
+
+    fn acquire_at(&self, key: &str, now: Instant) -> Decision {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(key) {
+            // 가득 찬 버킷을 먼저 지우고, 그래도 자리가 없으면 가장 오래 쓰지 않은 버킷을 지움
+            buckets.retain(|_, bucket| self.refilled(bucket, now) < self.capacity);
+            if buckets.len() >= MAX_BUCKETS {
+                let oldest = buckets.iter().min_by_key(|(_, bucket)| bucket.updated).map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    buckets.remove(&oldest);
+                }
+            }
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let decision = Decision {
+            allowed,
+            limit: self.capacity as u64,
+            remaining: bucket.tokens as u64,
+            reset_secs: ((self.capacity - bucket.tokens) / self.refill_per_sec).ceil() as u64,
+            retry_after_secs: ((1.0 - bucket.tokens) / self.refill_per_sec).ceil().max(1.0) as u64,
+        };
+
+        // 가득 찬 버킷은 처음 보는 클라이언트의 버킷과 같으므로 지워서 메모리를 제한
+        let mut last_sweep = self.last_sweep.lock().unwrap();
+        if now.duration_since(*last_sweep) >= SWEEP_INTERVAL {
+            buckets.retain(|_, bucket| self.refilled(bucket, now) < self.capacity);
+            *last_sweep = now;
+        }
+        decision
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity)
+    }
+}
+
+impl Decision {
+    // 거부된 요청에 보낼 429 응답
+    pub fn rejection<'a>(&self) -> HttpResponse<'a> {
+        let mut resp = HttpResponse::new("429", None, Some(String::new()));
+        resp.add_header("Retry-After", self.retry_after_secs.to_string());
+        self.add_headers(&mut resp);
+        resp
+    }
+
+    pub fn add_headers(&self, resp: &mut HttpResponse) {
+        resp.add_header("RateLimit-Limit", self.limit.to_string());
+        resp.add_header("RateLimit-Remaining", self.remaining.to_string());
+        resp.add_header("RateLimit-Reset", self.reset_secs.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests: u32, window_secs: u64, burst: u32, key: RateLimitKey) -> RateLimiter {
+        RateLimiter::new(&RateLimitSection {
+            requests,
+            window_secs,
+            burst,
+            key,
+        })
+    }
+
+    #[test]
+    fn test_token_bucket_refills() {
+        // 초당 1개씩 다시 차고 한 번에 3개까지
+        let limiter = limiter(60, 60, 3, RateLimitKey::Peer);
+        let start = Instant::now();
+        for remaining in [2, 1, 0] {
+            let decision = limiter.acquire_at("ip:1", start);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
+        }
+        let denied = limiter.acquire_at("ip:1", start);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after_secs, 1);
+        assert_eq!(denied.reset_secs, 3);
+
+        let resp = denied.rejection();
+        assert_eq!(resp.status_code(), "429");
+        assert_eq!(resp.header("Retry-After"), Some("1"));
+        assert_eq!(resp.header("RateLimit-Limit"), Some("3"));
+        assert_eq!(resp.header("RateLimit-Remaining"), Some("0"));
+
+        // 다른 클라이언트는 별도의 버킷
+        assert!(limiter.acquire_at("ip:2", start).allowed);
+        // 1초 뒤에는 토큰 하나가 다시 생김
+        let later = start + Duration::from_secs(1);
+        assert!(limiter.acquire_at("ip:1", later).allowed);
+        assert!(!limiter.acquire_at("ip:1", later).allowed);
+    }
+
+    #[test]
+    fn test_idle_buckets_are_evicted() {
+        let limiter = limiter(10, 1, 0, RateLimitKey::Peer);
+        let start = Instant::now();
+        limiter.acquire_at("ip:1", start);
+        limiter.acquire_at("ip:2", start);
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 2);
+
+        // 정리 주기가 지나면 다시 가득 찬 버킷은 지워지고 방금 쓴 버킷만 남음
+        limiter.acquire_at("ip:3", start + SWEEP_INTERVAL);
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert!(buckets.contains_key("ip:3"));
+    }
+
+    #[test]
+    fn test_bucket_count_is_capped() {
+        // 정리 주기 안에 처음 보는 키가 계속 와도 버킷 수는 한도를 넘지 않음
+        let limiter = limiter(10, 3600, 0, RateLimitKey::Peer);
+        let start = Instant::now();
+        for i in 0..MAX_BUCKETS + 10 {
+            limiter.acquire_at(&format!("ip:{}", i), start + Duration::from_millis(i as u64));
+        }
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), MAX_BUCKETS);
+        // 가장 오래 쓰지 않은 버킷부터 지워짐
+        assert!(!buckets.contains_key("ip:0"));
+        assert!(buckets.contains_key(&format!("ip:{}", MAX_BUCKETS + 9)));
+    }
+
+    #[test]
+    fn test_client_key() {
+        let peer: Option<SocketAddr> = Some("10.0.0.7:51000".parse().unwrap());
+        let req: HttpRequest = "GET /api HTTP/1.1\r\nAuthorization: Bearer abc\r\n\r\n".to_string().into();
+        let anonymous: HttpRequest = "GET /api HTTP/1.1\r\n\r\n".to_string().into();
+
+        let by_peer = limiter(1, 1, 0, RateLimitKey::Peer);
+        assert_eq!(by_peer.client_key(&req, peer, true), "ip:10.0.0.7");
+        let by_token = limiter(1, 1, 0, RateLimitKey::Token);
+        assert_eq!(by_token.client_key(&req, peer, true), "token:Bearer abc");
+        assert_eq!(by_token.client_key(&anonymous, peer, false), "ip:10.0.0.7");
+        // 인증을 통과하지 않은 Authorization 값은 쓰지 않음
+        assert_eq!(by_token.client_key(&req, peer, false), "ip:10.0.0.7");
+    }
+}
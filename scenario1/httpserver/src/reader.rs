@@ -0,0 +1,565 @@
+use super::config::ServerSection;
+use http::framing::{self, BodyLength, FramingError};
+use rustls::{ServerConnection, StreamOwned};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::time::{Duration, Instant};
+
+// 읽기 타임아웃을 바꿀 수 있는 스트림(평문 TCP, TLS, 유닉스 도메인 소켓 지원)
+pub trait TimeoutStream: Read + Write {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl TimeoutStream for TcpStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+impl TimeoutStream for UnixStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+impl TimeoutStream for StreamOwned<ServerConnection, TcpStream> {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.sock.set_read_timeout(timeout)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReadError {
+    // 아무것도 보내지 않고 커넥션을 닫음(응답할 필요 없음)
+    Closed,
+    Malformed,
+    Timeout,
+    RequestLineTooLong,
+    HeadersTooLarge,
+    BodyTooLarge,
+    // chunked 외의 전송 코딩(gzip 등)은 지원하지 않음
+    UnsupportedTransferCoding,
+}
+
+impl ReadError {
+    // 클라이언트에게 돌려줄 상태 코드, None이면 응답 없이 커넥션을 닫음
+    pub fn status_code(&self) -> Option<&'static str> {
+        match self {
+            ReadError::Closed => None,
+            ReadError::Malformed => Some("400"),
+            ReadError::Timeout => Some("408"),
+            ReadError::RequestLineTooLong => Some("414"),
+            ReadError::HeadersTooLarge => Some("431"),
+            ReadError::BodyTooLarge => Some("413"),
+            ReadError::UnsupportedTransferCoding => Some("501"),
+        }
+    }
+}
+
+impl From<FramingError> for ReadError {
+    fn from(e: FramingError) -> Self {
+        match e {
+            // 헤더나 바디를 끝까지 받은 뒤에만 프레이밍을 검사하므로 Incomplete는 잘못된 요청
+            FramingError::Incomplete | FramingError::Malformed => ReadError::Malformed,
+            FramingError::UnsupportedTransferCoding => ReadError::UnsupportedTransferCoding,
+        }
+    }
+}
+
+// 헤더 부분(요청 행 + 헤더)과 바디를 나눠서 리턴
+#[derive(Debug, PartialEq)]
+pub struct RawRequest {
+    pub head: String,
+    pub body: Vec<u8>,
+    // 프레이밍이 모호했던 요청(strict_parsing = false에서 받아들인 경우)이라 응답 후 커넥션을 닫아야 함
+    pub must_close: bool,
+}
+
+// 바디를 읽기 전의 헤더 부분과 프레이밍
+#[derive(Debug, PartialEq)]
+pub struct RequestHead {
+    pub head: String,
+    pub body_length: BodyLength,
+    pub must_close: bool,
+}
+
+// 설정된 한도 안에서 요청 하나를 읽음
+// pending에는 이전 요청 뒤에 이미 읽어 둔 바이트가 들어 있고, 이 요청 뒤에 남은 바이트를 다시 담음(keep-alive)
+pub fn read_request(
+    stream: &mut impl TimeoutStream,
+    limits: &ServerSection,
+    pending: &mut Vec<u8>,
+) -> Result<RawRequest, ReadError> {
+    let head = read_head(stream, limits, pending)?;
+    let body = read_body(stream, &head, limits, pending)?;
+    Ok(RawRequest {
+        head: head.head,
+        body,
+        must_close: head.must_close,
+    })
+}
+
+// 요청 행과 헤더를 읽고 프레이밍을 검사, 헤더 뒤에 읽은 바이트는 pending에 남김
+// 헤더는 header_read_timeout_secs 안에 모두 도착해야 하므로 한 바이트씩 보내는 느린 클라이언트도 차단됨
+// Content-Length가 max_body_bytes를 넘으면 바디를 읽기 전에 413
+pub fn read_head(
+    stream: &mut impl TimeoutStream,
+    limits: &ServerSection,
+    pending: &mut Vec<u8>,
+) -> Result<RequestHead, ReadError> {
+    let read_timeout = Duration::from_secs(limits.read_timeout_secs);
+    let deadline = Instant::now() + Duration::from_secs(limits.header_read_timeout_secs);
+    let mut buffer: Vec<u8> = std::mem::take(pending);
+    let mut chunk = [0; 1024];
+
+    let head_end = loop {
+        match framing::head_end(&buffer) {
+            Ok(end) => break end,
+            Err(FramingError::Incomplete) => {}
+            Err(e) => return Err(e.into()),
+        }
+        check_head_size(&buffer, limits)?;
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(ReadError::Timeout);
+        }
+        let _ = stream.set_read_timeout(Some(remaining.min(read_timeout)));
+
+        match stream.read(&mut chunk) {
+            Ok(0) if buffer.is_empty() => return Err(ReadError::Closed),
+            Ok(0) => return Err(ReadError::Malformed),
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+            Err(e) if is_timeout(&e) => {
+                return Err(if buffer.is_empty() {
+                    ReadError::Closed
+                } else {
+                    ReadError::Timeout
+                })
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return Err(ReadError::Closed),
+        }
+    };
+    *pending = buffer.split_off(head_end);
+    check_head_size(&buffer, limits)?;
+
+    let head = String::from_utf8(buffer).map_err(|_| ReadError::Malformed)?;
+    check_header_count(&head, limits)?;
+    // 행 구분, 토큰, CL/TE 규칙은 http 크레이트의 프레이밍 파서가 검사
+    let framing = framing::request_framing(&head, limits.strict_parsing)?;
+    if matches!(framing.body_length, BodyLength::Fixed(length) if length > limits.max_body_bytes) {
+        return Err(ReadError::BodyTooLarge);
+    }
+
+    Ok(RequestHead {
+        head,
+        body_length: framing.body_length,
+        must_close: framing.must_close,
+    })
+}
+
+// read_head 뒤에 바디를 모두 읽음, 바디 뒤에 남은 바이트는 pending에 다시 담음
+pub fn read_body(
+    stream: &mut impl TimeoutStream,
+    head: &RequestHead,
+    limits: &ServerSection,
+    pending: &mut Vec<u8>,
+) -> Result<Vec<u8>, ReadError> {
+    // 바디는 일반 읽기 타임아웃으로 읽음
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(limits.read_timeout_secs)));
+    let body_start = std::mem::take(pending);
+    let (body, leftover) = match head.body_length {
+        BodyLength::Chunked => read_chunked_body(stream, body_start, limits.max_body_bytes)?,
+        BodyLength::Fixed(length) => read_fixed_body(stream, body_start, length)?,
+    };
+    *pending = leftover;
+    Ok(body)
+}
+
+// 읽어 두지 않은 Content-Length 바디를 커넥션에서 바로 읽는 리더(프록시가 업스트림으로 스트리밍할 때 씀)
+// pending에 이미 읽어 둔 바이트를 먼저 내주고 나머지는 length까지만 스트림에서 읽으므로
+// 다음 요청의 바이트는 pending에 그대로 남음
+pub struct BodyReader<'s, S> {
+    stream: &'s mut S,
+    pending: &'s mut Vec<u8>,
+    remaining: usize,
+}
+
+impl<'s, S: TimeoutStream> BodyReader<'s, S> {
+    pub fn new(
+        stream: &'s mut S,
+        limits: &ServerSection,
+        pending: &'s mut Vec<u8>,
+        length: usize,
+    ) -> BodyReader<'s, S> {
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(limits.read_timeout_secs)));
+        BodyReader {
+            stream,
+            pending,
+            remaining: length,
+        }
+    }
+
+    // 바디를 끝까지 읽었는지 여부, 남은 바이트가 있으면 커넥션을 다음 요청에 쓸 수 없음
+    pub fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+impl<S: Read> Read for BodyReader<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let limit = buf.len().min(self.remaining);
+        if limit == 0 {
+            return Ok(0);
+        }
+        let n = if self.pending.is_empty() {
+            let n = self.stream.read(&mut buf[..limit])?;
+            if n == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            n
+        } else {
+            let n = limit.min(self.pending.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            n
+        };
+        self.remaining -= n;
+        Ok(n)
+    }
+}
+
+fn check_head_size(buffer: &[u8], limits: &ServerSection) -> Result<(), ReadError> {
+    // 요청 행이 끝나기 전에 한도를 넘으면 414, 헤더 전체가 한도를 넘으면 431
+    let line_end = find(buffer, b"\r\n").unwrap_or(buffer.len());
+    if line_end > limits.max_request_line_bytes {
+        return Err(ReadError::RequestLineTooLong);
+    }
+    if buffer.len() > limits.max_header_bytes + 4 {
+        return Err(ReadError::HeadersTooLarge);
+    }
+    Ok(())
+}
+
+fn check_header_count(head: &str, limits: &ServerSection) -> Result<(), ReadError> {
+    let header_count = head.split("\r\n").skip(1).filter(|l| !l.is_empty()).count();
+    if header_count > limits.max_header_count {
+        return Err(ReadError::HeadersTooLarge);
+    }
+    Ok(())
+}
+
+// 바디와 그 뒤에 남은 바이트를 리턴
+fn read_fixed_body(
+    stream: &mut impl Read,
+    mut body: Vec<u8>,
+    length: usize,
+) -> Result<(Vec<u8>, Vec<u8>), ReadError> {
+    while body.len() < length {
+        fill(stream, &mut body)?;
+    }
+    let leftover = body.split_off(length);
+    Ok((body, leftover))
+}
+
+// Transfer-Encoding: chunked 바디를 디코딩(트레일러는 무시)
+fn read_chunked_body(
+    stream: &mut impl Read,
+    mut pending: Vec<u8>,
+    max_body_bytes: usize,
+) -> Result<(Vec<u8>, Vec<u8>), ReadError> {
+    let mut body = Vec::new();
+    loop {
+        // 청크 크기 행(크기;확장) 읽기
+        let size = framing::chunk_size(&take_line(stream, &mut pending)?)?;
+
+        if size == 0 {
+            // 트레일러 헤더들을 빈 행까지 소비
+            while !take_line(stream, &mut pending)?.is_empty() {}
+            return Ok((body, pending));
+        }
+        if size > max_body_bytes - body.len() {
+            return Err(ReadError::BodyTooLarge);
+        }
+
+        // 청크 데이터와 뒤따르는 CRLF 읽기
+        while pending.len() < size + 2 {
+            fill(stream, &mut pending)?;
+        }
+        if &pending[size..size + 2] != b"\r\n" {
+            return Err(ReadError::Malformed);
+        }
+        body.extend_from_slice(&pending[..size]);
+        pending.drain(..size + 2);
+    }
+}
+
+// pending 버퍼에서 CRLF로 끝나는 행 하나를 꺼냄(필요하면 스트림에서 더 읽음)
+fn take_line(stream: &mut impl Read, pending: &mut Vec<u8>) -> Result<String, ReadError> {
+    let line_end = loop {
+        if let Some(pos) = find(pending, b"\r\n") {
+            break pos;
+        }
+        if pending.len() > 1024 {
+            return Err(ReadError::Malformed);
+        }
+        fill(stream, pending)?;
+    };
+    let line = String::from_utf8(pending[..line_end].to_vec()).map_err(|_| ReadError::Malformed)?;
+    pending.drain(..line_end + 2);
+    Ok(line)
+}
+
+fn fill(stream: &mut impl Read, pending: &mut Vec<u8>) -> Result<(), ReadError> {
+    let mut chunk = [0; 4096];
+    let n = stream.read(&mut chunk).map_err(read_error)?;
+    if n == 0 {
+        return Err(ReadError::Malformed);
+    }
+    pending.extend_from_slice(&chunk[..n]);
+    Ok(())
+}
+
+fn read_error(e: io::Error) -> ReadError {
+    if is_timeout(&e) {
+        ReadError::Timeout
+    } else {
+        ReadError::Closed
+    }
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // 테스트용 메모리 스트림(타임아웃 설정은 무시)
+    struct MockStream(Cursor<Vec<u8>>);
+
+    impl Read for MockStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for MockStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl TimeoutStream for MockStream {
+        fn set_read_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn read(input: &str, limits: &ServerSection) -> Result<RawRequest, ReadError> {
+        let mut stream = MockStream(Cursor::new(input.as_bytes().to_vec()));
+        read_request(&mut stream, limits, &mut Vec::new())
+    }
+
+    #[test]
+    fn test_reads_head_and_content_length_body() {
+        let raw = read(
+            "POST /orders HTTP/1.1\r\nHost: localhost\r\nContent-Length: 11\r\n\r\nhello\nworld",
+            &ServerSection::default(),
+        )
+        .unwrap();
+        assert!(raw.head.starts_with("POST /orders HTTP/1.1\r\n"));
+        assert_eq!(raw.body, b"hello\nworld");
+    }
+
+    #[test]
+    fn test_reads_chunked_body() {
+        let raw = read(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6;ext=1\r\n world\r\n0\r\n\r\n",
+            &ServerSection::default(),
+        )
+        .unwrap();
+        assert_eq!(raw.body, b"hello world");
+    }
+
+    #[test]
+    fn test_pipelined_requests_keep_leftover() {
+        let mut stream = MockStream(Cursor::new(
+            b"POST /a HTTP/1.1\r\nContent-Length: 2\r\n\r\nhiGET /b HTTP/1.1\r\n\r\nPOST /c HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n1\r\nx\r\n0\r\n\r\nGET /d HTTP/1.0\r\n\r\n".to_vec(),
+        ));
+        let limits = ServerSection::default();
+        let mut pending = Vec::new();
+
+        let paths: Vec<(String, Vec<u8>)> = (0..4)
+            .map(|_| {
+                let raw = read_request(&mut stream, &limits, &mut pending).unwrap();
+                (raw.head.split(' ').nth(1).unwrap().to_string(), raw.body)
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("/a".to_string(), b"hi".to_vec()),
+                ("/b".to_string(), vec![]),
+                ("/c".to_string(), b"x".to_vec()),
+                ("/d".to_string(), vec![]),
+            ]
+        );
+        assert_eq!(read_request(&mut stream, &limits, &mut pending), Err(ReadError::Closed));
+
+        // HTTP/1.0 요청의 청크 인코딩은 거부
+        let chunked_1_0 = "POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n";
+        assert_eq!(read(chunked_1_0, &limits), Err(ReadError::Malformed));
+    }
+
+    #[test]
+    fn test_body_reader_stops_at_content_length() {
+        // 헤더와 함께 읽힌 바디 앞부분을 먼저 내주고 나머지는 스트림에서 읽은 뒤 다음 요청은 남겨 둠
+        let mut stream = MockStream(Cursor::new(
+            b"PUT /a HTTP/1.1\r\nContent-Length: 11\r\n\r\nhello worldGET /b HTTP/1.1\r\n\r\n".to_vec(),
+        ));
+        let limits = ServerSection::default();
+        let mut pending = Vec::new();
+
+        let head = read_head(&mut stream, &limits, &mut pending).unwrap();
+        assert_eq!(head.body_length, BodyLength::Fixed(11));
+        let mut body = BodyReader::new(&mut stream, &limits, &mut pending, 11);
+        let mut first = [0; 5];
+        body.read_exact(&mut first).unwrap();
+        assert!(!body.is_done());
+        let mut rest = Vec::new();
+        body.read_to_end(&mut rest).unwrap();
+        assert!(body.is_done());
+        assert_eq!([&first[..], &rest[..]].concat(), b"hello world");
+
+        let next = read_request(&mut stream, &limits, &mut pending).unwrap();
+        assert!(next.head.starts_with("GET /b HTTP/1.1\r\n"));
+
+        // 바디가 Content-Length보다 짧게 끝나면 오류
+        let mut stream = MockStream(Cursor::new(b"PUT /a HTTP/1.1\r\nContent-Length: 5\r\n\r\nhi".to_vec()));
+        let mut pending = Vec::new();
+        read_head(&mut stream, &limits, &mut pending).unwrap();
+        let mut body = BodyReader::new(&mut stream, &limits, &mut pending, 5);
+        let error = body.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_limits_map_to_status_codes() {
+        let limits = ServerSection {
+            max_request_line_bytes: 32,
+            max_header_count: 2,
+            max_header_bytes: 128,
+            max_body_bytes: 4,
+            ..ServerSection::default()
+        };
+
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(64));
+        assert_eq!(read(&long_line, &limits).unwrap_err().status_code(), Some("414"));
+
+        let many_headers = "GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n";
+        assert_eq!(read(many_headers, &limits).unwrap_err().status_code(), Some("431"));
+
+        let big_header = format!("GET / HTTP/1.1\r\nA: {}\r\n\r\n", "x".repeat(200));
+        assert_eq!(read(&big_header, &limits).unwrap_err().status_code(), Some("431"));
+
+        let big_body = "POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+        assert_eq!(read(big_body, &limits).unwrap_err().status_code(), Some("413"));
+
+        // 청크 크기가 한도를 넘으면 데이터를 기다리지 않고 413
+        let big_chunk = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffffffff\r\nhi\r\n0\r\n\r\n";
+        assert_eq!(read(big_chunk, &limits).unwrap_err().status_code(), Some("413"));
+    }
+
+    #[test]
+    fn test_incomplete_and_empty_requests() {
+        let limits = ServerSection::default();
+        assert_eq!(read("", &limits), Err(ReadError::Closed));
+        assert_eq!(read("GET / HTTP/1.1\r\nHost: a", &limits), Err(ReadError::Malformed));
+        assert_eq!(
+            read("POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc", &limits),
+            Err(ReadError::Malformed)
+        );
+    }
+
+    // 요청 스머글링에 쓰일 수 있는 요청은 기본(strict_parsing = true)으로 400
+    #[test]
+    fn test_strict_parsing_rejects_ambiguous_requests() {
+        let strict = ServerSection::default();
+        let lenient = ServerSection {
+            strict_parsing: false,
+            ..ServerSection::default()
+        };
+        let cl_te = "POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nhi\r\n0\r\n\r\n";
+        let space = "POST / HTTP/1.1\r\nContent-Length : 2\r\n\r\nhi";
+        let cases = [
+            cl_te,
+            "POST / HTTP/1.1\r\nContent-Length: 2\r\nContent-Length: 3\r\n\r\nhi",
+            space,
+            "POST / HTTP/1.1\r\nTransfer-Encoding\t: chunked\r\n\r\n0\r\n\r\n",
+            "POST / HTTP/1.1\r\nX(Y): 1\r\n\r\n",
+            "POST / HTTP/1.1\r\nX Y: 1\r\n\r\n",
+            "POST / HTTP/1.1\r\n: 1\r\n\r\n",
+            "GE\"T / HTTP/1.1\r\n\r\n",
+        ];
+        for input in cases {
+            let error = read(input, &strict).unwrap_err();
+            assert_eq!(error.status_code(), Some("400"), "{:?}", input);
+        }
+
+        // 엄격하지 않으면 Transfer-Encoding을 따르되 응답 후 커넥션을 닫음
+        let raw = read(cl_te, &lenient).unwrap();
+        assert_eq!(raw.body, b"hi");
+        assert!(raw.must_close);
+        let raw = read(space, &lenient).unwrap();
+        assert_eq!(raw.body, b"hi");
+        assert!(!raw.must_close);
+        // 서로 다른 Content-Length는 설정과 관계없이 거부
+        assert_eq!(read(cases[1], &lenient), Err(ReadError::Malformed));
+
+        // origin-form이 아닌 요청 대상도 설정과 관계없이 400(OPTIONS *만 허용)
+        for input in ["GET x HTTP/1.1\r\n\r\n", "GET http:x HTTP/1.1\r\n\r\n", "GET  HTTP/1.1\r\n\r\n", "GET * HTTP/1.1\r\n\r\n"] {
+            for limits in [&strict, &lenient] {
+                assert_eq!(read(input, limits).unwrap_err().status_code(), Some("400"), "{:?}", input);
+            }
+        }
+        assert!(read("OPTIONS * HTTP/1.1\r\n\r\n", &strict).is_ok());
+    }
+
+    #[test]
+    fn test_silent_client_times_out() {
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            // 요청 행의 일부만 보내고 멈춤
+            stream.write_all(b"GET / HT").unwrap();
+            std::thread::sleep(Duration::from_millis(1500));
+        });
+
+        let (mut stream, _) = listener.accept().unwrap();
+        let limits = ServerSection {
+            header_read_timeout_secs: 1,
+            ..ServerSection::default()
+        };
+        let started = Instant::now();
+        assert_eq!(read_request(&mut stream, &limits, &mut Vec::new()), Err(ReadError::Timeout));
+        assert!(started.elapsed() < Duration::from_millis(1400));
+        client.join().unwrap();
+    }
+}
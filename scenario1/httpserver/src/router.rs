@@ -1,40 +1,364 @@
-use super::handler::{Handler, PageNotFoundHandler, StaticPageHandler, WebServiceHandler};
+use super::auth::AuthError;
+use super::config::{HandlerKind, RouteSection, ServerConfig};
+use super::handler::{
+    FormHandler, Handler, MetricsHandler, OrderStatusSocket, PageNotFoundHandler, SessionHandler,
+    StaticPageHandler, WebServiceHandler,
+};
+use super::proxy::ProxyHandler;
+use super::reader::TimeoutStream;
+use super::sse;
+use super::state::{AppState, RequestContext, StreamedBody};
+use super::websocket;
 use http::{httprequest, httprequest::HttpRequest, httpresponse::HttpResponse};
-use std::io::prelude::*;
+use std::io::Read;
+
+// 101 응답을 보낸 뒤 커넥션과 요청 뒤에 이미 읽어 둔 바이트를 넘겨받아 다른 프로토콜로 통신하는 훅
+pub type Upgrade<'a> = Box<dyn FnOnce(&mut dyn TimeoutStream, Vec<u8>) + 'a>;
+
+// 라우터가 리턴하는 응답
+// 프록시처럼 바디를 미리 읽을 수 없는 경우에는 헤더만 담고 바디는 스트림으로 전달
+pub struct Reply<'a> {
+    pub response: HttpResponse<'a>,
+    pub body_stream: Option<Box<dyn Read + 'a>>,
+    pub upgrade: Option<Upgrade<'a>>,
+}
+
+impl<'a> From<HttpResponse<'a>> for Reply<'a> {
+    fn from(response: HttpResponse<'a>) -> Self {
+        Reply {
+            response,
+            body_stream: None,
+            upgrade: None,
+        }
+    }
+}
 
 pub struct Router;
 
 impl Router {
-    pub fn route(req: HttpRequest, stream: &mut impl Write) -> () {
-        match req.method {
-            // GET 요청이면
-            httprequest::Method::Get => match &req.resource {
-                httprequest::Resource::Path(s) => {
-                    
-                    // URI를 파싱
-                    let route: Vec<&str> = s.split("/").collect();
-
-                    match route[1] {
-                        // 라우트가 /api로 시작하면 웹 서비스를 호출
-                        "api" => {
-                            let resp: HttpResponse = WebServiceHandler::handle(&req);
-                            let _ = resp.send_response(stream);
-                        }
-                        // 그렇지 않으면 정적 페이지 핸들러를 호출
-                        _ => {
-                            let resp: HttpResponse = StaticPageHandler::handle(&req);
-                            let _ = resp.send_response(stream);
-                        }
+    // 요청을 적절한 핸들러로 보내고 응답을 리턴(전송은 서버가 담당)
+    // body는 서버가 읽어 두지 않은 요청 바디로, 프록시 라우트만 업스트림으로 스트리밍하며 읽음
+    pub fn route<'a>(req: &'a HttpRequest, ctx: &RequestContext<'a>, body: Option<StreamedBody>) -> Reply<'a> {
+        let httprequest::Resource::Path(s) = &req.resource;
+        let (site, route) = Router::resolve(&ctx.state.config, host(req), s);
+
+        // 요청 한도가 있는 라우트는 인증보다 먼저 검사(비밀번호 대입 시도도 한도에 걸림)
+        let limiter = route
+            .and_then(|route| route.rate_limit.as_ref())
+            .and_then(|name| ctx.state.rate_limiters.get(name));
+        // key = "token"이면 인증을 통과한 토큰만 따로 세므로 인증을 먼저 확인하고, 그 결과를 핸들러 호출에도 씀
+        // 인증에 실패한 요청은 IP로 세므로 Authorization 값을 바꿔 가며 보내도 한도를 피할 수 없음
+        let authenticator = route
+            .and_then(|route| route.auth.as_ref())
+            .and_then(|name| ctx.state.authenticators.get(name));
+        let auth = match (limiter, authenticator) {
+            (Some(limiter), Some(authenticator)) if limiter.keys_by_token() => Some(authenticator.check(req)),
+            _ => None,
+        };
+        let decision = limiter.map(|limiter| limiter.acquire(req, ctx.peer, auth == Some(Ok(()))));
+        if let Some(decision) = decision.filter(|decision| !decision.allowed) {
+            return decision.rejection().into();
+        }
+
+        let mut reply = Router::call_handler(req, ctx, site, route, auth, body);
+        if let Some(decision) = decision {
+            decision.add_headers(&mut reply.response);
+        }
+        reply
+    }
+
+    // Host 헤더로 가상 호스트를 선택하고(일치하는 호스트가 없으면 기본 설정)
+    // 그 사이트의 라우트 테이블에서 경로 접두사가 일치하는 첫 번째 라우트를 찾음
+    pub fn resolve<'c>(
+        config: &'c ServerConfig,
+        host: &str,
+        path: &str,
+    ) -> (&'c ServerConfig, Option<&'c RouteSection>) {
+        let site = config.site_for(host);
+        (site, site.routes.iter().find(|route| route.matches(path)))
+    }
+
+    // 소켓 없이 요청 하나를 라우팅하고 응답을 리턴(핸들러 단위 테스트용)
+    // 세션은 요청의 쿠키로 불러와서 저장하고, 스트리밍 바디는 끝까지 읽어서 바디에 담음
+    // CORS, HTTPS 리다이렉트, keep-alive처럼 커넥션 단위로 서버가 처리하는 부분은 testing::TestServer로 확인
+    pub fn dispatch(state: &AppState, req: HttpRequest) -> HttpResponse<'static> {
+        let session = state.sessions.load(&req);
+        let ctx = RequestContext {
+            state,
+            peer: None,
+            secure: false,
+            body: req.msg_body.as_bytes(),
+            session: &session,
+        };
+        let reply = Router::route(&req, &ctx, None);
+        let mut resp = reply.response.into_owned();
+        if let Some(mut body_stream) = reply.body_stream {
+            let mut body = Vec::new();
+            let _ = body_stream.read_to_end(&mut body);
+            resp.set_body(Some(String::from_utf8_lossy(&body).into_owned()));
+        }
+        state.sessions.commit(&session, &mut resp, false);
+        resp
+    }
+
+    fn call_handler<'a>(
+        req: &'a HttpRequest,
+        ctx: &RequestContext<'a>,
+        site: &'a ServerConfig,
+        route: Option<&'a RouteSection>,
+        auth: Option<Result<(), AuthError>>,
+        body: Option<StreamedBody>,
+    ) -> Reply<'a> {
+        // 인증이 필요한 라우트는 핸들러로 보내기 전에 자격 증명을 확인(실패하면 401)
+        // 요청 한도를 정하면서 이미 확인했으면 그 결과를 씀
+        if let Some(name) = route.and_then(|route| route.auth.as_ref()) {
+            match ctx.state.authenticators.get(name) {
+                Some(authenticator) => {
+                    if let Err(error) = auth.unwrap_or_else(|| authenticator.check(req)) {
+                        return authenticator.challenge(error).into();
                     }
+                }
+                None => return HttpResponse::new("500", None, Some(String::new())).into(),
+            }
+        }
 
+        match req.method {
+            // 프록시 라우트는 메서드와 관계없이 업스트림으로 전달
+            _ if route.map(|route| route.handler) == Some(HandlerKind::Proxy) => {
+                let route = route.unwrap();
+                match route.upstream.as_ref().and_then(|name| ctx.state.upstreams.get(name)) {
+                    Some(pool) => ProxyHandler::forward(req, route, pool, ctx, body),
+                    None => HttpResponse::new("502", None, Some(String::new())).into(),
                 }
+            }
+
+            // 세션 라우트는 메서드마다 세션 값을 읽고 바꾸고 지움
+            _ if route.map(|route| route.handler) == Some(HandlerKind::Session) => {
+                let privileged_keys = &ctx.state.config.session.privileged_keys;
+                SessionHandler::handle(req, ctx.session, privileged_keys).into()
+            }
+
+            // 폼 라우트는 POST로 제출된 폼과 업로드 파일을 받음
+            httprequest::Method::Post if route.map(|route| route.handler) == Some(HandlerKind::Form) => {
+                FormHandler::handle(req, ctx.body, site).into()
+            }
+
+            // GET 요청이면
+            httprequest::Method::Get => match route.map(|route| route.handler) {
+                // 웹 서비스 라우트(기본 설정에서는 /api), 이벤트 스트림을 요청하면 변경 피드로 응답
+                Some(HandlerKind::WebService) if sse::accepts_event_stream(req) => {
+                    WebServiceHandler::stream(req, site)
+                }
+                Some(HandlerKind::WebService) => WebServiceHandler::handle(req, site).into(),
+                // 정적 페이지 라우트
+                Some(HandlerKind::Static) => StaticPageHandler::handle(req, site).into(),
+                // 서버 지표(Prometheus 텍스트 형식)
+                Some(HandlerKind::Metrics) => MetricsHandler::handle(&ctx.state.metrics).into(),
+                // 웹소켓 라우트는 핸드셰이크 후 커넥션을 엔드포인트 핸들러에 넘김
+                Some(HandlerKind::WebSocket) => match route.and_then(|r| r.endpoint.as_deref()) {
+                    Some("order_status") => websocket::accept(
+                        req,
+                        Box::new(OrderStatusSocket::new(site)),
+                        site.server.max_body_bytes,
+                    ),
+                    _ => PageNotFoundHandler::handle(req, site).into(),
+                },
+                _ => PageNotFoundHandler::handle(req, site).into(),
             },
 
             // 메서드가 GET 요청이 아니면 404 페이지를 리턴
-            _ => {
-                let resp: HttpResponse = PageNotFoundHandler::handle(&req);
-                let _ = resp.send_response(stream);
-            }
+            _ => PageNotFoundHandler::handle(req, site).into(),
         }
     }
-}
\ No newline at end of file
+}
+
+fn host(req: &HttpRequest) -> &str {
+    req.headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("Host"))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AuthSection, RateLimitKey, RateLimitSection, VirtualHostSection};
+    use crate::session::Session;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn request(host: &str, path: &str) -> HttpRequest {
+        format!("GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, host).into()
+    }
+
+    // 라우팅 결과의 상태 코드와 바디
+    fn route(req: &HttpRequest, state: &AppState) -> (String, String) {
+        let ctx = RequestContext {
+            state,
+            peer: None,
+            secure: false,
+            body: &[],
+            session: &Session::default(),
+        };
+        let resp = Router::route(req, &ctx, None).response;
+        (resp.status_code().to_string(), resp.body().to_string())
+    }
+
+    #[test]
+    fn test_dispatch_by_host_header() {
+        let dir = std::env::temp_dir().join(format!("httpserver-vhost-{}", std::process::id()));
+        for site in ["shop", "blog"] {
+            fs::create_dir_all(dir.join(site)).unwrap();
+            fs::write(dir.join(site).join("index.html"), format!("{} index", site)).unwrap();
+        }
+
+        let vhost = |hostnames: &[&str], site: &str, routes: Option<Vec<RouteSection>>| {
+            VirtualHostSection {
+                hostnames: hostnames.iter().map(|h| h.to_string()).collect(),
+                public_path: Some(dir.join(site)),
+                data_path: None,
+                cache: None,
+                routes,
+            }
+        };
+        let mut config = ServerConfig::default();
+        // 기본 퍼블릭 디렉터리의 index.html을 파일 그대로 비교
+        config.static_files.templates = false;
+        config.vhosts = vec![
+            vhost(&["shop.localhost"], "shop", None),
+            vhost(
+                &["*.blog.localhost"],
+                "blog",
+                Some(vec![RouteSection {
+                    prefix: "/".to_string(),
+                    handler: HandlerKind::Static,
+                    upstream: None,
+                    strip_prefix: false,
+                    endpoint: None,
+                    auth: None,
+                    rate_limit: None,
+                }]),
+            ),
+        ];
+        config.resolve_vhosts();
+        let state = AppState::new(config);
+
+        let req = request("shop.localhost", "/");
+        assert_eq!(route(&req, &state).1, "shop index");
+        let req = request("Alice.Blog.localhost", "/");
+        assert_eq!(route(&req, &state).1, "blog index");
+
+        // 가상 호스트의 라우트 테이블에 /api가 없으면 정적 핸들러가 처리(파일이 없으므로 404)
+        let req = request("x.blog.localhost", "/api/shipping/orders");
+        assert_eq!(route(&req, &state).0, "404");
+
+        // 일치하는 호스트가 없으면 기본 설정의 퍼블릭 디렉터리를 사용
+        let req = request("unknown.localhost", "/");
+        let default_index = fs::read_to_string(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("public/index.html"),
+        )
+        .unwrap();
+        assert_eq!(route(&req, &state).1, default_index);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_protected_route_requires_credentials() {
+        let mut config = ServerConfig::default();
+        config.auth.insert(
+            "api".to_string(),
+            AuthSection {
+                tokens: vec!["secret-token".to_string()],
+                ..AuthSection::default()
+            },
+        );
+        config.routes[2].auth = Some("api".to_string());
+        let state = AppState::new(config);
+
+        let req = request("localhost", "/");
+        assert_eq!(route(&req, &state).0, "401");
+        let req: HttpRequest = "GET / HTTP/1.1\r\nAuthorization: Bearer secret-token\r\n\r\n"
+            .to_string()
+            .into();
+        assert_eq!(route(&req, &state).0, "200");
+    }
+
+    #[test]
+    fn test_rate_limited_route() {
+        let mut config = ServerConfig::default();
+        config.rate_limits.insert(
+            "static".to_string(),
+            RateLimitSection {
+                requests: 1,
+                window_secs: 60,
+                ..RateLimitSection::default()
+            },
+        );
+        config.routes[2].rate_limit = Some("static".to_string());
+        let state = AppState::new(config);
+
+        let req = request("localhost", "/");
+        assert_eq!(route(&req, &state).0, "200");
+        assert_eq!(route(&req, &state).0, "429");
+    }
+
+    #[test]
+    fn test_token_rate_limit_counts_unauthenticated_requests_by_ip() {
+        let mut config = ServerConfig::default();
+        config.auth.insert(
+            "api".to_string(),
+            AuthSection {
+                tokens: vec!["secret-token".to_string()],
+                ..AuthSection::default()
+            },
+        );
+        config.rate_limits.insert(
+            "api".to_string(),
+            RateLimitSection {
+                requests: 1,
+                window_secs: 60,
+                key: RateLimitKey::Token,
+                ..RateLimitSection::default()
+            },
+        );
+        config.routes[2].auth = Some("api".to_string());
+        config.routes[2].rate_limit = Some("api".to_string());
+        let state = AppState::new(config);
+        let with_token = |token: &str| -> HttpRequest {
+            format!("GET / HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", token).into()
+        };
+
+        // 인증에 실패한 토큰은 값을 바꿔도 같은 IP 버킷을 씀
+        assert_eq!(route(&with_token("guess-1"), &state).0, "401");
+        assert_eq!(route(&with_token("guess-2"), &state).0, "429");
+        // 인증을 통과한 토큰은 자기 버킷을 씀
+        assert_eq!(route(&with_token("secret-token"), &state).0, "200");
+        assert_eq!(route(&with_token("secret-token"), &state).0, "429");
+    }
+
+    #[test]
+    fn test_site_for_prefers_exact_then_longest_wildcard() {
+        let vhost = |name: &str, public: &str| VirtualHostSection {
+            hostnames: vec![name.to_string()],
+            public_path: Some(PathBuf::from(public)),
+            data_path: None,
+            cache: None,
+            routes: None,
+        };
+        let mut config = ServerConfig::default();
+        config.vhosts = vec![
+            vhost("*.example.com", "/wildcard"),
+            vhost("*.api.example.com", "/api-wildcard"),
+            vhost("v1.api.example.com", "/exact"),
+        ];
+        config.resolve_vhosts();
+
+        let public = |host: &str| config.site_for(host).static_files.public_path.clone();
+        assert_eq!(public("v1.api.example.com:3000"), PathBuf::from("/exact"));
+        assert_eq!(public("v2.api.example.com"), PathBuf::from("/api-wildcard"));
+        assert_eq!(public("WWW.EXAMPLE.COM."), PathBuf::from("/wildcard"));
+        assert_eq!(public("example.com"), config.static_files.public_path);
+    }
+}
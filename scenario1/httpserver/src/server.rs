@@ -1,39 +1,647 @@
-use super::router::Router;
-use http::httprequest::HttpRequest;
-use std::io::prelude::*;
-use std::net::TcpListener;
-use std::str;
+use super::access_log::{AccessLog, AccessLogEntry};
+use super::config::{HandlerKind, LogLevel, ServerConfig, TlsSection};
+use super::cors;
+use super::lifecycle::{self, ServerHandle};
+use super::listener::{self, Connection, Listener};
+use super::metrics;
+use super::pool::ThreadPool;
+use super::reader::{self, BodyReader, ReadError, TimeoutStream};
+use super::router::{Reply, Router};
+use super::state::{AppState, RequestContext, StreamedBody};
+use chrono::Local;
+use http::framing::{self, BodyLength};
+use http::httprequest::{HttpRequest, Resource, Version};
+use http::httpresponse::HttpResponse;
+use std::fs;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::os::unix::net::UnixStream;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// 종료 요청을 확인하는 주기(커넥션이 들어오면 기다리지 않고 바로 받음)
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 pub struct Server<'a> {
     socket_addr: &'a str,
+    handle: ServerHandle,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    access_log: Option<Arc<AccessLog>>,
 }
 
 impl<'a> Server<'a> {
-    pub fn new(socket_addr: &'a str) -> Self {
-        Server { socket_addr }
+    pub fn new(socket_addr: &'a str, handle: ServerHandle) -> Self {
+        Server {
+            socket_addr,
+            handle,
+            tls: None,
+            access_log: None,
+        }
+    }
+
+    // 이 리스너를 HTTPS로 동작하도록 설정
+    pub fn with_tls(mut self, tls: Arc<rustls::ServerConfig>) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    // 처리한 요청을 액세스 로그에 기록
+    pub fn with_access_log(mut self, access_log: Arc<AccessLog>) -> Self {
+        self.access_log = Some(access_log);
+        self
     }
 
     pub fn run(&self) {
-        // 소켓 주소를 리스닝하는 서버를 시작
-        let connection_listener = TcpListener::bind(self.socket_addr).unwrap();
-        println!("Running on {}", self.socket_addr);
+        // 소켓 주소를 리스닝하는 서버를 시작(이전 프로세스가 넘겨준 소켓이 있으면 그대로 사용)
+        // "unix:" 주소는 TCP 대신 유닉스 도메인 소켓에 바인딩
+        let connection_listener = match lifecycle::inherited_listener(self.socket_addr) {
+            Some(listener) => listener,
+            None => {
+                let mode = self.handle.state().config.server.unix_socket_mode;
+                Listener::bind(self.socket_addr, mode).unwrap()
+            }
+        };
+        self.run_on(connection_listener);
+    }
+
+    // 이미 바인딩한 소켓으로 서버를 실행(임시 포트에 바인딩한 테스트 서버)
+    pub fn run_on(&self, connection_listener: Listener) {
+        // 종료 요청을 확인할 수 있도록 논블로킹으로 받음
+        connection_listener.set_nonblocking(true).unwrap();
+        self.handle.add_listener(self.socket_addr, &connection_listener);
+        let state = self.handle.state();
+        let config = &state.config;
+        if config.log.level >= LogLevel::Info {
+            let scheme = if self.tls.is_some() { "https" } else { "http" };
+            println!("Running on {} ({})", self.socket_addr, scheme);
+        }
+
+        // 커넥션은 설정된 개수의 워커 스레드에서 처리
+        let pool = ThreadPool::new(config.server.workers);
+
+        // 종료할 때까지 루프 안에서 유입되는 커넥션을 리스닝
+        while !state.is_shutting_down() {
+            if !lifecycle::wait_readable(&connection_listener, ACCEPT_POLL_INTERVAL) {
+                continue;
+            }
+
+            let connection = match connection_listener.accept() {
+                Ok(connection) => connection,
+                // 리스닝 소켓을 넘겨받은 새 프로세스가 먼저 받아간 경우
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                Err(e) => {
+                    if config.log.level >= LogLevel::Error {
+                        eprintln!("Failed to accept connection: {}", e);
+                    }
+                    continue;
+                }
+            };
+            let _ = connection.set_nonblocking(false);
+            if config.log.level >= LogLevel::Debug {
+                println!("Connection established");
+            }
+
+            // 설정을 다시 로드했으면 새 커넥션부터 새 설정으로 처리
+            let state = self.handle.state();
+            let tls = self.tls.clone();
+            let access_log = self.access_log.clone();
+            state.metrics.connection_queued();
+            pool.execute(move || {
+                let _connection = state.metrics.connection_started();
+                let access_log = access_log.as_deref();
+                match (connection, tls) {
+                    (Connection::Tcp(stream), Some(tls)) => handle_tls_connection(stream, tls, &state, access_log),
+                    (Connection::Tcp(stream), None) => handle_connection(stream, &state, access_log),
+                    (Connection::Unix(stream), _) => handle_unix_connection(stream, &state, access_log),
+                }
+            });
+        }
 
-        // 루프 안에서 유입되는 커넥션을 리스닝
-        for stream in connection_listener.incoming() {
+        // 리스닝 소켓을 닫고 처리 중이거나 대기 중인 커넥션이 끝나기를 기다림
+        self.handle.remove_listener(self.socket_addr);
+        drop(connection_listener);
+        // 소켓을 넘겨받은 새 프로세스가 계속 쓰는 경우가 아니면 유닉스 도메인 소켓 파일을 지움
+        if let Some(path) = listener::unix_path(self.socket_addr) {
+            if !self.handle.handed_off() {
+                let _ = fs::remove_file(path);
+            }
+        }
+        let remaining = pool.shutdown(Duration::from_secs(config.server.shutdown_timeout_secs));
+        if remaining > 0 && config.log.level >= LogLevel::Warn {
+            eprintln!("Stopped {} with {} connections still open", self.socket_addr, remaining);
+        }
+    }
+
+}
+
+fn handle_connection(mut stream: TcpStream, state: &AppState, access_log: Option<&AccessLog>) {
+    set_timeouts(&stream, &state.config);
+    let peer = stream.peer_addr().ok();
+    serve(&mut stream, peer, state, access_log, false);
+}
 
-            let mut stream = stream.unwrap();
-            println!("Connection established");
-            let mut read_buffer = [0; 90];
+// 유닉스 도메인 소켓은 피어 주소가 없고 평문으로만 처리
+fn handle_unix_connection(mut stream: UnixStream, state: &AppState, access_log: Option<&AccessLog>) {
+    let config = &state.config;
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(config.server.read_timeout_secs)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(config.server.write_timeout_secs)));
+    serve(&mut stream, None, state, access_log, false);
+}
+
+fn handle_tls_connection(
+    stream: TcpStream,
+    tls: Arc<rustls::ServerConfig>,
+    state: &AppState,
+    access_log: Option<&AccessLog>,
+) {
+    set_timeouts(&stream, &state.config);
+    let peer = stream.peer_addr().ok();
 
-            // 바이트 스트림 읽기
-            stream.read(&mut read_buffer).unwrap();
+    // 핸드셰이크는 첫 번째 read/write 시점에 StreamOwned가 처리
+    let connection = match rustls::ServerConnection::new(tls) {
+        Ok(connection) => connection,
+        Err(_) => return,
+    };
+    let mut tls_stream = rustls::StreamOwned::new(connection, stream);
+    serve(&mut tls_stream, peer, state, access_log, true);
 
-            // HTTP 요청을 러스트 데이터 구조체로 변환
-            let req: HttpRequest = String::from_utf8(read_buffer.to_vec()).unwrap().into();
+    // 커넥션을 닫기 전에 close_notify 알림을 전송
+    tls_stream.conn.send_close_notify();
+    let _ = tls_stream.flush();
+}
 
-            // 요청을 적절한 핸들(라우터)로 전달
-            Router::route(req, &mut stream);
+// 설정된 타임아웃을 적용
+fn set_timeouts(stream: &TcpStream, config: &ServerConfig) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(config.server.read_timeout_secs)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(config.server.write_timeout_secs)));
+}
+
+// 커넥션 하나에서 요청을 차례로 처리(keep-alive)
+fn serve(
+    stream: &mut impl TimeoutStream,
+    peer: Option<SocketAddr>,
+    state: &AppState,
+    access_log: Option<&AccessLog>,
+    secure: bool,
+) {
+    let keep_alive_timeout = Duration::from_secs(state.config.server.keep_alive_timeout_secs);
+    let mut pending = Vec::new();
+    while serve_request(stream, peer, state, access_log, secure, &mut pending) {
+        // 다음 요청의 첫 바이트는 keep-alive 타임아웃 동안만 기다림
+        if pending.is_empty() && !wait_for_request(stream, keep_alive_timeout, &mut pending) {
+            return;
         }
     }
+}
+
+// 요청 하나를 읽어서 응답하고, 같은 커넥션에서 다음 요청을 받을 수 있으면 true를 리턴
+fn serve_request(
+    stream: &mut impl TimeoutStream,
+    peer: Option<SocketAddr>,
+    state: &AppState,
+    access_log: Option<&AccessLog>,
+    secure: bool,
+    pending: &mut Vec<u8>,
+) -> bool {
+    let config = &state.config;
+    let started = Instant::now();
+    let log = |head: &str, resp: &HttpResponse, bytes_sent: usize| {
+        // 지표의 route 레이블은 요청을 처리한 라우트의 접두사
+        let route = head
+            .lines()
+            .next()
+            .and_then(|line| line.split(' ').nth(1))
+            .and_then(|path| Router::resolve(config, framing::header_value(head, "Host").unwrap_or(""), path).1)
+            .map_or(metrics::NO_ROUTE, |route| route.prefix.as_str());
+        state.metrics.observe(route, resp.status_code(), started.elapsed());
 
-}
\ No newline at end of file
+        if let Some(access_log) = access_log {
+            access_log.log(&AccessLogEntry {
+                peer,
+                time: Local::now(),
+                request_line: head.lines().next().unwrap_or(""),
+                status: resp.status_code(),
+                bytes_sent,
+                referer: framing::header_value(head, "Referer"),
+                user_agent: framing::header_value(head, "User-Agent"),
+                duration: started.elapsed(),
+            });
+        }
+    };
+
+    // 설정된 한도 안에서 요청 행과 헤더를 읽고, 한도를 넘으면 해당 상태 코드로 응답하고 끝냄
+    let raw = match reader::read_head(stream, &config.server, pending) {
+        Ok(raw) => raw,
+        Err(e) => {
+            if let Some(resp) = send_read_error(stream, &e) {
+                log("", &resp, resp.body().len());
+            }
+            return false;
+        }
+    };
+
+    // HTTP 요청을 러스트 데이터 구조체로 변환
+    let mut req = HttpRequest::from(raw.head.clone());
+
+    // 프록시 라우트로 가는 Content-Length 바디는 읽어 두지 않고 업스트림으로 스트리밍
+    // 그 밖의 바디는 한도 안에서 모두 읽어서 msg_body를 채움
+    let Resource::Path(path) = &req.resource;
+    let streamed_length = match raw.body_length {
+        BodyLength::Fixed(length)
+            if length > 0
+                && Router::resolve(config, framing::header_value(&raw.head, "Host").unwrap_or(""), path)
+                    .1
+                    .is_some_and(|route| route.handler == HandlerKind::Proxy) =>
+        {
+            Some(length)
+        }
+        _ => None,
+    };
+    let body = match streamed_length {
+        Some(_) => Vec::new(),
+        None => match reader::read_body(stream, &raw, &config.server, pending) {
+            Ok(body) => body,
+            Err(e) => {
+                if let Some(resp) = send_read_error(stream, &e) {
+                    log(&raw.head, &resp, resp.body().len());
+                }
+                return false;
+            }
+        },
+    };
+    req.msg_body = String::from_utf8_lossy(&body).to_string();
+
+    // HTTP/1.0과 HTTP/1.1만 처리하고 나머지 버전은 505로 응답
+    if !matches!(req.version, Version::V1_0 | Version::V1_1) {
+        let mut resp = HttpResponse::new("505", None, Some(String::new()));
+        resp.add_header("Connection", "close");
+        let _ = resp.send_response(stream);
+        log(&raw.head, &resp, resp.body().len());
+        return false;
+    }
+    let mut keep_alive =
+        config.server.keep_alive_timeout_secs > 0
+            && wants_keep_alive(&req)
+            && !raw.must_close
+            && !state.is_shutting_down();
+
+    let session = state.sessions.load(&req);
+    let ctx = RequestContext {
+        state,
+        peer,
+        secure,
+        body: &body,
+        session: &session,
+    };
+    let mut body_reader = BodyReader::new(stream, &config.server, pending, streamed_length.unwrap_or(0));
+    let streamed_body = streamed_length.map(|length| StreamedBody {
+        reader: &mut body_reader,
+        length,
+    });
+    let Reply {
+        response: mut resp,
+        body_stream,
+        upgrade,
+    } = match &config.tls {
+        // 평문 리스너는 HTTPS로 리다이렉트하도록 설정되어 있으면 라우팅하지 않음
+        Some(tls) if !secure && tls.redirect_http => https_redirect(&req, tls).into(),
+
+        // CORS 프리플라이트는 인증을 요구하지 않으므로 라우팅하지 않고 바로 응답
+        _ if config.cors.is_some() && cors::is_preflight(&req) => {
+            cors::preflight(&req, config.cors.as_ref().unwrap()).into()
+        }
+
+        // 요청을 적절한 핸들(라우터)로 전달
+        _ => Router::route(&req, &ctx, streamed_body),
+    };
+
+    // 스트리밍할 바디를 끝까지 읽지 못했으면(업스트림에 연결하지 못한 경우 등) 다음 요청의 시작을 알 수 없음
+    if !body_reader.is_done() {
+        keep_alive = false;
+    }
+
+    // 핸들러가 바꾼 세션을 저장하고 필요하면 세션 쿠키를 발급
+    state.sessions.commit(&session, &mut resp, secure);
+
+    // 허용된 오리진에서 온 요청이면 CORS 헤더를 추가
+    if let Some(cors) = &config.cors {
+        cors::apply(&req, cors, &mut resp);
+    }
+
+    // HSTS 헤더는 HTTPS 응답에만 추가
+    if secure {
+        if let Some(hsts) = config.tls.as_ref().and_then(|tls| tls.hsts.as_ref()) {
+            resp.add_header("Strict-Transport-Security", hsts.header_value());
+        }
+    }
+
+    // 프로토콜 전환을 요청한 HTTP/1.1 요청에 101로 응답하면 커넥션을 업그레이드 훅에 넘김
+    if resp.status_code() == "101" && requested_upgrade(&req).is_some() {
+        if let Some(upgrade) = upgrade {
+            if resp.send_head(stream).and_then(|_| stream.flush()).is_ok() {
+                log(&raw.head, &resp, 0);
+                upgrade(stream, std::mem::take(pending));
+            }
+            return false;
+        }
+    }
+
+    // 길이를 알 수 없는 스트리밍 바디는 커넥션을 닫아서 끝을 알림
+    if body_stream.is_some() && resp.header("Content-Length").is_none() && !resp.is_chunked() {
+        keep_alive = false;
+    }
+
+    // HTTP/1.0 요청에는 HTTP/1.0으로 응답하고, 기본 동작과 다를 때만 Connection 헤더를 보냄
+    if req.version == Version::V1_0 {
+        resp.set_version("HTTP/1.0");
+        if keep_alive {
+            resp.add_header("Connection", "keep-alive");
+        }
+    } else if !keep_alive {
+        resp.add_header("Connection", "close");
+    }
+
+    // 스트리밍 바디는 헤더를 먼저 보내고 원본에서 그대로 복사
+    let (sent, bytes_sent) = match body_stream {
+        Some(mut body) => match resp.send_head(stream) {
+            Ok(()) => match io::copy(&mut body, stream) {
+                Ok(n) => (stream.flush().is_ok(), n as usize),
+                Err(_) => (false, 0),
+            },
+            Err(_) => (false, 0),
+        },
+        None => (resp.send_response(stream).is_ok(), resp.body().len()),
+    };
+    log(&raw.head, &resp, bytes_sent);
+    sent && keep_alive
+}
+
+// 요청을 읽다가 생긴 오류를 해당 상태 코드로 응답하고 보낸 응답을 리턴(응답 없이 닫는 경우는 None)
+fn send_read_error(stream: &mut impl TimeoutStream, e: &ReadError) -> Option<HttpResponse<'static>> {
+    let mut resp = HttpResponse::new(e.status_code()?, None, Some(String::new()));
+    resp.add_header("Connection", "close");
+    let _ = resp.send_response(stream);
+    Some(resp)
+}
+
+// keep-alive 타임아웃 안에 다음 요청의 바이트가 도착하면 pending에 담고 true를 리턴
+fn wait_for_request(stream: &mut impl TimeoutStream, timeout: Duration, pending: &mut Vec<u8>) -> bool {
+    let _ = stream.set_read_timeout(Some(timeout));
+    let mut chunk = [0; 1024];
+    match stream.read(&mut chunk) {
+        Ok(n) if n > 0 => {
+            pending.extend_from_slice(&chunk[..n]);
+            true
+        }
+        _ => false,
+    }
+}
+
+// Connection 헤더의 토큰들(소문자)
+fn connection_tokens(req: &HttpRequest) -> Vec<String> {
+    req.headers
+        .iter()
+        .filter(|(key, _)| key.trim().eq_ignore_ascii_case("Connection"))
+        .flat_map(|(_, value)| value.split(','))
+        .map(|token| token.trim().to_ascii_lowercase())
+        .collect()
+}
+
+// HTTP/1.1은 Connection: close가 없으면, HTTP/1.0은 Connection: keep-alive가 있으면 커넥션을 유지
+fn wants_keep_alive(req: &HttpRequest) -> bool {
+    let tokens = connection_tokens(req);
+    match req.version {
+        Version::V1_1 => !tokens.iter().any(|t| t == "close"),
+        _ => tokens.iter().any(|t| t == "keep-alive"),
+    }
+}
+
+// HTTP/1.1 요청이 Connection: upgrade와 함께 요청한 프로토콜 이름(소문자)
+pub fn requested_upgrade(req: &HttpRequest) -> Option<String> {
+    if req.version != Version::V1_1 || !connection_tokens(req).iter().any(|t| t == "upgrade") {
+        return None;
+    }
+    req.headers
+        .iter()
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("Upgrade"))
+        .map(|(_, value)| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty())
+}
+
+// 같은 호스트와 경로의 HTTPS 주소로 영구 리다이렉트
+fn https_redirect<'a>(req: &'a HttpRequest, tls: &TlsSection) -> HttpResponse<'a> {
+    let Resource::Path(path) = &req.resource;
+    let host = req
+        .headers
+        .get("Host")
+        .map(|h| h.trim())
+        .filter(|h| !h.is_empty())
+        .unwrap_or("localhost");
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+
+    let location = match tls.https_port() {
+        Some(443) | None => format!("https://{}{}", host, path),
+        Some(port) => format!("https://{}:{}{}", host, port, path),
+    };
+    let mut resp = HttpResponse::new("301", None, Some(String::new()));
+    resp.add_header("Location", location);
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tls_section(bind: &str) -> TlsSection {
+        TlsSection {
+            bind: vec![bind.to_string()],
+            certificates: vec![],
+            redirect_http: true,
+            hsts: None,
+        }
+    }
+
+    #[test]
+    fn test_https_redirect_keeps_host_and_path() {
+        let req: HttpRequest =
+            String::from("GET /api/shipping/orders HTTP/1.1\r\nHost: example.com\r\n\r\n").into();
+
+        let resp = https_redirect(&req, &tls_section("0.0.0.0:443"));
+        assert_eq!(resp.status_code(), "301");
+        assert_eq!(resp.header("Location"), Some("https://example.com/api/shipping/orders"));
+
+        let resp = https_redirect(&req, &tls_section("localhost:3443"));
+        assert_eq!(resp.header("Location"), Some("https://example.com:3443/api/shipping/orders"));
+    }
+
+    // 요청들을 한 번에 보내고 서버가 커넥션을 닫을 때까지 받은 응답 전체를 리턴
+    fn exchange(requests: &str) -> String {
+        use crate::config::ServerConfig;
+        use std::io::Read;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = requests.to_string();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(requests.as_bytes()).unwrap();
+            let mut received = String::new();
+            stream.read_to_string(&mut received).unwrap();
+            received
+        });
+
+        let (mut stream, peer) = listener.accept().unwrap();
+        let mut config = ServerConfig::default();
+        config.server.keep_alive_timeout_secs = 1;
+        serve(&mut stream, Some(peer), &AppState::new(config), None, false);
+        drop(stream);
+        client.join().unwrap()
+    }
+
+    #[test]
+    fn test_keep_alive_by_version() {
+        // 1.1은 기본으로 유지, 1.0은 keep-alive를 요청할 때만 유지하고 그 다음 1.0 요청 뒤에 닫음
+        let received = exchange(
+            "GET /health HTTP/1.1\r\nHost: a\r\n\r\n\
+             GET /health HTTP/1.0\r\nConnection: keep-alive\r\n\r\n\
+             GET /health HTTP/1.0\r\n\r\n\
+             GET /health HTTP/1.1\r\n\r\n",
+        );
+        assert_eq!(received.matches("HTTP/1.1 200 OK").count(), 1);
+        assert_eq!(received.matches("HTTP/1.0 200 OK").count(), 2);
+        assert!(received.find("HTTP/1.1 200 OK") < received.find("HTTP/1.0 200 OK"));
+        assert_eq!(received.matches("Connection:keep-alive").count(), 1);
+        assert!(!received.contains("Connection:close"));
+
+        // Connection: close를 보낸 1.1 요청 뒤에는 닫고 Connection: close로 알림
+        let received = exchange("GET /health HTTP/1.1\r\nConnection: close\r\n\r\nGET /health HTTP/1.1\r\n\r\n");
+        assert_eq!(received.matches("HTTP/1.1 200 OK").count(), 1);
+        assert!(received.contains("Connection:close"));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let received = exchange("GET / HTTP/2.0\r\n\r\n");
+        assert!(received.starts_with("HTTP/1.1 505 HTTP Version Not Supported\r\n"));
+        assert!(received.contains("Connection:close"));
+    }
+
+    #[test]
+    fn test_graceful_shutdown() {
+        use crate::config::ServerConfig;
+        use std::io::{BufRead, BufReader, Read};
+
+        // 비어 있는 포트를 골라서 서버를 시작
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let mut config = ServerConfig::default();
+        config.server.keep_alive_timeout_secs = 2;
+        let handle = ServerHandle::new(AppState::new(config));
+        let server_handle = handle.clone();
+        let server_addr = addr.clone();
+        let server = std::thread::spawn(move || Server::new(&server_addr, server_handle).run());
+
+        let mut stream = loop {
+            match TcpStream::connect(&addr) {
+                Ok(stream) => break stream,
+                Err(_) => std::thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        // 응답 하나의 헤더 부분(바디는 Content-Length만큼 읽어서 버림)
+        let mut read_head = || {
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+                if line == "\r\n" || line.is_empty() {
+                    reader.by_ref().take(length).read_to_end(&mut Vec::new()).unwrap();
+                    return head;
+                }
+            }
+        };
+        stream.write_all(b"GET /health HTTP/1.1\r\n\r\n").unwrap();
+        assert!(read_head().starts_with("HTTP/1.1 200 OK"));
+
+        // 종료를 시작해도 이미 받은 커넥션의 요청은 처리하고, 그 응답 후에 커넥션을 닫음
+        handle.shutdown();
+        stream.write_all(b"GET /health HTTP/1.1\r\n\r\n").unwrap();
+        let head = read_head();
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        assert!(head.contains("Connection:close"));
+
+        server.join().unwrap();
+        assert!(TcpStream::connect(&addr).is_err());
+    }
+
+    #[test]
+    fn test_unix_socket_server() {
+        use crate::config::ServerConfig;
+        use std::io::Read;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("httpserver-uds-server-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server.sock");
+        let addr = format!("unix:{}", path.display());
+        let mut config = ServerConfig::default();
+        config.server.unix_socket_mode = 0o600;
+        let handle = ServerHandle::new(AppState::new(config));
+        let server_handle = handle.clone();
+        let server_addr = addr.clone();
+        let server = std::thread::spawn(move || Server::new(&server_addr, server_handle).run());
+
+        let mut stream = loop {
+            match UnixStream::connect(&path) {
+                Ok(stream) => break stream,
+                Err(_) => std::thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        // TCP와 같은 라우트 테이블로 처리
+        stream.write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+        assert!(received.starts_with("HTTP/1.1 200 OK"));
+
+        // 종료하면 소켓 파일을 지움
+        handle.shutdown();
+        server.join().unwrap();
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_ambiguous_framing_is_rejected() {
+        // Content-Length만 보는 앞단 프록시라면 "0\r\n\r\nGET /health..."를 바디로 넘기는 CL.TE 스머글링 시도
+        let received = exchange(
+            "POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 30\r\nTransfer-Encoding: chunked\r\n\r\n\
+             0\r\n\r\nGET /health HTTP/1.1\r\n\r\n",
+        );
+        assert!(received.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+        assert!(!received.contains("200 OK"));
+    }
+
+    #[test]
+    fn test_requested_upgrade() {
+        let req = |s: &str| -> HttpRequest { s.to_string().into() };
+        assert_eq!(
+            requested_upgrade(&req("GET /ws HTTP/1.1\r\nConnection: keep-alive, Upgrade\r\nUpgrade: WebSocket\r\n\r\n")),
+            Some("websocket".to_string())
+        );
+        assert_eq!(requested_upgrade(&req("GET /ws HTTP/1.1\r\nUpgrade: websocket\r\n\r\n")), None);
+        assert_eq!(
+            requested_upgrade(&req("GET /ws HTTP/1.0\r\nConnection: upgrade\r\nUpgrade: websocket\r\n\r\n")),
+            None
+        );
+    }
+}
@@ -0,0 +1,372 @@
+use super::config::{SessionSection, SessionStoreKind};
+use http::cookie::{SameSite, SetCookie};
+use http::httprequest::HttpRequest;
+use http::httpresponse::HttpResponse;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// 만료된 세션을 저장소에서 한꺼번에 지우는 주기
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+// 세션 ID는 32바이트 난수를 16진수로 쓴 것
+const ID_BYTES: usize = 32;
+
+pub type SessionData = HashMap<String, String>;
+
+// 세션 데이터를 보관하는 저장소, 워커 스레드가 공유하므로 Send + Sync
+pub trait SessionStore: Send + Sync {
+    // 만료되지 않은 세션의 데이터
+    fn load(&self, id: &str) -> Option<SessionData>;
+    // 데이터를 저장하고 만료 시각을 지금부터 ttl 뒤로 미룸
+    fn save(&self, id: &str, data: &SessionData, ttl: Duration);
+    fn remove(&self, id: &str);
+}
+
+// 프로세스 메모리에 보관하는 저장소(재시작하면 세션이 사라짐)
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, (SessionData, Instant)>>,
+    last_sweep: Mutex<Instant>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        MemoryStore::new()
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore {
+            entries: Mutex::new(HashMap::new()),
+            last_sweep: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn load(&self, id: &str) -> Option<SessionData> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(id) {
+            Some((data, expires)) if *expires > Instant::now() => Some(data.clone()),
+            Some(_) => {
+                entries.remove(id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn save(&self, id: &str, data: &SessionData, ttl: Duration) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(id.to_string(), (data.clone(), now + ttl));
+
+        let mut last_sweep = self.last_sweep.lock().unwrap();
+        if now.duration_since(*last_sweep) >= SWEEP_INTERVAL {
+            entries.retain(|_, (_, expires)| *expires > now);
+            *last_sweep = now;
+        }
+    }
+
+    fn remove(&self, id: &str) {
+        self.entries.lock().unwrap().remove(id);
+    }
+}
+
+// 디렉터리에 세션마다 JSON 파일 하나로 보관하는 저장소(재시작해도 유지됨)
+pub struct FileStore {
+    dir: PathBuf,
+    last_sweep: Mutex<Instant>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    expires_at: u64, // 유닉스 시간(초)
+    data: SessionData,
+}
+
+impl FileStore {
+    pub fn new(dir: PathBuf) -> FileStore {
+        FileStore {
+            dir,
+            last_sweep: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn read(&self, path: &PathBuf) -> Option<StoredSession> {
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+    }
+
+    // 만료되었거나 읽을 수 없는 세션 파일을 지움
+    fn sweep(&self) {
+        let now = unix_now();
+        for entry in fs::read_dir(&self.dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let is_session = path.file_name().and_then(|n| n.to_str()).is_some_and(valid_id);
+            if is_session && self.read(&path).is_none_or(|s| s.expires_at <= now) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+impl SessionStore for FileStore {
+    fn load(&self, id: &str) -> Option<SessionData> {
+        let path = self.dir.join(id);
+        let stored = self.read(&path)?;
+        if stored.expires_at <= unix_now() {
+            let _ = fs::remove_file(path);
+            return None;
+        }
+        Some(stored.data)
+    }
+
+    fn save(&self, id: &str, data: &SessionData, ttl: Duration) {
+        let stored = StoredSession {
+            expires_at: unix_now() + ttl.as_secs(),
+            data: data.clone(),
+        };
+        // 다른 워커가 반쯤 쓴 파일을 읽지 않도록 임시 파일에 쓴 뒤 이름을 바꿈
+        let tmp = self.dir.join(format!("{}.tmp", id));
+        let _ = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&tmp, serde_json::to_string(&stored).unwrap()))
+            .and_then(|_| fs::rename(&tmp, self.dir.join(id)));
+
+        let mut last_sweep = self.last_sweep.lock().unwrap();
+        if last_sweep.elapsed() >= SWEEP_INTERVAL {
+            *last_sweep = Instant::now();
+            drop(last_sweep);
+            self.sweep();
+        }
+    }
+
+    fn remove(&self, id: &str) {
+        let _ = fs::remove_file(self.dir.join(id));
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// 쿠키로 받은 ID는 파일 이름으로도 쓰이므로 형식이 맞는 것만 받아들임
+fn valid_id(id: &str) -> bool {
+    id.len() == ID_BYTES * 2 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn new_id() -> String {
+    let mut bytes = [0; ID_BYTES];
+    getrandom::getrandom(&mut bytes).expect("운영체제 난수 생성기를 사용할 수 없습니다");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// 요청 하나를 처리하는 동안 핸들러가 읽고 쓰는 세션
+// 핸들러는 컨텍스트를 공유 참조로 받으므로 내부 가변성을 사용
+#[derive(Debug, Default)]
+pub struct Session {
+    id: Option<String>,       // 요청의 쿠키로 불러온 세션 ID(새 세션이면 None)
+    original: SessionData,    // 불러온 시점의 데이터(권한 변경 감지에 사용)
+    data: RefCell<SessionData>,
+    destroyed: Cell<bool>,
+}
+
+impl Session {
+    pub fn insert(&self, key: impl Into<String>, value: impl Into<String>) {
+        self.data.borrow_mut().insert(key.into(), value.into());
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.data.borrow_mut().remove(key);
+    }
+
+    pub fn values(&self) -> SessionData {
+        self.data.borrow().clone()
+    }
+
+    // 세션을 저장소에서 지우고 브라우저의 세션 쿠키도 만료시킴(로그아웃)
+    pub fn destroy(&self) {
+        self.data.borrow_mut().clear();
+        self.destroyed.set(true);
+    }
+}
+
+// 세션 쿠키로 세션을 불러오고 요청이 끝나면 저장
+pub struct SessionManager {
+    store: Box<dyn SessionStore>,
+    config: SessionSection,
+}
+
+impl SessionManager {
+    pub fn new(config: &SessionSection) -> SessionManager {
+        let store: Box<dyn SessionStore> = match config.store {
+            SessionStoreKind::Memory => Box::new(MemoryStore::new()),
+            SessionStoreKind::File => Box::new(FileStore::new(config.path.clone())),
+        };
+        SessionManager {
+            store,
+            config: config.clone(),
+        }
+    }
+
+    // 쿠키의 세션이 없거나 만료되었으면 빈 세션(응답할 때 데이터가 있으면 새 ID를 발급)
+    pub fn load(&self, req: &HttpRequest) -> Session {
+        let cookies = req.cookies();
+        let id = cookies.get(&self.config.cookie_name).filter(|id| valid_id(id));
+        match id.and_then(|id| Some((id, self.store.load(id)?))) {
+            Some((id, data)) => Session {
+                id: Some(id.to_string()),
+                original: data.clone(),
+                data: RefCell::new(data),
+                destroyed: Cell::new(false),
+            },
+            None => Session::default(),
+        }
+    }
+
+    // 세션을 저장하고 ID가 새로 생기거나 바뀌면 응답에 Set-Cookie를 추가
+    // 권한 키(privileged_keys)의 값이 바뀌면 세션 고정 공격을 막기 위해 ID를 새로 발급
+    pub fn commit(&self, session: &Session, resp: &mut HttpResponse, secure: bool) {
+        let data = session.data.borrow();
+        if session.destroyed.get() || data.is_empty() {
+            if let Some(id) = &session.id {
+                self.store.remove(id);
+                resp.add_cookie(self.cookie(SetCookie::removal(self.config.cookie_name.as_str()), secure));
+            }
+            return;
+        }
+
+        let privilege_changed = self
+            .config
+            .privileged_keys
+            .iter()
+            .any(|key| data.get(key) != session.original.get(key));
+        let id = match &session.id {
+            Some(id) if !privilege_changed => id.clone(),
+            old => {
+                if let Some(old) = old {
+                    self.store.remove(old);
+                }
+                let id = new_id();
+                let cookie = SetCookie::new(self.config.cookie_name.as_str(), id.as_str());
+                resp.add_cookie(self.cookie(cookie, secure));
+                id
+            }
+        };
+        // 세션을 사용한 요청마다 만료 시각을 연장
+        self.store.save(&id, &data, Duration::from_secs(self.config.ttl_secs));
+    }
+
+    // 세션 쿠키는 스크립트에서 읽을 수 없고, HTTPS로 받은 세션은 HTTPS로만 보내도록 함
+    fn cookie(&self, cookie: SetCookie, secure: bool) -> SetCookie {
+        cookie
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .secure(secure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(cookie: Option<&str>) -> HttpRequest {
+        let cookie = cookie.map(|c| format!("Cookie: sid={}\r\n", c)).unwrap_or_default();
+        format!("GET / HTTP/1.1\r\nHost: localhost\r\n{}\r\n", cookie).into()
+    }
+
+    // 응답에 붙은 세션 쿠키의 ID
+    fn issued_id(resp: &HttpResponse) -> Option<String> {
+        let cookie = resp.set_cookies().first()?;
+        let value = cookie.strip_prefix("sid=")?.split(';').next()?;
+        Some(value.to_string())
+    }
+
+    fn response() -> HttpResponse<'static> {
+        HttpResponse::new("200", None, Some(String::new()))
+    }
+
+    fn check_store(store: &dyn SessionStore) {
+        let id = new_id();
+        let data: SessionData = [("cart".to_string(), "3".to_string())].into();
+        store.save(&id, &data, Duration::from_secs(60));
+        assert_eq!(store.load(&id), Some(data.clone()));
+        store.remove(&id);
+        assert_eq!(store.load(&id), None);
+
+        // TTL이 지난 세션은 불러오지 않음
+        store.save(&id, &data, Duration::ZERO);
+        assert_eq!(store.load(&id), None);
+    }
+
+    #[test]
+    fn test_stores_expire_sessions() {
+        check_store(&MemoryStore::new());
+
+        let dir = std::env::temp_dir().join(format!("httpserver-sessions-{}", std::process::id()));
+        check_store(&FileStore::new(dir.clone()));
+
+        // 주기적인 정리에서 만료된 세션 파일을 지움
+        let store = FileStore::new(dir.clone());
+        let (live, expired) = (new_id(), new_id());
+        store.save(&live, &SessionData::new(), Duration::from_secs(60));
+        store.save(&expired, &SessionData::new(), Duration::ZERO);
+        store.sweep();
+        assert!(dir.join(&live).exists());
+        assert!(!dir.join(&expired).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_session_lifecycle_and_rotation() {
+        let manager = SessionManager::new(&SessionSection::default());
+
+        // 데이터가 없는 새 세션은 쿠키를 발급하지 않음
+        let session = manager.load(&request(None));
+        let mut resp = response();
+        manager.commit(&session, &mut resp, false);
+        assert!(resp.set_cookies().is_empty());
+
+        // 데이터를 넣으면 새 ID를 발급
+        session.insert("cart", "3");
+        let mut resp = response();
+        manager.commit(&session, &mut resp, true);
+        let id = issued_id(&resp).unwrap();
+        assert!(resp.set_cookies()[0].contains("Path=/; Secure; HttpOnly; SameSite=Lax"));
+
+        // 같은 ID로 불러와서 권한과 관계없는 값을 바꾸면 ID를 유지
+        let session = manager.load(&request(Some(&id)));
+        assert_eq!(session.values().get("cart").map(String::as_str), Some("3"));
+        session.insert("theme", "dark");
+        let mut resp = response();
+        manager.commit(&session, &mut resp, false);
+        assert!(resp.set_cookies().is_empty());
+
+        // 로그인처럼 권한 키가 바뀌면 새 ID로 옮기고 이전 ID는 더 이상 쓸 수 없음
+        let session = manager.load(&request(Some(&id)));
+        session.insert("user", "alice");
+        let mut resp = response();
+        manager.commit(&session, &mut resp, false);
+        let rotated = issued_id(&resp).unwrap();
+        assert_ne!(rotated, id);
+        assert_eq!(manager.load(&request(Some(&id))).values().get("theme"), None);
+        let values = manager.load(&request(Some(&rotated))).values();
+        assert_eq!(values.get("theme").map(String::as_str), Some("dark"));
+
+        // 세션을 없애면 저장소에서 지우고 쿠키를 만료시킴
+        let session = manager.load(&request(Some(&rotated)));
+        session.destroy();
+        let mut resp = response();
+        manager.commit(&session, &mut resp, false);
+        assert!(resp.set_cookies()[0].starts_with("sid=; Path=/; Expires=Thu, 01 Jan 1970"));
+        assert_eq!(manager.load(&request(Some(&rotated))).values().get("user"), None);
+
+        // 형식이 맞지 않는 ID는 저장소에서 찾지 않음
+        assert!(manager.load(&request(Some("../../etc/passwd"))).values().is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+use super::router::Reply;
+use http::httprequest::HttpRequest;
+use http::httpresponse::HttpResponse;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 이벤트 소스에 새 이벤트가 있는지 확인하는 주기
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// 이 시간 동안 보낸 것이 없으면 주석 행을 보내서 중간 프록시가 커넥션을 끊지 않게 하고 끊긴 클라이언트를 찾아냄
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+// 커넥션이 끊겼을 때 클라이언트가 재연결하기 전에 기다릴 시간
+const RETRY: Duration = Duration::from_secs(3);
+
+// text/event-stream으로 보내는 이벤트 하나
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Event {
+    pub event: Option<String>, // 이벤트 이름(없으면 클라이언트에서 message 이벤트)
+    pub id: Option<String>,    // 재연결할 때 Last-Event-ID로 돌아오는 값
+    pub data: String,
+}
+
+impl Event {
+    // 필드 형식으로 인코딩(여러 행의 데이터는 data 필드 여러 개로 나눔)
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        if let Some(id) = &self.id {
+            out.push_str(&format!("id: {}\n", single_line(id)));
+        }
+        if let Some(event) = &self.event {
+            out.push_str(&format!("event: {}\n", single_line(event)));
+        }
+        for line in self.data.split("\r\n").flat_map(|l| l.split(['\r', '\n'])) {
+            out.push_str(&format!("data: {}\n", line));
+        }
+        out.push('\n');
+        out
+    }
+}
+
+// id와 event 필드에는 행 구분 문자와 NUL을 넣을 수 없음
+fn single_line(value: &str) -> String {
+    value.chars().filter(|c| !matches!(c, '\r' | '\n' | '\0')).collect()
+}
+
+// 이벤트 스트림에 보낼 이벤트를 만드는 쪽에서 구현
+pub trait EventSource {
+    // 연결(또는 재연결)했을 때 처음 보낼 이벤트
+    // last_event_id는 재연결한 클라이언트가 마지막으로 받은 이벤트의 ID
+    fn open(&mut self, last_event_id: Option<&str>) -> Vec<Event>;
+
+    // 주기적으로 호출되어 그사이에 생긴 이벤트를 리턴
+    fn poll(&mut self) -> Vec<Event>;
+}
+
+// 이벤트 소스를 응답 바디로 읽는 스트림
+// 끝나지 않는 바디이므로 클라이언트가 끊어서 쓰기가 실패할 때까지 워커 스레드가 전송을 계속함
+pub struct EventStream<'a> {
+    source: Box<dyn EventSource + 'a>,
+    last_event_id: Option<String>,
+    opened: bool,
+    buffer: Vec<u8>,
+    last_sent: Instant,
+    poll_interval: Duration,
+    heartbeat_interval: Duration,
+}
+
+impl<'a> EventStream<'a> {
+    pub fn new(source: Box<dyn EventSource + 'a>, last_event_id: Option<String>) -> EventStream<'a> {
+        EventStream {
+            source,
+            last_event_id,
+            opened: false,
+            buffer: Vec::new(),
+            last_sent: Instant::now(),
+            poll_interval: POLL_INTERVAL,
+            heartbeat_interval: HEARTBEAT_INTERVAL,
+        }
+    }
+
+    fn push(&mut self, events: Vec<Event>) {
+        for event in events {
+            self.buffer.extend_from_slice(event.encode().as_bytes());
+        }
+    }
+}
+
+impl Read for EventStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // 보낼 것이 생길 때까지 이벤트 소스를 폴링
+        while self.buffer.is_empty() {
+            if !self.opened {
+                self.opened = true;
+                self.buffer
+                    .extend_from_slice(format!("retry: {}\n\n", RETRY.as_millis()).as_bytes());
+                let events = self.source.open(self.last_event_id.as_deref());
+                self.push(events);
+                continue;
+            }
+            thread::sleep(self.poll_interval);
+            let events = self.source.poll();
+            self.push(events);
+            if self.buffer.is_empty() && self.last_sent.elapsed() >= self.heartbeat_interval {
+                self.buffer.extend_from_slice(b": heartbeat\n\n");
+            }
+        }
+
+        let n = buf.len().min(self.buffer.len());
+        buf[..n].copy_from_slice(&self.buffer[..n]);
+        self.buffer.drain(..n);
+        self.last_sent = Instant::now();
+        Ok(n)
+    }
+}
+
+// 클라이언트가 이벤트 스트림을 요청했는지(EventSource는 Accept: text/event-stream을 보냄)
+pub fn accepts_event_stream(req: &HttpRequest) -> bool {
+    req.headers
+        .iter()
+        .filter(|(key, _)| key.trim().eq_ignore_ascii_case("Accept"))
+        .flat_map(|(_, value)| value.split(','))
+        .any(|media| {
+            let media = media.split(';').next().unwrap_or("").trim();
+            media.eq_ignore_ascii_case("text/event-stream")
+        })
+}
+
+// 이벤트 소스를 text/event-stream 응답으로 보냄
+// 길이를 알 수 없는 바디이므로 서버는 응답 뒤에 커넥션을 닫음
+pub fn stream<'a>(req: &HttpRequest, source: Box<dyn EventSource + 'a>) -> Reply<'a> {
+    let last_event_id = req
+        .headers
+        .iter()
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("Last-Event-ID"))
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    let mut headers: HashMap<&str, &str> = HashMap::new();
+    headers.insert("Content-Type", "text/event-stream");
+    headers.insert("Cache-Control", "no-cache");
+    // nginx 같은 리버스 프록시가 이벤트를 버퍼링하지 않도록 함
+    headers.insert("X-Accel-Buffering", "no");
+    Reply {
+        response: HttpResponse::new("200", Some(headers), None),
+        body_stream: Some(Box::new(EventStream::new(source, last_event_id))),
+        upgrade: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_encode_event_fields() {
+        let event = Event {
+            event: Some("orders".to_string()),
+            id: Some("4\n2".to_string()),
+            data: "first\r\nsecond\nthird".to_string(),
+        };
+        assert_eq!(
+            event.encode(),
+            "id: 42\nevent: orders\ndata: first\ndata: second\ndata: third\n\n"
+        );
+        assert_eq!(Event::default().encode(), "data: \n\n");
+    }
+
+    #[test]
+    fn test_accepts_event_stream() {
+        let req: HttpRequest =
+            "GET / HTTP/1.1\r\nAccept: text/html, text/event-stream;q=0.9\r\n\r\n".to_string().into();
+        assert!(accepts_event_stream(&req));
+        let req: HttpRequest = "GET / HTTP/1.1\r\nAccept: */*\r\n\r\n".to_string().into();
+        assert!(!accepts_event_stream(&req));
+    }
+
+    // 연결할 때 받은 Last-Event-ID를 기록하고, 폴링할 때마다 미리 넣어 둔 이벤트를 하나씩 내보냄
+    struct Scripted {
+        resumed_from: Arc<Mutex<Option<String>>>,
+        pending: Vec<Vec<Event>>,
+    }
+
+    impl EventSource for Scripted {
+        fn open(&mut self, last_event_id: Option<&str>) -> Vec<Event> {
+            *self.resumed_from.lock().unwrap() = last_event_id.map(str::to_string);
+            vec![Event {
+                data: "hello".to_string(),
+                ..Default::default()
+            }]
+        }
+
+        fn poll(&mut self) -> Vec<Event> {
+            if self.pending.is_empty() {
+                Vec::new()
+            } else {
+                self.pending.remove(0)
+            }
+        }
+    }
+
+    fn scripted(pending: Vec<Vec<Event>>) -> (Scripted, Arc<Mutex<Option<String>>>) {
+        let resumed_from = Arc::new(Mutex::new(None));
+        let source = Scripted {
+            resumed_from: Arc::clone(&resumed_from),
+            pending,
+        };
+        (source, resumed_from)
+    }
+
+    #[test]
+    fn test_stream_passes_last_event_id() {
+        let (source, resumed_from) = scripted(Vec::new());
+        let req: HttpRequest =
+            "GET /feed HTTP/1.1\r\nLast-Event-ID:  6 \r\n\r\n".to_string().into();
+        let reply = stream(&req, Box::new(source));
+        assert_eq!(reply.response.header("Content-Type"), Some("text/event-stream"));
+        assert_eq!(reply.response.header("Cache-Control"), Some("no-cache"));
+
+        // retry 필드와 연결할 때의 이벤트는 폴링을 기다리지 않고 바로 나옴
+        let expected = "retry: 3000\n\ndata: hello\n\n";
+        let mut buf = vec![0; expected.len()];
+        reply.body_stream.unwrap().read_exact(&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+        assert_eq!(resumed_from.lock().unwrap().as_deref(), Some("6"));
+    }
+
+    #[test]
+    fn test_stream_polls_and_sends_heartbeats() {
+        let update = Event {
+            id: Some("7".to_string()),
+            data: "update".to_string(),
+            ..Default::default()
+        };
+        let (source, resumed_from) = scripted(vec![vec![update], Vec::new()]);
+        let mut body = EventStream::new(Box::new(source), None);
+        // 테스트에서는 폴링과 하트비트 주기를 줄임
+        body.poll_interval = Duration::from_millis(1);
+        body.heartbeat_interval = Duration::from_millis(20);
+
+        let expected = "retry: 3000\n\ndata: hello\n\nid: 7\ndata: update\n\n: heartbeat\n\n";
+        let mut buf = vec![0; expected.len()];
+        body.read_exact(&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+        assert_eq!(*resumed_from.lock().unwrap(), None);
+    }
+}
@@ -0,0 +1,89 @@
+use super::auth::Authenticator;
+use super::config::ServerConfig;
+use super::metrics::Metrics;
+use super::proxy::UpstreamPool;
+use super::ratelimit::RateLimiter;
+use super::session::{Session, SessionManager};
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// 모든 워커 스레드가 공유하는 애플리케이션 상태
+pub struct AppState {
+    pub config: ServerConfig, // 공유된 이뮤터블 상태
+    pub upstreams: HashMap<String, UpstreamPool>, // 업스트림별 라운드 로빈 위치와 헬스 상태
+    pub sessions: Arc<SessionManager>,
+    pub authenticators: HashMap<String, Authenticator>, // [auth.<이름>]별 htpasswd 사용자와 토큰
+    pub rate_limiters: HashMap<String, RateLimiter>, // [rate_limits.<이름>]별 클라이언트 토큰 버킷
+    pub metrics: Arc<Metrics>, // /metrics로 내보내는 요청 수, 응답 시간, 커넥션 수
+    // 종료 중이면 리스너가 새 커넥션을 받지 않고 keep-alive 커넥션은 다음 응답 후 닫음
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl AppState {
+    pub fn new(config: ServerConfig) -> AppState {
+        let upstreams = config
+            .upstreams
+            .iter()
+            .map(|(name, section)| (name.clone(), UpstreamPool::new(section)))
+            .collect();
+        let sessions = Arc::new(SessionManager::new(&config.session));
+        let authenticators = config
+            .auth
+            .iter()
+            .map(|(name, section)| (name.clone(), Authenticator::new(section)))
+            .collect();
+        let rate_limiters = config
+            .rate_limits
+            .iter()
+            .map(|(name, section)| (name.clone(), RateLimiter::new(section)))
+            .collect();
+        AppState {
+            config,
+            upstreams,
+            sessions,
+            authenticators,
+            rate_limiters,
+            metrics: Arc::new(Metrics::new()),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // 다시 로드한 설정으로 새 상태를 만듦(SIGHUP)
+    // 지표와 종료 상태는 이어가고, 세션 설정이 같으면 로그인한 세션도 유지
+    pub fn reload(&self, config: ServerConfig) -> AppState {
+        let mut state = AppState::new(config);
+        state.metrics = Arc::clone(&self.metrics);
+        state.shutting_down = Arc::clone(&self.shutting_down);
+        if state.config.session == self.config.session {
+            state.sessions = Arc::clone(&self.sessions);
+        }
+        state
+    }
+
+    pub fn shut_down(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+}
+
+// 요청 하나를 처리하는 동안 핸들러가 참조하는 컨텍스트
+pub struct RequestContext<'a> {
+    pub state: &'a AppState,
+    pub peer: Option<SocketAddr>,
+    pub secure: bool, // HTTPS 리스너로 들어온 요청인지 여부
+    pub body: &'a [u8], // 요청 바디의 원본 바이트(msg_body는 UTF-8로 바꾼 값이라 바이너리가 손상됨)
+    pub session: &'a Session, // 응답을 보내기 전에 서버가 저장하고 필요하면 세션 쿠키를 발급
+}
+
+// 서버가 읽어 두지 않고 커넥션에서 바로 읽도록 넘긴 요청 바디(프록시 라우트의 Content-Length 바디)
+// 응답이 커넥션을 다시 쓰므로 RequestContext와 달리 라우팅하는 동안에만 빌림
+pub struct StreamedBody<'r> {
+    pub reader: &'r mut dyn Read,
+    pub length: usize,
+}
@@ -0,0 +1,302 @@
+use serde_json::Value;
+use std::fmt;
+
+// 인클루드가 자기 자신을 다시 인클루드하는 경우를 막기 위한 최대 깊이
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+// 퍼블릭 디렉터리의 HTML 파일에 쓰는 간단한 템플릿 문법
+//   {{ path }}             값을 HTML 이스케이프해서 출력(order.order_id처럼 점으로 필드, orders.0처럼 인덱스 접근)
+//   {{ path | raw }}       이스케이프하지 않고 출력
+//   {% include "a.html" %} 다른 템플릿을 같은 데이터로 렌더링해서 삽입
+//   {% for x in path %} ... {% endfor %}  배열의 항목마다 반복(loop.index는 1부터 시작하는 순번)
+//   {% if path %} ... {% else %} ... {% endif %}  null, false, 0, 빈 문자열/배열/객체가 아니면 참
+// 없는 값은 빈 문자열로 출력
+#[derive(Debug, PartialEq)]
+pub enum TemplateError {
+    Syntax(String),
+    IncludeNotFound(String),
+    IncludeTooDeep(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::Syntax(reason) => write!(f, "템플릿 문법 오류: {}", reason),
+            TemplateError::IncludeNotFound(name) => write!(f, "인클루드할 템플릿 {}이(가) 없습니다", name),
+            TemplateError::IncludeTooDeep(name) => {
+                write!(f, "{} 인클루드가 {}단계를 넘습니다", name, MAX_INCLUDE_DEPTH)
+            }
+        }
+    }
+}
+
+enum Node {
+    Text(String),
+    Value { path: String, raw: bool },
+    Include(String),
+    For { name: String, path: String, body: Vec<Node> },
+    If { path: String, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+enum Token<'t> {
+    Text(&'t str),
+    Output(&'t str),
+    Tag(&'t str),
+}
+
+// source를 context의 값으로 렌더링하고, 인클루드는 load로 템플릿 원본을 가져옴
+pub fn render(source: &str, context: &Value, load: &dyn Fn(&str) -> Option<String>) -> Result<String, TemplateError> {
+    let renderer = Renderer { context, load };
+    let mut out = String::new();
+    renderer.render(source, &mut Vec::new(), 0, &mut out)?;
+    Ok(out)
+}
+
+struct Renderer<'r> {
+    context: &'r Value,
+    load: &'r dyn Fn(&str) -> Option<String>,
+}
+
+impl Renderer<'_> {
+    fn render(&self, source: &str, scopes: &mut Vec<(String, Value)>, depth: usize, out: &mut String) -> Result<(), TemplateError> {
+        let tokens = tokenize(source)?;
+        let mut tokens = tokens.into_iter();
+        let (nodes, _) = parse(&mut tokens, &[])?;
+        self.render_nodes(&nodes, scopes, depth, out)
+    }
+
+    fn render_nodes(&self, nodes: &[Node], scopes: &mut Vec<(String, Value)>, depth: usize, out: &mut String) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Value { path, raw: true } => out.push_str(&display(&self.lookup(scopes, path))),
+                Node::Value { path, raw: false } => out.push_str(&escape(&display(&self.lookup(scopes, path)))),
+                Node::Include(name) => {
+                    if depth >= MAX_INCLUDE_DEPTH {
+                        return Err(TemplateError::IncludeTooDeep(name.clone()));
+                    }
+                    let source = (self.load)(name).ok_or_else(|| TemplateError::IncludeNotFound(name.clone()))?;
+                    self.render(&source, scopes, depth + 1, out)?;
+                }
+                Node::For { name, path, body } => {
+                    let items = match self.lookup(scopes, path) {
+                        Value::Array(items) => items,
+                        _ => Vec::new(),
+                    };
+                    for (i, item) in items.into_iter().enumerate() {
+                        scopes.push(("loop".to_string(), serde_json::json!({ "index": i + 1 })));
+                        scopes.push((name.clone(), item));
+                        let result = self.render_nodes(body, scopes, depth, out);
+                        scopes.truncate(scopes.len() - 2);
+                        result?;
+                    }
+                }
+                Node::If { path, then, otherwise } => {
+                    let branch = if is_truthy(&self.lookup(scopes, path)) { then } else { otherwise };
+                    self.render_nodes(branch, scopes, depth, out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 반복 변수(안쪽 루프 우선)를 먼저 찾고, 없으면 전체 데이터에서 찾음
+    fn lookup(&self, scopes: &[(String, Value)], path: &str) -> Value {
+        let mut parts = path.split('.');
+        let first = parts.next().unwrap_or("");
+        let mut value = match scopes.iter().rev().find(|(name, _)| name == first) {
+            Some((_, value)) => value,
+            None => &self.context[first],
+        };
+        for part in parts {
+            value = match (value, part.parse::<usize>()) {
+                (Value::Array(items), Ok(i)) => items.get(i).unwrap_or(&Value::Null),
+                _ => &value[part],
+            };
+        }
+        value.clone()
+    }
+}
+
+// {{ ... }}와 {% ... %}를 기준으로 나눔
+fn tokenize(source: &str) -> Result<Vec<Token<'_>>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    loop {
+        let start = match (rest.find("{{"), rest.find("{%")) {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(a), None) | (None, Some(a)) => a,
+            (None, None) => break,
+        };
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        let is_output = rest[start..].starts_with("{{");
+        let close = if is_output { "}}" } else { "%}" };
+        let inner_start = start + 2;
+        let Some(len) = rest[inner_start..].find(close) else {
+            return Err(TemplateError::Syntax(format!("{}이(가) 닫히지 않았습니다", &rest[start..inner_start])));
+        };
+        let inner = rest[inner_start..inner_start + len].trim();
+        tokens.push(if is_output { Token::Output(inner) } else { Token::Tag(inner) });
+        rest = &rest[inner_start + len + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Ok(tokens)
+}
+
+// ends 중 하나의 태그를 만날 때까지 파싱하고, 만난 태그를 함께 리턴(끝까지 읽으면 None)
+fn parse<'t>(
+    tokens: &mut impl Iterator<Item = Token<'t>>,
+    ends: &[&str],
+) -> Result<(Vec<Node>, Option<&'t str>), TemplateError> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.to_string())),
+            Token::Output(expr) => {
+                let (path, raw) = match expr.split_once('|') {
+                    Some((path, filter)) if filter.trim() == "raw" => (path.trim(), true),
+                    Some(_) => return Err(TemplateError::Syntax(format!("알 수 없는 필터: {{{{ {} }}}}", expr))),
+                    None => (expr, false),
+                };
+                nodes.push(Node::Value { path: path.to_string(), raw });
+            }
+            Token::Tag(tag) => {
+                let words: Vec<&str> = tag.split_whitespace().collect();
+                match words.as_slice() {
+                    [end] if ends.contains(end) => return Ok((nodes, Some(end))),
+                    ["include", name] if name.len() >= 2 && name.starts_with('"') && name.ends_with('"') => {
+                        nodes.push(Node::Include(name[1..name.len() - 1].to_string()));
+                    }
+                    ["for", name, "in", path] => {
+                        let (body, end) = parse(tokens, &["endfor"])?;
+                        if end.is_none() {
+                            return Err(TemplateError::Syntax(format!("{{% {} %}}에 endfor가 없습니다", tag)));
+                        }
+                        nodes.push(Node::For {
+                            name: name.to_string(),
+                            path: path.to_string(),
+                            body,
+                        });
+                    }
+                    ["if", path] => {
+                        let (then, end) = parse(tokens, &["else", "endif"])?;
+                        let otherwise = match end {
+                            Some("else") => match parse(tokens, &["endif"])? {
+                                (otherwise, Some(_)) => otherwise,
+                                (_, None) => return Err(TemplateError::Syntax(format!("{{% {} %}}에 endif가 없습니다", tag))),
+                            },
+                            Some(_) => Vec::new(),
+                            None => return Err(TemplateError::Syntax(format!("{{% {} %}}에 endif가 없습니다", tag))),
+                        };
+                        nodes.push(Node::If {
+                            path: path.to_string(),
+                            then,
+                            otherwise,
+                        });
+                    }
+                    _ => return Err(TemplateError::Syntax(format!("알 수 없는 태그: {{% {} %}}", tag))),
+                }
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn no_includes(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_render_values_loops_and_conditions() {
+        let context = json!({
+            "path": "/<script>",
+            "orders": [{ "id": 1, "status": "Delivered" }, { "id": 2, "status": "Pending" }],
+            "empty": [],
+        });
+        let render = |source: &str| render(source, &context, &no_includes).unwrap();
+
+        // 값은 이스케이프하고, raw 필터나 템플릿 원문은 그대로 출력
+        assert_eq!(render("<p>{{ path }}</p>"), "<p>/&lt;script&gt;</p>");
+        assert_eq!(render("{{path|raw}}"), "/<script>");
+        assert_eq!(render("{{ orders.1.status }}/{{ missing }}/{{ orders.0.id }}"), "Pending//1");
+        assert_eq!(
+            render("{% for o in orders %}{{ loop.index }}:{{ o.status }} {% endfor %}"),
+            "1:Delivered 2:Pending "
+        );
+        assert_eq!(render("{% if empty %}yes{% else %}none{% endif %}"), "none");
+        assert_eq!(render("{% if orders %}{{ orders.0.id }}{% endif %}"), "1");
+
+        // 바깥 루프의 변수도 안쪽에서 쓸 수 있음
+        let nested = "{% for o in orders %}{% for p in orders %}{{ o.id }}{{ p.id }},{% endfor %}{% endfor %}";
+        assert_eq!(render(nested), "11,12,21,22,");
+    }
+
+    #[test]
+    fn test_includes_and_errors() {
+        let load = |name: &str| match name {
+            "header.html" => Some("<h1>{{ title }}</h1>".to_string()),
+            "self.html" => Some("{% include \"self.html\" %}".to_string()),
+            _ => None,
+        };
+        let context = json!({ "title": "Orders" });
+        assert_eq!(
+            render("{% include \"header.html\" %}<p></p>", &context, &load).unwrap(),
+            "<h1>Orders</h1><p></p>"
+        );
+        assert_eq!(
+            render("{% include \"self.html\" %}", &context, &load),
+            Err(TemplateError::IncludeTooDeep("self.html".to_string()))
+        );
+        assert_eq!(
+            render("{% include \"footer.html\" %}", &context, &load),
+            Err(TemplateError::IncludeNotFound("footer.html".to_string()))
+        );
+
+        for source in ["{{ title", "{% for o in orders %}", "{% if title %}", "{% endif %}", "{% while %}", "{{ title | upper }}"] {
+            assert!(matches!(render(source, &context, &load), Err(TemplateError::Syntax(_))), "{}", source);
+        }
+    }
+}
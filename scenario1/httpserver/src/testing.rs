@@ -0,0 +1,157 @@
+use super::config::ServerConfig;
+use super::lifecycle::ServerHandle;
+use super::server::Server;
+use super::state::AppState;
+use http::client::Client;
+use http::httprequest::{HttpRequest, Method, Resource, Version};
+use http::httpresponse::HttpResponse;
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+// 임시 포트(127.0.0.1:0)에서 실제 서버를 실행하는 테스트용 서버
+// 라우트는 주어진 설정(또는 상태)의 라우트 테이블을 쓰고, 드롭하면 서버를 종료하고 스레드가 끝나기를 기다림
+pub struct TestServer {
+    addr: SocketAddr,
+    handle: ServerHandle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    pub fn start(config: ServerConfig) -> TestServer {
+        TestServer::with_state(AppState::new(config))
+    }
+
+    pub fn with_state(state: AppState) -> TestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = ServerHandle::new(state);
+        let server_handle = handle.clone();
+        let thread = thread::spawn(move || {
+            let socket_addr = addr.to_string();
+            Server::new(&socket_addr, server_handle).run_on(listener.into());
+        });
+        TestServer {
+            addr,
+            handle,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // 서버가 쓰는 상태(지표, 세션 저장소 확인용)
+    pub fn state(&self) -> Arc<AppState> {
+        self.handle.state()
+    }
+
+    pub fn client(&self) -> TestClient {
+        TestClient {
+            addr: self.addr,
+            client: Client::new().with_max_redirects(0),
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.handle.shutdown();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// TestServer로 요청을 보내는 클라이언트
+// 리다이렉트는 따라가지 않고, 요청마다 커넥션을 닫아서 서버가 바로 종료할 수 있게 함
+// 테스트용이므로 전송에 실패하면 패닉
+pub struct TestClient {
+    addr: SocketAddr,
+    client: Client,
+}
+
+impl TestClient {
+    pub fn get(&self, path: &str) -> HttpResponse<'static> {
+        self.send(self.request(Method::Get, path, &[], ""))
+    }
+
+    pub fn post(&self, path: &str, content_type: &str, body: &str) -> HttpResponse<'static> {
+        self.send(self.request(Method::Post, path, &[("Content-Type", content_type)], body))
+    }
+
+    // 경로와 헤더를 지정한 요청(Host 헤더는 서버 주소로 채움)
+    pub fn request(&self, method: Method, path: &str, headers: &[(&str, &str)], body: &str) -> HttpRequest {
+        let mut map = HashMap::new();
+        map.insert("Host".to_string(), self.addr.to_string());
+        for (key, value) in headers {
+            map.insert(key.to_string(), value.to_string());
+        }
+        HttpRequest {
+            method,
+            version: Version::V1_1,
+            resource: Resource::Path(path.to_string()),
+            headers: map,
+            msg_body: body.to_string(),
+        }
+    }
+
+    pub fn send(&self, mut req: HttpRequest) -> HttpResponse<'static> {
+        req.headers.insert("Connection".to_string(), "close".to_string());
+        match self.client.send(req) {
+            Ok(resp) => resp,
+            Err(e) => panic!("{} 요청 실패: {:?}", self.addr, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CorsSection;
+    use crate::router::Router;
+
+    #[test]
+    fn test_server_and_client() {
+        let mut config = ServerConfig::default();
+        config.cors = Some(CorsSection {
+            allowed_origins: vec!["https://shop.example".to_string()],
+            ..CorsSection::default()
+        });
+        let server = TestServer::start(config);
+        let client = server.client();
+
+        let resp = client.get("/health");
+        assert_eq!(resp.status_code(), "200");
+        assert_eq!(resp.header("Connection"), Some("close"));
+        assert_eq!(client.get("/missing.html").status_code(), "404");
+
+        // 커넥션 단위로 처리하는 CORS 헤더도 실제 서버와 같이 붙음
+        let req = client.request(Method::Get, "/", &[("Origin", "https://shop.example")], "");
+        assert_eq!(client.send(req).header("Access-Control-Allow-Origin"), Some("https://shop.example"));
+
+        // 드롭하면 처리 중인 커넥션이 끝나기를 기다린 뒤 종료되어 더 이상 커넥션을 받지 않음
+        let addr = server.addr();
+        let state = server.state();
+        drop(server);
+        assert!(std::net::TcpStream::connect(addr).is_err());
+        let metrics = state.metrics.render();
+        assert!(metrics.contains("httpserver_requests_total{route=\"/\",status=\"200\"} 2\n"));
+    }
+
+    #[test]
+    fn test_dispatch_matches_server() {
+        // 같은 요청은 소켓을 거치지 않아도 같은 응답
+        let state = AppState::new(ServerConfig::default());
+        let server = TestServer::start(ServerConfig::default());
+        let client = server.client();
+        for path in ["/", "/health", "/missing.html"] {
+            let resp = Router::dispatch(&state, client.request(Method::Get, path, &[], ""));
+            let expected = client.get(path);
+            assert_eq!(resp.status_code(), expected.status_code(), "{}", path);
+            assert_eq!(resp.body(), expected.body(), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use super::config::TlsSection;
+use rustls::crypto::ring;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum TlsError {
+    Pem { path: PathBuf, message: String },
+    NoCertificate(PathBuf),
+    Rustls(rustls::Error),
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::Pem { path, message } => {
+                write!(f, "PEM 파일 {}을(를) 읽을 수 없습니다: {}", path.display(), message)
+            }
+            TlsError::NoCertificate(path) => {
+                write!(f, "{}에 인증서가 없습니다", path.display())
+            }
+            TlsError::Rustls(e) => write!(f, "TLS 설정 실패: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+impl From<rustls::Error> for TlsError {
+    fn from(e: rustls::Error) -> Self {
+        TlsError::Rustls(e)
+    }
+}
+
+// 설정된 인증서들로 rustls 서버 설정을 생성
+pub fn load_tls_config(tls: &TlsSection) -> Result<Arc<rustls::ServerConfig>, TlsError> {
+    let mut resolver = SniResolver::default();
+    for cert in &tls.certificates {
+        let key = Arc::new(load_certified_key(&cert.cert_path, &cert.key_path)?);
+        resolver.add(&cert.hostnames, key);
+    }
+
+    let provider = Arc::new(ring::default_provider());
+    let mut config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_cert_resolver(Arc::new(resolver));
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<CertifiedKey, TlsError> {
+    let pem_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |e: rustls::pki_types::pem::Error| TlsError::Pem {
+            path,
+            message: e.to_string(),
+        }
+    };
+
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .map_err(pem_error(cert_path))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(pem_error(cert_path))?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificate(cert_path.to_path_buf()));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(pem_error(key_path))?;
+    let signing_key = ring::sign::any_supported_type(&key)?;
+    Ok(CertifiedKey::new(certs, signing_key))
+}
+
+// ClientHello의 SNI 호스트 이름으로 인증서를 선택
+// 정확히 일치하는 이름 -> 와일드카드(*.example.com) -> 기본 인증서 순서로 찾음
+#[derive(Debug, Default)]
+struct SniResolver {
+    by_name: HashMap<String, Arc<CertifiedKey>>,
+    default: Option<Arc<CertifiedKey>>,
+}
+
+impl SniResolver {
+    fn add(&mut self, hostnames: &[String], key: Arc<CertifiedKey>) {
+        if self.default.is_none() {
+            self.default = Some(Arc::clone(&key));
+        }
+        for name in hostnames {
+            self.by_name
+                .insert(name.to_ascii_lowercase(), Arc::clone(&key));
+        }
+    }
+
+    fn lookup(&self, server_name: Option<&str>) -> Option<Arc<CertifiedKey>> {
+        if let Some(name) = server_name.map(|n| n.to_ascii_lowercase()) {
+            if let Some(key) = self.by_name.get(&name) {
+                return Some(Arc::clone(key));
+            }
+            if let Some((_, parent)) = name.split_once('.') {
+                if let Some(key) = self.by_name.get(&format!("*.{}", parent)) {
+                    return Some(Arc::clone(key));
+                }
+            }
+        }
+        self.default.clone()
+    }
+}
+
+impl ResolvesServerCert for SniResolver {
+    fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        self.lookup(client_hello.server_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CertificateSection;
+    use rustls::pki_types::ServerName;
+    use std::fs;
+
+    // rcgen으로 자체 서명 인증서를 만들어 임시 디렉터리에 저장
+    fn self_signed(dir: &Path, name: &str) -> CertificateSection {
+        let cert = rcgen::generate_simple_self_signed(vec![name.to_string()]).unwrap();
+        let cert_path = dir.join(format!("{}.pem", name));
+        let key_path = dir.join(format!("{}-key.pem", name));
+        fs::write(&cert_path, cert.cert.pem()).unwrap();
+        fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+        CertificateSection {
+            hostnames: vec![name.to_string()],
+            cert_path,
+            key_path,
+        }
+    }
+
+    // 소켓 없이 메모리 안에서 핸드셰이크를 진행하고 서버가 보낸 인증서를 리턴
+    fn handshake(server_config: Arc<rustls::ServerConfig>, name: &str, root: &Path) -> Vec<u8> {
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(root).unwrap() {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let client_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+            ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+        let server_name = ServerName::try_from(name.to_string()).unwrap();
+        let mut client =
+            rustls::ClientConnection::new(Arc::new(client_config), server_name).unwrap();
+        let mut server = rustls::ServerConnection::new(server_config).unwrap();
+
+        while client.is_handshaking() || server.is_handshaking() {
+            let mut buf = Vec::new();
+            client.write_tls(&mut buf).unwrap();
+            server.read_tls(&mut buf.as_slice()).unwrap();
+            server.process_new_packets().unwrap();
+
+            let mut buf = Vec::new();
+            server.write_tls(&mut buf).unwrap();
+            client.read_tls(&mut buf.as_slice()).unwrap();
+            client.process_new_packets().unwrap();
+        }
+        client.peer_certificates().unwrap()[0].to_vec()
+    }
+
+    #[test]
+    fn test_sni_selects_certificate_per_hostname() {
+        let dir = std::env::temp_dir().join(format!("httpserver-tls-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let first = self_signed(&dir, "first.localhost");
+        let second = self_signed(&dir, "second.localhost");
+
+        let tls = TlsSection {
+            bind: vec!["localhost:3443".to_string()],
+            certificates: vec![first.clone(), second.clone()],
+            redirect_http: false,
+            hsts: None,
+        };
+        let server_config = load_tls_config(&tls).unwrap();
+
+        let expected = |section: &CertificateSection| {
+            CertificateDer::pem_file_iter(&section.cert_path)
+                .unwrap()
+                .next()
+                .unwrap()
+                .unwrap()
+                .to_vec()
+        };
+        let presented = handshake(server_config.clone(), "second.localhost", &second.cert_path);
+        assert_eq!(presented, expected(&second));
+        let presented = handshake(server_config, "first.localhost", &first.cert_path);
+        assert_eq!(presented, expected(&first));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolver_falls_back_to_wildcard_and_default() {
+        let dir = std::env::temp_dir().join(format!("httpserver-sni-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let default = self_signed(&dir, "default.localhost");
+        let wildcard = self_signed(&dir, "wildcard.localhost");
+
+        let mut resolver = SniResolver::default();
+        let default_key = Arc::new(load_certified_key(&default.cert_path, &default.key_path).unwrap());
+        let wildcard_key =
+            Arc::new(load_certified_key(&wildcard.cert_path, &wildcard.key_path).unwrap());
+        resolver.add(&default.hostnames, Arc::clone(&default_key));
+        resolver.add(&["*.example.com".to_string()], Arc::clone(&wildcard_key));
+
+        assert!(Arc::ptr_eq(&resolver.lookup(Some("API.example.com")).unwrap(), &wildcard_key));
+        assert!(Arc::ptr_eq(&resolver.lookup(Some("unknown.test")).unwrap(), &default_key));
+        assert!(Arc::ptr_eq(&resolver.lookup(None).unwrap(), &default_key));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_certificate_file() {
+        let tls = TlsSection {
+            bind: vec!["localhost:3443".to_string()],
+            certificates: vec![CertificateSection {
+                hostnames: vec![],
+                cert_path: PathBuf::from("/nonexistent/cert.pem"),
+                key_path: PathBuf::from("/nonexistent/key.pem"),
+            }],
+            redirect_http: false,
+            hsts: None,
+        };
+        assert!(matches!(load_tls_config(&tls), Err(TlsError::Pem { .. })));
+    }
+}
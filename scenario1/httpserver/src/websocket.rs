@@ -0,0 +1,656 @@
+use super::reader::TimeoutStream;
+use super::router::Reply;
+use super::server::requested_upgrade;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use http::httprequest::{HttpRequest, Method};
+use http::httpresponse::HttpResponse;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::io;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Sec-WebSocket-Accept 계산에 쓰는 RFC 6455의 고정 GUID
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// 보낼 메시지를 확인하는 주기(읽기 타임아웃)와 on_idle 호출 주기
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const IDLE_INTERVAL: Duration = Duration::from_secs(1);
+
+// 보내는 메시지를 이 크기보다 큰 조각으로 나누지 않음(나머지는 continuation 프레임)
+const MAX_FRAME_PAYLOAD: usize = 16 * 1024;
+
+// Close 프레임을 보낸 뒤 상대의 Close 응답을 기다리는 시간
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 이 시간 동안 받은 프레임이 없으면 ping을 보내서 끊긴 커넥션을 찾아냄
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+// 종료 코드(RFC 6455 7.4.1)
+pub const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+pub const CLOSE_INVALID_DATA: u16 = 1007;
+pub const CLOSE_TOO_BIG: u16 = 1009;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl Opcode {
+    fn from_u8(value: u8) -> Option<Opcode> {
+        match value {
+            0x0 => Some(Opcode::Continuation),
+            0x1 => Some(Opcode::Text),
+            0x2 => Some(Opcode::Binary),
+            0x8 => Some(Opcode::Close),
+            0x9 => Some(Opcode::Ping),
+            0xA => Some(Opcode::Pong),
+            _ => None,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Opcode::Continuation => 0x0,
+            Opcode::Text => 0x1,
+            Opcode::Binary => 0x2,
+            Opcode::Close => 0x8,
+            Opcode::Ping => 0x9,
+            Opcode::Pong => 0xA,
+        }
+    }
+
+    fn is_control(self) -> bool {
+        matches!(self, Opcode::Close | Opcode::Ping | Opcode::Pong)
+    }
+}
+
+// 와이어 위의 프레임 하나
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub fin: bool,
+    pub opcode: Opcode,
+    pub mask: Option<[u8; 4]>,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(fin: bool, opcode: Opcode, payload: Vec<u8>) -> Frame {
+        Frame {
+            fin,
+            opcode,
+            mask: None,
+            payload,
+        }
+    }
+
+    // 프레임을 바이트로 변환, mask가 있으면 페이로드를 마스킹(클라이언트가 보내는 프레임)
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.payload.len() + 14);
+        bytes.push(if self.fin { 0x80 } else { 0 } | self.opcode.as_u8());
+
+        let mask_bit = if self.mask.is_some() { 0x80 } else { 0 };
+        let len = self.payload.len();
+        if len < 126 {
+            bytes.push(mask_bit | len as u8);
+        } else if len <= u16::MAX as usize {
+            bytes.push(mask_bit | 126);
+            bytes.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            bytes.push(mask_bit | 127);
+            bytes.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+
+        match self.mask {
+            Some(mask) => {
+                bytes.extend_from_slice(&mask);
+                bytes.extend(self.payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+            }
+            None => bytes.extend_from_slice(&self.payload),
+        }
+        bytes
+    }
+
+    // 버퍼 앞부분에서 프레임 하나를 파싱
+    // 아직 프레임이 다 오지 않았으면 None, 프레임과 사용한 바이트 수를 리턴하고, 잘못된 프레임이면 종료 코드
+    pub fn parse(buf: &[u8], max_payload: usize) -> Result<Option<(Frame, usize)>, u16> {
+        if buf.len() < 2 {
+            return Ok(None);
+        }
+        let fin = buf[0] & 0x80 != 0;
+        // 확장을 협상하지 않았으므로 RSV 비트는 0이어야 함
+        if buf[0] & 0x70 != 0 {
+            return Err(CLOSE_PROTOCOL_ERROR);
+        }
+        let opcode = Opcode::from_u8(buf[0] & 0x0F).ok_or(CLOSE_PROTOCOL_ERROR)?;
+        let masked = buf[1] & 0x80 != 0;
+
+        let (len, mut offset) = match buf[1] & 0x7F {
+            126 if buf.len() < 4 => return Ok(None),
+            126 => (u16::from_be_bytes([buf[2], buf[3]]) as u64, 4),
+            127 if buf.len() < 10 => return Ok(None),
+            127 => (u64::from_be_bytes(buf[2..10].try_into().unwrap()), 10),
+            len => (len as u64, 2),
+        };
+        // 제어 프레임은 쪼갤 수 없고 페이로드가 125바이트 이하
+        if opcode.is_control() && (!fin || len > 125) {
+            return Err(CLOSE_PROTOCOL_ERROR);
+        }
+        if len > max_payload as u64 {
+            return Err(CLOSE_TOO_BIG);
+        }
+        let len = len as usize;
+
+        let mask = if masked {
+            if buf.len() < offset + 4 {
+                return Ok(None);
+            }
+            let mask: [u8; 4] = buf[offset..offset + 4].try_into().unwrap();
+            offset += 4;
+            Some(mask)
+        } else {
+            None
+        };
+        if buf.len() < offset + len {
+            return Ok(None);
+        }
+
+        let mut payload = buf[offset..offset + len].to_vec();
+        if let Some(mask) = mask {
+            for (i, b) in payload.iter_mut().enumerate() {
+                *b ^= mask[i % 4];
+            }
+        }
+        let frame = Frame {
+            fin,
+            opcode,
+            mask,
+            payload,
+        };
+        Ok(Some((frame, offset + len)))
+    }
+}
+
+// 조각을 합친 메시지
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close(Option<(u16, String)>),
+}
+
+impl Message {
+    // 메시지를 서버가 보낼 프레임들로 변환(큰 데이터 메시지는 여러 프레임으로 나눔)
+    pub fn into_frames(self) -> Vec<Frame> {
+        let (opcode, payload) = match self {
+            Message::Text(text) => (Opcode::Text, text.into_bytes()),
+            Message::Binary(data) => (Opcode::Binary, data),
+            Message::Ping(data) => return vec![Frame::new(true, Opcode::Ping, data)],
+            Message::Pong(data) => return vec![Frame::new(true, Opcode::Pong, data)],
+            Message::Close(None) => return vec![Frame::new(true, Opcode::Close, Vec::new())],
+            Message::Close(Some((code, reason))) => {
+                let mut payload = code.to_be_bytes().to_vec();
+                payload.extend_from_slice(reason.as_bytes());
+                return vec![Frame::new(true, Opcode::Close, payload)];
+            }
+        };
+        if payload.len() <= MAX_FRAME_PAYLOAD {
+            return vec![Frame::new(true, opcode, payload)];
+        }
+        let chunks: Vec<&[u8]> = payload.chunks(MAX_FRAME_PAYLOAD).collect();
+        let last = chunks.len() - 1;
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                let opcode = if i == 0 { opcode } else { Opcode::Continuation };
+                Frame::new(i == last, opcode, chunk.to_vec())
+            })
+            .collect()
+    }
+}
+
+// 핸들러와 다른 스레드가 메시지를 보낼 때 사용하는 핸들(복제해서 여러 스레드에서 사용 가능)
+#[derive(Clone)]
+pub struct WebSocketSender(Sender<Message>);
+
+impl WebSocketSender {
+    // 커넥션이 이미 닫혔으면 false
+    pub fn send(&self, message: Message) -> bool {
+        self.0.send(message).is_ok()
+    }
+
+    pub fn send_text(&self, text: impl Into<String>) -> bool {
+        self.send(Message::Text(text.into()))
+    }
+}
+
+// 커넥션마다 하나씩 만들어지는 웹소켓 핸들러
+// 모든 콜백은 커넥션 전용 스레드에서 호출되므로 오래 걸리는 작업을 해도 프레임 송수신이 멈추지 않음
+pub trait WebSocketHandler: Send {
+    fn on_open(&mut self, _socket: &WebSocketSender) {}
+
+    // 텍스트와 바이너리 메시지만 전달(ping/pong/close는 서버가 처리)
+    fn on_message(&mut self, socket: &WebSocketSender, message: Message);
+
+    // 받은 메시지가 없을 때 IDLE_INTERVAL마다 호출(서버가 먼저 보내는 푸시에 사용)
+    fn on_idle(&mut self, _socket: &WebSocketSender) {}
+
+    fn on_close(&mut self) {}
+}
+
+// 핸드셰이크 키에 대한 Sec-WebSocket-Accept 값
+pub fn accept_key(key: &str) -> String {
+    let mut sha1 = Sha1::new();
+    sha1.update(key.as_bytes());
+    sha1.update(WEBSOCKET_GUID.as_bytes());
+    BASE64_STANDARD.encode(sha1.finalize())
+}
+
+// 웹소켓 핸드셰이크 요청을 검사해서 101 응답과 커넥션을 넘겨받을 훅을 리턴
+// 잘못된 요청이면 400, 지원하지 않는 버전이면 426으로 응답
+pub fn accept<'a>(
+    req: &HttpRequest,
+    handler: Box<dyn WebSocketHandler>,
+    max_message_bytes: usize,
+) -> Reply<'a> {
+    let header = |name: &str| {
+        req.headers
+            .iter()
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.trim())
+    };
+    let bad_request = || HttpResponse::new("400", None, Some(String::new())).into();
+
+    if req.method != Method::Get || requested_upgrade(req).as_deref() != Some("websocket") {
+        return bad_request();
+    }
+    if header("Sec-WebSocket-Version") != Some("13") {
+        let mut resp = HttpResponse::new("426", None, Some(String::new()));
+        resp.add_header("Sec-WebSocket-Version", "13");
+        return resp.into();
+    }
+    // 키는 base64로 인코딩된 16바이트 난수
+    let key = match header("Sec-WebSocket-Key") {
+        Some(key) if BASE64_STANDARD.decode(key).is_ok_and(|k| k.len() == 16) => key,
+        _ => return bad_request(),
+    };
+
+    let mut headers = HashMap::new();
+    headers.insert("Upgrade", "websocket");
+    headers.insert("Connection", "Upgrade");
+    let mut response = HttpResponse::new("101", Some(headers), None);
+    response.add_header("Sec-WebSocket-Accept", accept_key(key));
+
+    Reply {
+        response,
+        body_stream: None,
+        upgrade: Some(Box::new(move |stream: &mut dyn TimeoutStream, pending: Vec<u8>| {
+            run(stream, pending, handler, max_message_bytes)
+        })),
+    }
+}
+
+// 업그레이드된 커넥션에서 프레임을 주고받음
+// 이 함수를 실행하는 워커 스레드는 소켓 입출력만 담당하고, 핸들러 콜백은 전용 스레드에서 실행
+pub fn run(
+    stream: &mut dyn TimeoutStream,
+    mut pending: Vec<u8>,
+    mut handler: Box<dyn WebSocketHandler>,
+    max_message_bytes: usize,
+) {
+    let (outgoing_tx, outgoing_rx) = mpsc::channel::<Message>();
+    let (incoming_tx, incoming_rx) = mpsc::channel::<Message>();
+    let sender = WebSocketSender(outgoing_tx);
+
+    let handler_thread = thread::spawn(move || {
+        handler.on_open(&sender);
+        loop {
+            match incoming_rx.recv_timeout(IDLE_INTERVAL) {
+                Ok(message) => handler.on_message(&sender, message),
+                Err(RecvTimeoutError::Timeout) => handler.on_idle(&sender),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        handler.on_close();
+    });
+
+    let _ = stream.set_read_timeout(Some(POLL_INTERVAL));
+    let mut connection = Connection {
+        stream,
+        incoming: incoming_tx,
+        fragments: None,
+        max_message_bytes,
+        close_sent: None,
+    };
+    let mut chunk = [0; 4096];
+    let mut last_received = Instant::now();
+    'conn: loop {
+        // 핸들러가 보낸 메시지를 먼저 전송
+        while let Ok(message) = outgoing_rx.try_recv() {
+            if connection.send(message).is_err() {
+                break 'conn;
+            }
+        }
+
+        // 받은 바이트에서 완성된 프레임을 모두 처리
+        loop {
+            match Frame::parse(&pending, max_message_bytes) {
+                Ok(Some((frame, used))) => {
+                    pending.drain(..used);
+                    if !connection.handle(frame) {
+                        break 'conn;
+                    }
+                }
+                Ok(None) => break,
+                Err(code) => {
+                    let _ = connection.send(Message::Close(Some((code, String::new()))));
+                    break 'conn;
+                }
+            }
+        }
+
+        // Close를 보낸 뒤 상대가 응답하지 않으면 기다리지 않고 닫음
+        if connection.close_sent.is_some_and(|sent| sent.elapsed() > CLOSE_TIMEOUT) {
+            break;
+        }
+        if last_received.elapsed() > PING_INTERVAL {
+            last_received = Instant::now();
+            if connection.send(Message::Ping(Vec::new())).is_err() {
+                break;
+            }
+        }
+
+        match connection.stream.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => {
+                last_received = Instant::now();
+                pending.extend_from_slice(&chunk[..n]);
+            }
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+
+    // 핸들러 스레드에 커넥션 종료를 알리고 끝날 때까지 기다림
+    drop(connection);
+    let _ = handler_thread.join();
+}
+
+// 커넥션 하나의 송수신 상태
+struct Connection<'s> {
+    stream: &'s mut dyn TimeoutStream,
+    incoming: Sender<Message>,
+    // 조각난 메시지의 첫 프레임 opcode와 지금까지 받은 페이로드
+    fragments: Option<(Opcode, Vec<u8>)>,
+    max_message_bytes: usize,
+    close_sent: Option<Instant>,
+}
+
+impl Connection<'_> {
+    fn send(&mut self, message: Message) -> io::Result<()> {
+        if self.close_sent.is_some() {
+            return Ok(());
+        }
+        if matches!(message, Message::Close(_)) {
+            self.close_sent = Some(Instant::now());
+        }
+        for frame in message.into_frames() {
+            self.stream.write_all(&frame.encode())?;
+        }
+        self.stream.flush()
+    }
+
+    // 프레임 하나를 처리하고, 커넥션을 닫아야 하면 false를 리턴
+    fn handle(&mut self, frame: Frame) -> bool {
+        // 클라이언트가 보내는 프레임은 반드시 마스킹되어야 함
+        if frame.mask.is_none() {
+            return self.fail(CLOSE_PROTOCOL_ERROR);
+        }
+
+        match frame.opcode {
+            Opcode::Ping => self.send(Message::Pong(frame.payload)).is_ok(),
+            Opcode::Pong => true,
+            Opcode::Close => {
+                // 상대가 먼저 닫으면 받은 종료 코드로 응답하고 닫음
+                let code = match frame.payload.len() {
+                    0 => None,
+                    1 => return self.fail(CLOSE_PROTOCOL_ERROR),
+                    _ => Some(u16::from_be_bytes([frame.payload[0], frame.payload[1]])),
+                };
+                let _ = self.send(Message::Close(code.map(|code| (code, String::new()))));
+                false
+            }
+            Opcode::Text | Opcode::Binary if self.fragments.is_some() => self.fail(CLOSE_PROTOCOL_ERROR),
+            Opcode::Text | Opcode::Binary if frame.fin => self.deliver(frame.opcode, frame.payload),
+            Opcode::Text | Opcode::Binary => {
+                self.fragments = Some((frame.opcode, frame.payload));
+                true
+            }
+            Opcode::Continuation => {
+                let Some((opcode, mut payload)) = self.fragments.take() else {
+                    return self.fail(CLOSE_PROTOCOL_ERROR);
+                };
+                if payload.len() + frame.payload.len() > self.max_message_bytes {
+                    return self.fail(CLOSE_TOO_BIG);
+                }
+                payload.extend_from_slice(&frame.payload);
+                if frame.fin {
+                    self.deliver(opcode, payload)
+                } else {
+                    self.fragments = Some((opcode, payload));
+                    true
+                }
+            }
+        }
+    }
+
+    fn deliver(&mut self, opcode: Opcode, payload: Vec<u8>) -> bool {
+        let message = match opcode {
+            Opcode::Text => match String::from_utf8(payload) {
+                Ok(text) => Message::Text(text),
+                Err(_) => return self.fail(CLOSE_INVALID_DATA),
+            },
+            _ => Message::Binary(payload),
+        };
+        let _ = self.incoming.send(message);
+        true
+    }
+
+    fn fail(&mut self, code: u16) -> bool {
+        let _ = self.send(Message::Close(Some((code, String::new()))));
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    const CLOSE_NORMAL: u16 = 1000;
+
+    // 받은 메시지를 그대로 돌려주고, "bye"를 받으면 커넥션을 닫는 핸들러
+    struct Echo;
+
+    impl WebSocketHandler for Echo {
+        fn on_open(&mut self, socket: &WebSocketSender) {
+            socket.send_text("hello");
+        }
+
+        fn on_message(&mut self, socket: &WebSocketSender, message: Message) {
+            if message == Message::Text("bye".to_string()) {
+                socket.send(Message::Close(Some((CLOSE_NORMAL, "bye".to_string()))));
+            } else {
+                socket.send(message);
+            }
+        }
+    }
+
+    fn masked(fin: bool, opcode: Opcode, payload: &[u8]) -> Vec<u8> {
+        Frame {
+            fin,
+            opcode,
+            mask: Some([1, 2, 3, 4]),
+            payload: payload.to_vec(),
+        }
+        .encode()
+    }
+
+    // 클라이언트 쪽에서 서버가 보낸 프레임을 하나 읽음
+    fn read_frame(stream: &mut TcpStream, buf: &mut Vec<u8>) -> Frame {
+        loop {
+            if let Some((frame, used)) = Frame::parse(buf, 1 << 20).unwrap() {
+                buf.drain(..used);
+                return frame;
+            }
+            let mut chunk = [0; 1024];
+            let n = stream.read(&mut chunk).unwrap();
+            assert!(n > 0, "connection closed");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    // 서버 쪽에서 run()을 실행하는 TCP 커넥션 쌍
+    fn connect(max_message_bytes: usize) -> (TcpStream, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        let server = thread::spawn(move || run(&mut stream, Vec::new(), Box::new(Echo), max_message_bytes));
+        (client, server)
+    }
+
+    #[test]
+    fn test_accept_key_matches_rfc_example() {
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn test_handshake_validation() {
+        let request = |extra: &str| -> HttpRequest {
+            format!(
+                "GET /ws HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n{}\r\n",
+                extra
+            )
+            .into()
+        };
+
+        let req = request("Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n");
+        let reply = accept(&req, Box::new(Echo), 1024);
+        assert_eq!(reply.response.status_code(), "101");
+        assert_eq!(reply.response.header("Sec-WebSocket-Accept"), Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+        assert!(reply.upgrade.is_some());
+
+        let req = request("Sec-WebSocket-Version: 8\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n");
+        let reply = accept(&req, Box::new(Echo), 1024);
+        assert_eq!(reply.response.status_code(), "426");
+        assert_eq!(reply.response.header("Sec-WebSocket-Version"), Some("13"));
+
+        let req = request("Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: short\r\n");
+        assert_eq!(accept(&req, Box::new(Echo), 1024).response.status_code(), "400");
+    }
+
+    #[test]
+    fn test_frame_lengths_round_trip() {
+        for len in [0, 125, 126, 65535, 65536] {
+            let frame = Frame {
+                fin: true,
+                opcode: Opcode::Binary,
+                mask: Some([9, 8, 7, 6]),
+                payload: (0..len).map(|i| i as u8).collect(),
+            };
+            let bytes = frame.encode();
+            assert_eq!(Frame::parse(&bytes, 1 << 20), Ok(Some((frame, bytes.len()))));
+            assert_eq!(Frame::parse(&bytes[..bytes.len() - 1], 1 << 20), Ok(None));
+        }
+        let bytes = Frame::new(true, Opcode::Text, vec![0; 200]).encode();
+        assert_eq!(Frame::parse(&bytes, 100), Err(CLOSE_TOO_BIG));
+        let ping = Frame::new(false, Opcode::Ping, vec![]).encode();
+        assert_eq!(Frame::parse(&ping, 100), Err(CLOSE_PROTOCOL_ERROR));
+    }
+
+    #[test]
+    fn test_fragmented_message_ping_and_close() {
+        let (mut client, server) = connect(1024);
+        let mut buf = Vec::new();
+        assert_eq!(read_frame(&mut client, &mut buf).payload, b"hello");
+
+        // 조각난 텍스트 메시지 사이에 끼어든 ping에는 바로 pong으로 응답
+        let mut bytes = masked(false, Opcode::Text, b"frag");
+        bytes.extend(masked(true, Opcode::Ping, b"p"));
+        bytes.extend(masked(false, Opcode::Continuation, b"men"));
+        bytes.extend(masked(true, Opcode::Continuation, b"ted"));
+        client.write_all(&bytes).unwrap();
+
+        let pong = read_frame(&mut client, &mut buf);
+        assert_eq!((pong.opcode, pong.payload.as_slice()), (Opcode::Pong, &b"p"[..]));
+        let echo = read_frame(&mut client, &mut buf);
+        assert_eq!((echo.opcode, echo.fin, echo.mask), (Opcode::Text, true, None));
+        assert_eq!(echo.payload, b"fragmented");
+
+        // 핸들러가 닫으면 Close 프레임을 보내고 상대의 Close를 받은 뒤 종료
+        client.write_all(&masked(true, Opcode::Text, b"bye")).unwrap();
+        let close = read_frame(&mut client, &mut buf);
+        assert_eq!(close.opcode, Opcode::Close);
+        assert_eq!(&close.payload[..2], &CLOSE_NORMAL.to_be_bytes());
+        client.write_all(&masked(true, Opcode::Close, &CLOSE_NORMAL.to_be_bytes())).unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_protocol_errors_close_connection() {
+        // 마스킹하지 않은 클라이언트 프레임
+        let (mut client, server) = connect(1024);
+        let mut buf = Vec::new();
+        read_frame(&mut client, &mut buf);
+        client.write_all(&Frame::new(true, Opcode::Text, b"x".to_vec()).encode()).unwrap();
+        let close = read_frame(&mut client, &mut buf);
+        assert_eq!(&close.payload[..2], &CLOSE_PROTOCOL_ERROR.to_be_bytes());
+        server.join().unwrap();
+
+        // 조각을 합친 메시지가 한도를 넘음
+        let (mut client, server) = connect(4);
+        let mut buf = Vec::new();
+        read_frame(&mut client, &mut buf);
+        let mut bytes = masked(false, Opcode::Binary, b"abc");
+        bytes.extend(masked(true, Opcode::Continuation, b"de"));
+        client.write_all(&bytes).unwrap();
+        let close = read_frame(&mut client, &mut buf);
+        assert_eq!(&close.payload[..2], &CLOSE_TOO_BIG.to_be_bytes());
+        server.join().unwrap();
+
+        // UTF-8이 아닌 텍스트 메시지
+        let (mut client, server) = connect(1024);
+        let mut buf = Vec::new();
+        read_frame(&mut client, &mut buf);
+        client.write_all(&masked(true, Opcode::Text, &[0xFF, 0xFE])).unwrap();
+        let close = read_frame(&mut client, &mut buf);
+        assert_eq!(&close.payload[..2], &CLOSE_INVALID_DATA.to_be_bytes());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_large_messages_are_fragmented() {
+        let frames = Message::Binary(vec![7; MAX_FRAME_PAYLOAD * 2 + 1]).into_frames();
+        let shape: Vec<(Opcode, bool, usize)> =
+            frames.iter().map(|f| (f.opcode, f.fin, f.payload.len())).collect();
+        assert_eq!(
+            shape,
+            vec![
+                (Opcode::Binary, false, MAX_FRAME_PAYLOAD),
+                (Opcode::Continuation, false, MAX_FRAME_PAYLOAD),
+                (Opcode::Continuation, true, 1),
+            ]
+        );
+    }
+}
@@ -7,12 +7,12 @@ fn main() {
     let mut stream = TcpStream::connect("localhost:3000").unwrap();
 
     // Hello 라는 메시지를 TCP 서버 커넥션에 작성
-    stream.write("Hello".as_bytes()).unwrap();
+    stream.write_all("Hello".as_bytes()).unwrap();
 
     let mut buffer = [0; 5];
 
     // 서버로부터 수신된 바이트 읽음
-    stream.read(&mut buffer).unwrap();
+    stream.read_exact(&mut buffer).unwrap();
     println!(
         "Got response from server:{:?}",
         str::from_utf8(&buffer).unwrap()
@@ -19,7 +19,7 @@ fn main() {
         println!("Connection established");
 
         let mut buffer = [0; 1024];
-        stream.read(&mut buffer).unwrap(); // 유입되는 스트림에서 읽기
-        stream.write(&mut buffer).unwrap(); // 받은 데이터를 같은 커넥션을 통해 클라이언트에게 전송
+        let bytes_read = stream.read(&mut buffer).unwrap(); // 유입되는 스트림에서 읽기
+        stream.write_all(&buffer[..bytes_read]).unwrap(); // 받은 데이터를 같은 커넥션을 통해 클라이언트에게 전송
     }
 }